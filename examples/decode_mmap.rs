@@ -0,0 +1,114 @@
+use std::{env, fs, io};
+
+use v4l::buffer::Type;
+use v4l::event::{Event, SourceChanges, Type as EventType};
+use v4l::format::description::Flags;
+use v4l::io::is_source_change;
+use v4l::io::traits::{CaptureStream, OutputStream};
+use v4l::prelude::*;
+use v4l::video::{Capture, Output};
+
+/// Feeds the next part of the bitstream to the decoder, returns false once it is exhausted
+fn feed(stream: &mut MmapStream, bitstream: &[u8], pos: &mut usize) -> io::Result<bool> {
+    if *pos >= bitstream.len() {
+        return Ok(false);
+    }
+
+    // The buffer is handed to the decoder on the next call. Decoders which do not report
+    // CONTINUOUS_BITSTREAM expect exactly one frame per buffer, which requires a bitstream
+    // parser. This skeleton simply fills the buffers.
+    let (buf, meta) = OutputStream::next(stream)?;
+    let len = (bitstream.len() - *pos).min(buf.len());
+    buf[..len].copy_from_slice(&bitstream[*pos..*pos + len]);
+    meta.bytesused = len as u32;
+    *pos += len;
+    Ok(true)
+}
+
+fn main() -> io::Result<()> {
+    let mut args = env::args().skip(1);
+    let (path, input) = match (args.next(), args.next()) {
+        (Some(path), Some(input)) => (path, input),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "usage: decode_mmap <device> <bitstream>",
+            ))
+        }
+    };
+    println!("Using device: {}\n", path);
+
+    let bitstream = fs::read(&input)?;
+    let dev = Device::with_path(&path)?;
+
+    // 1. Select a coded format whose resolution is parsed from the bitstream
+    let desc = Output::enum_formats(&dev)?
+        .into_iter()
+        .find(|desc| {
+            desc.flags
+                .contains(Flags::COMPRESSED | Flags::DYN_RESOLUTION)
+        })
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                "device does not offer coded formats with dynamic resolution",
+            )
+        })?;
+    println!("Coded format:\n{}", desc);
+
+    let mut fmt = Output::format(&dev)?;
+    fmt.fourcc = desc.fourcc;
+    Output::set_format(&dev, &fmt)?;
+
+    // 2. Subscribe before streaming so the event cannot be missed
+    dev.subscribe_event(EventType::SourceChange, 0)?;
+
+    // 3. Feed the bitstream until the decoder knows the resolution
+    let mut out_stream = MmapStream::with_buffers(&dev, Type::VideoOutput, 4)?;
+    let mut pos = 0;
+    loop {
+        if let Some(Event::SourceChange { changes, .. }) = dev.try_dequeue_event()? {
+            if changes.contains(SourceChanges::RESOLUTION) {
+                break;
+            }
+        }
+
+        if !feed(&mut out_stream, &bitstream, &mut pos)? {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "bitstream ended before the resolution was known",
+            ));
+        }
+    }
+
+    // 4. The decoded format is known now, so the capture buffers can be allocated
+    println!("Decoded format:\n{}", Capture::format(&dev)?);
+    let mut cap_stream = MmapStream::with_buffers(&dev, Type::VideoCapture, 4)?;
+    cap_stream.watch_source_change()?;
+
+    // 5. Decode until the bitstream is exhausted. Retrieving the frames still held by the
+    // decoder afterwards requires a stop command (V4L2_DEC_CMD_STOP), which is not covered here.
+    let mut frames = 0;
+    while feed(&mut out_stream, &bitstream, &mut pos)? {
+        match CaptureStream::try_next(&mut cap_stream) {
+            Ok(Some((_, meta))) => {
+                frames += 1;
+                println!(
+                    "Frame {}: {} bytes, sequence {}",
+                    frames, meta.bytesused, meta.sequence
+                );
+            }
+            Ok(None) => {}
+            // A real decoder reallocates the capture buffers here (drop the stream and create
+            // a new one for the new format) and continues decoding
+            Err(e) if is_source_change(&e) => {
+                println!("Resolution changed to:\n{}", Capture::format(&dev)?);
+                break;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    println!("\nDecoded {} frames", frames);
+    Ok(())
+}
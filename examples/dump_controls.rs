@@ -0,0 +1,15 @@
+use std::{env, io};
+
+use v4l::prelude::*;
+
+fn main() -> io::Result<()> {
+    let path = env::args()
+        .nth(1)
+        .unwrap_or_else(|| String::from("/dev/video0"));
+    println!("Using device: {}", path);
+
+    let dev = Device::with_path(&path)?;
+    print!("{}", dev.dump_controls()?);
+
+    Ok(())
+}
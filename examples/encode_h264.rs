@@ -0,0 +1,52 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::{env, time::Instant};
+
+use v4l::io::pipeline::CaptureEncode;
+use v4l::prelude::*;
+use v4l::video::Capture;
+
+fn main() -> io::Result<()> {
+    // The Raspberry Pi exposes its hardware H.264 encoder (bcm2835-codec) as /dev/video11
+    let mut args = env::args().skip(1);
+    let camera_path = args.next().unwrap_or_else(|| "/dev/video0".to_string());
+    let encoder_path = args.next().unwrap_or_else(|| "/dev/video11".to_string());
+    let output_path = args.next().unwrap_or_else(|| "capture.h264".to_string());
+    let fourcc: FourCC = args.next().as_deref().unwrap_or("H264").parse()?;
+    println!("Using camera: {}", camera_path);
+    println!("Using encoder: {}", encoder_path);
+    println!("Using coded format: {}\n", fourcc);
+
+    // Encode 100 frames by default
+    let count = 100;
+
+    let camera = Device::with_path(&camera_path)?;
+    println!("Camera format:\n{}", Capture::format(&camera)?);
+
+    let encoder = Device::with_path(&encoder_path)?;
+    println!("Encoder capabilities:\n{}", encoder.query_caps()?);
+
+    let mut pipeline = CaptureEncode::new(&camera, &encoder, fourcc)?;
+    let mut output = File::create(&output_path)?;
+
+    let start = Instant::now();
+    let mut bytes = 0;
+    for _ in 0..count {
+        let (buf, meta) = pipeline.next_encoded()?;
+        output.write_all(buf)?;
+        bytes += buf.len();
+
+        println!("Frame");
+        println!("  sequence  : {}", meta.sequence);
+        println!("  flags     : {}", meta.flags);
+        println!("  length    : {}", buf.len());
+    }
+
+    let secs = start.elapsed().as_secs_f64();
+    println!();
+    println!("FPS: {}", count as f64 / secs);
+    println!("kbit/s: {}", bytes as f64 * 8.0 / 1000.0 / secs);
+    println!("Written to {}", output_path);
+
+    Ok(())
+}
@@ -33,15 +33,15 @@ fn main() -> io::Result<()> {
         params = dev.params()?;
 
         // try RGB3 first
-        format.fourcc = FourCC::new(b"RGB3");
+        format.fourcc = FourCC::RGB24;
         format = dev.set_format(&format)?;
 
-        if format.fourcc != FourCC::new(b"RGB3") {
+        if format.fourcc != FourCC::RGB24 {
             // fallback to Motion-JPEG
-            format.fourcc = FourCC::new(b"MJPG");
+            format.fourcc = FourCC::MJPG;
             format = dev.set_format(&format)?;
 
-            if format.fourcc != FourCC::new(b"MJPG") {
+            if format.fourcc != FourCC::MJPG {
                 return Err(io::Error::new(
                     io::ErrorKind::Other,
                     "neither RGB3 nor MJPG supported by the device, but required by this example!",
@@ -133,9 +133,9 @@ fn main() -> io::Result<()> {
 
         loop {
             let (buf, _) = stream.next().unwrap();
-            let data = match &format.fourcc.repr {
-                b"RGB3" => buf.to_vec(),
-                b"MJPG" => {
+            let data = match format.fourcc {
+                FourCC::RGB24 => buf.to_vec(),
+                FourCC::MJPG => {
                     // Decode the JPEG frame to RGB
                     let mut decoder = jpeg::Decoder::new(buf);
                     decoder.decode().expect("failed to decode JPEG")
@@ -0,0 +1,39 @@
+use std::io;
+
+use v4l::control::Value;
+use v4l::prelude::*;
+use v4l::v4l_sys::*;
+use v4l::Control;
+
+fn main() -> io::Result<()> {
+    let path = "/dev/swradio0";
+    println!("Using device: {}\n", path);
+
+    let dev = Device::with_path(path)?;
+
+    // RTL-SDR style devices: the ADC tuner selects the sampling rate, the RF tuner the center
+    // frequency.
+    let rate = dev.set_sampling_rate(2_048_000)?;
+    println!("Sampling rate    : {} Hz", rate);
+
+    let freq = dev.set_sdr_frequency(100_000_000)?;
+    println!("Center frequency : {} Hz", freq);
+
+    // Let the tuner pick a bandwidth matching the sampling rate
+    let res = dev.set_control(Control {
+        id: V4L2_CID_RF_TUNER_BANDWIDTH_AUTO,
+        value: Value::Boolean(true),
+    });
+    if let Err(e) = res {
+        println!("Automatic bandwidth not supported: {}", e);
+    }
+
+    println!("\nRF tuner controls:");
+    for ctrl in dev.query_controls()? {
+        if ctrl.id & 0x0fff0000 == V4L2_CTRL_CLASS_RF_TUNER {
+            println!("  {}: [{}, {}]", ctrl.name, ctrl.minimum, ctrl.maximum);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::time::Instant;
+
+use v4l::io::traits::CaptureStream;
+use v4l::prelude::*;
+use v4l::video::Capture;
+
+fn main() -> io::Result<()> {
+    let path = "/dev/video0";
+    println!("Using device: {}\n", path);
+
+    // Capture 4 frames by default
+    let count = 4;
+
+    // Allocate 4 buffers by default
+    let buffer_count = 4;
+
+    let dev = Device::with_path(path)?;
+    let format = dev.format()?;
+    let params = dev.params()?;
+    println!("Active format:\n{}", format);
+    println!("Active parameters:\n{}", params);
+
+    // Setup a buffer stream, the dma-bufs could now be imported into e.g. an EGL context
+    let mut stream = DmaBufStream::with_buffers(&dev, BufferType::VideoCapture, buffer_count)?;
+    for buf in stream.buffers() {
+        println!(
+            "Exported buffer {} as fd {}",
+            buf.index,
+            buf.fd().as_raw_fd()
+        );
+    }
+
+    // warmup
+    stream.next()?;
+
+    let start = Instant::now();
+    for _ in 0..count {
+        let (buf, meta) = stream.next()?;
+
+        println!("Buffer");
+        println!("  index     : {}", buf.index);
+        println!("  fd        : {}", buf.fd().as_raw_fd());
+        println!("  sequence  : {}", meta.sequence);
+        println!("  timestamp : {}", meta.timestamp);
+        println!("  flags     : {}", meta.flags);
+        println!("  bytesused : {}", meta.bytesused);
+        println!("  length    : {}", buf.length);
+    }
+
+    println!();
+    println!("FPS: {}", count as f64 / start.elapsed().as_secs_f64());
+
+    Ok(())
+}
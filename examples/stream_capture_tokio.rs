@@ -0,0 +1,40 @@
+use std::io;
+use std::time::Instant;
+
+use v4l::io::tokio::AsyncMmapStream;
+use v4l::prelude::*;
+use v4l::video::Capture;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> io::Result<()> {
+    let path = "/dev/video0";
+    println!("Using device: {}\n", path);
+
+    // Capture 4 frames by default
+    let count = 4;
+
+    // Allocate 4 buffers by default
+    let buffer_count = 4;
+
+    let dev = Device::with_path(path)?;
+    println!("Active format:\n{}", dev.format()?);
+
+    // Setup a buffer stream, waiting for frames does not block the runtime
+    let mut stream = AsyncMmapStream::with_buffers(&dev, BufferType::VideoCapture, buffer_count)?;
+
+    let start = Instant::now();
+    for _ in 0..count {
+        let (buf, meta) = stream.next().await?;
+
+        println!("Buffer");
+        println!("  sequence  : {}", meta.sequence);
+        println!("  timestamp : {}", meta.timestamp);
+        println!("  flags     : {}", meta.flags);
+        println!("  length    : {}", buf.len());
+    }
+
+    println!();
+    println!("FPS: {}", count as f64 / start.elapsed().as_secs_f64());
+
+    Ok(())
+}
@@ -1,7 +1,6 @@
 use std::io;
 use std::time::Instant;
 
-use v4l::buffer::Type;
 use v4l::io::traits::CaptureStream;
 use v4l::prelude::*;
 use v4l::video::Capture;
@@ -23,7 +22,7 @@ fn main() -> io::Result<()> {
     println!("Active parameters:\n{}", params);
 
     // Setup a buffer stream and grab a frame, then print its data
-    let mut stream = UserptrStream::with_buffers(&dev, Type::VideoCapture, buffer_count)?;
+    let mut stream = UserptrStream::with_buffers(&dev, BufferType::VideoCapture, buffer_count)?;
 
     // warmup
     stream.next()?;
@@ -93,6 +93,9 @@ fn main() -> io::Result<()> {
         println!("  length    [out] : {}", buf_out.len());
     }
 
+    // Make sure the sink receives the last frame before the stream is torn down
+    out_stream.drain()?;
+
     println!();
     println!("FPS: {}", count as f64 / start.elapsed().as_secs_f64());
     println!("MB/s: {}", megabytes_ps);
@@ -1,4 +1,6 @@
-use std::fmt;
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime};
+use std::{error, fmt, io, mem};
 
 use crate::timestamp::Timestamp;
 
@@ -8,7 +10,7 @@ use crate::timestamp::Timestamp;
 #[allow(clippy::unreadable_literal)]
 #[rustfmt::skip]
 #[repr(u32)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Type {
     VideoCapture        = 1,
     VideoOutput         = 2,
@@ -29,6 +31,157 @@ pub enum Type {
     Private             = 0x80,
 }
 
+impl Type {
+    /// Returns whether buffers of this type use the multi-planar API
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    ///
+    /// assert!(Type::VideoCaptureMplane.is_mplane());
+    /// assert!(!Type::VideoCapture.is_mplane());
+    /// ```
+    pub fn is_mplane(&self) -> bool {
+        matches!(self, Type::VideoCaptureMplane | Type::VideoOutputMplane)
+    }
+
+    /// Returns whether buffers of this type are passed from the application to the driver
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    ///
+    /// assert!(Type::VideoOutputMplane.is_output());
+    /// assert!(!Type::VideoCapture.is_output());
+    /// ```
+    pub fn is_output(&self) -> bool {
+        matches!(
+            self,
+            Type::VideoOutput
+                | Type::VbiOutput
+                | Type::SlicedVbiOutput
+                | Type::VideoOutputOverlay
+                | Type::VideoOutputMplane
+                | Type::SdrOutput
+                | Type::MetaOutput
+        )
+    }
+}
+
+/// Converts a raw buffer type code
+impl TryFrom<u32> for Type {
+    type Error = UnknownBufferType;
+
+    fn try_from(code: u32) -> Result<Self, Self::Error> {
+        match code {
+            1 => Ok(Self::VideoCapture),
+            2 => Ok(Self::VideoOutput),
+            3 => Ok(Self::VideoOverlay),
+            4 => Ok(Self::VbiCapture),
+            5 => Ok(Self::VbiOutput),
+            6 => Ok(Self::SlicedVbiCapture),
+            7 => Ok(Self::SlicedVbiOutput),
+            8 => Ok(Self::VideoOutputOverlay),
+            9 => Ok(Self::VideoCaptureMplane),
+            10 => Ok(Self::VideoOutputMplane),
+            11 => Ok(Self::SdrCapture),
+            12 => Ok(Self::SdrOutput),
+            13 => Ok(Self::MetaCapture),
+            14 => Ok(Self::MetaOutput),
+            0x80 => Ok(Self::Private),
+            _ => Err(UnknownBufferType(code)),
+        }
+    }
+}
+
+/// Error returned when converting a raw code which does not name a buffer [`Type`]
+///
+/// It converts into an [`io::Error`] of kind [`io::ErrorKind::InvalidData`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownBufferType(pub u32);
+
+impl fmt::Display for UnknownBufferType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown buffer type {}", self.0)
+    }
+}
+
+impl error::Error for UnknownBufferType {}
+
+impl From<UnknownBufferType> for io::Error {
+    fn from(err: UnknownBufferType) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Error returned by format ioctls for buffer types the device does not support
+///
+/// Drivers reject `VIDIOC_G_FMT`, `VIDIOC_S_FMT` and `VIDIOC_TRY_FMT` with `EINVAL` if they do not
+/// implement the requested buffer type, e.g. when asking a webcam for its output format. Since
+/// the format itself is adjusted rather than rejected by these ioctls, `EINVAL` is mapped to this
+/// error. It is wrapped in an [`io::Error`] of kind [`io::ErrorKind::Unsupported`], use
+/// [`is_unsupported_buffer_type`] to detect it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedBufferType(pub Type);
+
+impl fmt::Display for UnsupportedBufferType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "buffer type {:?} is not supported by the device", self.0)
+    }
+}
+
+impl error::Error for UnsupportedBufferType {}
+
+impl From<UnsupportedBufferType> for io::Error {
+    fn from(err: UnsupportedBufferType) -> Self {
+        io::Error::new(io::ErrorKind::Unsupported, err)
+    }
+}
+
+impl UnsupportedBufferType {
+    /// Maps `EINVAL` of a format ioctl to [`UnsupportedBufferType`], other errors are passed on
+    pub(crate) fn map_format_error(buf_type: Type, err: io::Error) -> io::Error {
+        match err.raw_os_error() {
+            Some(libc::EINVAL) => UnsupportedBufferType(buf_type).into(),
+            _ => err,
+        }
+    }
+}
+
+/// Returns whether an error signals an unsupported buffer type (see [`UnsupportedBufferType`])
+///
+/// # Arguments
+///
+/// * `err` - Error returned by a format query
+///
+/// # Example
+///
+/// ```
+/// use std::io;
+/// use v4l::buffer::{is_unsupported_buffer_type, Type, UnsupportedBufferType};
+/// use v4l::prelude::*;
+/// use v4l::video::Output;
+///
+/// let err = io::Error::from(UnsupportedBufferType(Type::VideoOutput));
+/// assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+/// assert!(is_unsupported_buffer_type(&err));
+/// assert!(!is_unsupported_buffer_type(&io::Error::from_raw_os_error(libc::EINVAL)));
+///
+/// // probe which queues a device offers
+/// if let Ok(dev) = Device::new(0) {
+///     match Output::format(&dev) {
+///         Ok(fmt) => println!("output format:\n{}", fmt),
+///         Err(e) if is_unsupported_buffer_type(&e) => println!("no output queue"),
+///         Err(e) => println!("error: {}", e),
+///     }
+/// }
+/// ```
+pub fn is_unsupported_buffer_type(err: &io::Error) -> bool {
+    matches!(err.get_ref(), Some(inner) if inner.is::<UnsupportedBufferType>())
+}
+
 bitflags::bitflags! {
     #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
     pub struct Flags: u32 {
@@ -66,7 +219,7 @@ bitflags::bitflags! {
         const TSTAMP_SRC_MASK       = 0x00070000;
         const TSTAMP_SRC_EOF        = 0x00000000;
         const TSTAMP_SRC_SOE        = 0x00010000;
-        /// mem2mem encoder/decoder
+        /// Last buffer produced by a mem2mem encoder/decoder (end of stream)
         const LAST                  = 0x00100000;
         /// request_fd is valid
         const REQUEST_FD            = 0x00800000;
@@ -97,7 +250,36 @@ impl fmt::Display for Flags {
     }
 }
 
+/// Frame type of a compressed image
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FrameType {
+    /// Keyframe (I-frame), decodable on its own
+    Key,
+    /// Predicted frame, references previous frames
+    P,
+    /// Bidirectionally predicted frame, references previous and following frames
+    B,
+    /// Not reported by the driver, e.g. for uncompressed formats
+    Unknown,
+}
+
+impl fmt::Display for FrameType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameType::Key => write!(f, "keyframe"),
+            FrameType::P => write!(f, "P-frame"),
+            FrameType::B => write!(f, "B-frame"),
+            FrameType::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
 /// Buffer metadata, mostly used not to convolute the main buffer structs
+///
+/// The `length` field of `struct v4l2_buffer` is deliberately not part of this struct: v4l2
+/// overloads it to hold the buffer size in bytes for single-planar buffers, but the number of
+/// planes for multi-planar ones. The size of a buffer is the length of the slice handed out by a
+/// stream, so use that (and `bytesused` for the payload) instead.
 #[derive(Copy, Clone, Default)]
 pub struct Metadata {
     /// Number of bytes occupied by the data in the buffer
@@ -111,3 +293,114 @@ pub struct Metadata {
     /// Sequence number, counting the frames
     pub sequence: u32,
 }
+
+impl Metadata {
+    /// Returns whether this is the last buffer of the stream
+    ///
+    /// Stateful mem2mem codecs signal the end of a stream (e.g. after a drain was requested) by
+    /// setting [`Flags::LAST`] on the last capture buffer they produce. The buffer may be empty,
+    /// so check `bytesused` before processing its data. Any further attempt to dequeue a buffer
+    /// fails with `EPIPE` ([`std::io::ErrorKind::BrokenPipe`]) until the stream is restarted.
+    ///
+    /// A typical decode loop thus looks like this:
+    ///
+    /// 1. Fetch a buffer through `CaptureStream::next`
+    /// 2. Process the first `bytesused` bytes of the buffer
+    /// 3. Stop once `is_last()` returns true
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::{Flags, Metadata};
+    ///
+    /// let meta = Metadata {
+    ///     flags: Flags::LAST,
+    ///     ..Metadata::default()
+    /// };
+    /// assert!(meta.is_last());
+    /// assert!(!Metadata::default().is_last());
+    /// ```
+    pub fn is_last(&self) -> bool {
+        self.flags.contains(Flags::LAST)
+    }
+
+    /// Returns the frame type of a compressed image
+    ///
+    /// Encoders report the type through [`Flags::KEYFRAME`], [`Flags::PFRAME`] and
+    /// [`Flags::BFRAME`]. Recorders can use this to start new files on keyframes. Drivers which
+    /// do not set any of these flags (e.g. most MJPEG webcams) yield [`FrameType::Unknown`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::{Flags, FrameType, Metadata};
+    ///
+    /// // flags of a dequeued H.264 keyframe: DONE | KEYFRAME | TIMESTAMP_MONOTONIC
+    /// let meta = Metadata {
+    ///     flags: Flags::from(0x0000_200c),
+    ///     ..Metadata::default()
+    /// };
+    /// assert_eq!(meta.frame_type(), FrameType::Key);
+    ///
+    /// let meta = Metadata {
+    ///     flags: Flags::DONE | Flags::PFRAME,
+    ///     ..Metadata::default()
+    /// };
+    /// assert_eq!(meta.frame_type(), FrameType::P);
+    /// assert_eq!(Metadata::default().frame_type(), FrameType::Unknown);
+    /// ```
+    pub fn frame_type(&self) -> FrameType {
+        if self.flags.contains(Flags::KEYFRAME) {
+            FrameType::Key
+        } else if self.flags.contains(Flags::PFRAME) {
+            FrameType::P
+        } else if self.flags.contains(Flags::BFRAME) {
+            FrameType::B
+        } else {
+            FrameType::Unknown
+        }
+    }
+
+    /// Returns the wall-clock time of the timestamp or `None` if its clock is not known
+    ///
+    /// V4L2 has no realtime timestamp type: drivers either take timestamps from the monotonic
+    /// clock ([`Flags::TIMESTAMP_MONOTONIC`]), copy them from the output buffers of a mem2mem
+    /// device ([`Flags::TIMESTAMP_COPY`]) or do not specify their clock at all. Only monotonic
+    /// timestamps can be mapped to the wall-clock, which is done using the current offset between
+    /// both clocks. Adjustments of the system time since the frame was captured thus shift the
+    /// result.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    /// use v4l::buffer::{Flags, Metadata};
+    ///
+    /// let meta = Metadata {
+    ///     flags: Flags::TIMESTAMP_MONOTONIC,
+    ///     ..Metadata::default()
+    /// };
+    /// // the monotonic clock starts at boot
+    /// assert!(meta.system_time().unwrap() < SystemTime::now());
+    ///
+    /// let meta = Metadata {
+    ///     flags: Flags::TIMESTAMP_COPY,
+    ///     ..Metadata::default()
+    /// };
+    /// assert_eq!(meta.system_time(), None);
+    /// assert_eq!(Metadata::default().system_time(), None);
+    /// ```
+    pub fn system_time(&self) -> Option<SystemTime> {
+        if self.flags & Flags::TIMESTAMP_MASK != Flags::TIMESTAMP_MONOTONIC {
+            return None;
+        }
+
+        let mut now: libc::timespec = unsafe { mem::zeroed() };
+        if unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) } != 0 {
+            return None;
+        }
+        let now = Duration::new(now.tv_sec as u64, now.tv_nsec as u32);
+
+        SystemTime::now().checked_sub(now.saturating_sub(self.timestamp.as_duration()))
+    }
+}
@@ -4,6 +4,7 @@ use crate::v4l_sys::*;
 
 bitflags::bitflags! {
     #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Flags: u32 {
         const VIDEO_CAPTURE         = 0x00000001;
         const VIDEO_OUTPUT          = 0x00000002;
@@ -62,6 +63,7 @@ impl fmt::Display for Flags {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Device capabilities
 pub struct Capabilities {
     /// Driver name, e.g. uvc for usb video class devices
@@ -73,12 +75,68 @@ pub struct Capabilities {
     /// Version number MAJOR.MINOR.PATCH
     pub version: (u8, u8, u8),
 
-    /// Capability flags
+    /// Capability flags of the opened device node, same as `device_capabilities`
+    ///
+    /// Kept for backwards compatibility, prefer `device_capabilities` in new code.
     pub capabilities: Flags,
+    /// Capability flags of the opened device node
+    pub device_capabilities: Flags,
+    /// Capability flags of the whole physical device, i.e. the union of all its nodes
+    ///
+    /// Split devices (e.g. mem2mem codecs exposing several nodes) report flags here which the
+    /// opened node itself does not offer.
+    pub physical_capabilities: Flags,
+}
+
+impl Capabilities {
+    /// Returns whether the opened device node offers all of the given capabilities
+    ///
+    /// # Arguments
+    ///
+    /// * `flags` - Capabilities to check, e.g. [`Flags::VIDEO_CAPTURE`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::capability::Flags;
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(caps) = dev.query_caps() {
+    ///         if caps.supports(Flags::VIDEO_CAPTURE | Flags::STREAMING) {
+    ///             println!("{} can stream frames", caps.card);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn supports(&self, flags: Flags) -> bool {
+        self.device_capabilities.contains(flags)
+    }
+
+    /// Returns whether buffers can be exchanged through streaming I/O (mmap, userptr, ..)
+    pub fn supports_streaming(&self) -> bool {
+        self.supports(Flags::STREAMING)
+    }
+
+    /// Returns whether frames can be exchanged through the `read()` and `write()` calls
+    ///
+    /// Some simple or virtual devices only support this kind of I/O.
+    pub fn supports_readwrite(&self) -> bool {
+        self.supports(Flags::READ_WRITE)
+    }
 }
 
 impl From<v4l2_capability> for Capabilities {
     fn from(cap: v4l2_capability) -> Self {
+        // Drivers predating per-node capabilities only report those of the physical device
+        let physical_capabilities = Flags::from(cap.capabilities);
+        let device_caps = if physical_capabilities.contains(Flags::DEVICE_CAPS) {
+            Flags::from(cap.device_caps)
+        } else {
+            physical_capabilities
+        };
+
         Self {
             driver: str::from_utf8(&cap.driver)
                 .unwrap()
@@ -97,7 +155,9 @@ impl From<v4l2_capability> for Capabilities {
                 ((cap.version >> 8) & 0xff) as u8,
                 (cap.version & 0xff) as u8,
             ),
-            capabilities: Flags::from(cap.device_caps),
+            capabilities: device_caps,
+            device_capabilities: device_caps,
+            physical_capabilities,
         }
     }
 }
@@ -112,7 +172,8 @@ impl fmt::Display for Capabilities {
             "Version     : {}.{}.{}",
             self.version.0, self.version.1, self.version.2
         )?;
-        writeln!(f, "Capabilities : {}", self.capabilities)?;
+        writeln!(f, "Capabilities : {}", self.device_capabilities)?;
+        writeln!(f, "Physical     : {}", self.physical_capabilities)?;
         Ok(())
     }
 }
@@ -1,6 +1,20 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::capability::Flags as CapabilityFlags;
+use crate::device::Device;
+use crate::format::{Format, FourCC};
+use crate::fraction::Fraction;
+use crate::mode::Mode;
+use crate::video::capture::Parameters;
+use crate::video::Capture;
+
+/// Pixelformats considered by [`open_all_cameras`], in order of preference
+const COMMON_FOURCCS: [&[u8; 4]; 4] = [b"MJPG", b"YUYV", b"NV12", b"UYVY"];
+
+/// Minimum frame rate considered by [`open_all_cameras`]
+const MIN_FPS: u32 = 15;
+
 /// Returns a list of devices currently known to the system
 ///
 /// # Example
@@ -35,6 +49,90 @@ pub fn enum_devices() -> Vec<Node> {
     devices
 }
 
+/// Opens all capture devices and configures each of them with a reasonable default format
+///
+/// Devices are returned in the order of their node index, along with the format which was
+/// applied. The default format is chosen from the supported modes (see
+/// [`Device::supported_modes`]) as follows:
+///
+/// 1. Only common pixelformats are considered: MJPG, YUYV, NV12 and UYVY
+/// 2. Only modes offering at least 15 frames per second are considered
+/// 3. The mode with the highest resolution (in pixels) wins, ties are broken by the order of the
+///    pixelformats above and then by the higher frame rate
+///
+/// The frame rate of the chosen mode is applied too if the device supports that. Nodes which
+/// cannot be opened, are not single-planar video capture devices (e.g. the metadata nodes of
+/// UVC cameras) or offer no such mode are skipped. Since the modes of each device are
+/// enumerated, this may take a while.
+///
+/// # Example
+///
+/// ```
+/// use v4l::context;
+///
+/// for (dev, fmt) in context::open_all_cameras() {
+///     let caps = dev.query_caps().unwrap();
+///     print!("{}: {}", caps.card, fmt);
+/// }
+/// ```
+pub fn open_all_cameras() -> Vec<(Device, Format)> {
+    let mut nodes = enum_devices();
+    nodes.sort_by_key(|node| node.index());
+
+    nodes
+        .iter()
+        .filter_map(|node| {
+            let dev = Device::with_path(node.path()).ok()?;
+            let caps = dev.query_caps().ok()?;
+            if !caps.supports(CapabilityFlags::VIDEO_CAPTURE) {
+                return None;
+            }
+
+            let mode = default_mode(&dev)?;
+            let fmt = dev
+                .set_format(&Format::new(mode.width, mode.height, mode.fourcc))
+                .ok()?;
+            // the frame interval is the inverse of the frame rate
+            let interval = Parameters::new(Fraction::new(mode.fps.denominator, mode.fps.numerator));
+            let _ = dev.set_params(&interval);
+
+            Some((dev, fmt))
+        })
+        .collect()
+}
+
+/// Returns the mode picked by the heuristic documented at [`open_all_cameras`]
+fn default_mode(dev: &Device) -> Option<Mode> {
+    let preference = |mode: &Mode| {
+        COMMON_FOURCCS
+            .iter()
+            .position(|fourcc| FourCC::new(fourcc) == mode.fourcc)
+    };
+    // compares two frame rates given as fractions without rounding
+    let cmp_fps = |a: &Mode, b: &Mode| {
+        (u64::from(a.fps.numerator) * u64::from(b.fps.denominator))
+            .cmp(&(u64::from(b.fps.numerator) * u64::from(a.fps.denominator)))
+    };
+
+    dev.supported_modes()
+        .ok()?
+        .into_iter()
+        .filter(|mode| preference(mode).is_some())
+        .filter(|mode| {
+            mode.fps.denominator > 0
+                && u64::from(mode.fps.numerator)
+                    >= u64::from(MIN_FPS) * u64::from(mode.fps.denominator)
+        })
+        .max_by(|a, b| {
+            let pixels = |mode: &Mode| u64::from(mode.width) * u64::from(mode.height);
+            pixels(a)
+                .cmp(&pixels(b))
+                // a lower position means a more preferred pixelformat
+                .then_with(|| preference(b).cmp(&preference(a)))
+                .then_with(|| cmp_fps(a, b))
+        })
+}
+
 /// Represents a video4linux device node
 pub struct Node {
     /// Device node path
@@ -1,6 +1,7 @@
 use std::convert::{TryFrom, TryInto};
 use std::{ffi, fmt, mem, str};
 
+use crate::rect::Rect;
 use crate::v4l_sys::*;
 
 /// Control data type
@@ -8,6 +9,7 @@ use crate::v4l_sys::*;
 #[rustfmt::skip]
 #[repr(u32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Type {
     Integer         = 1,
     Boolean         = 2,
@@ -24,10 +26,12 @@ pub enum Type {
     U16             = 0x0101,
     U32             = 0x0102,
     Area            = 0x0106,
+    Rect            = 0x0107,
 
     Unknown(u32),
 }
 
+/// Converts a raw control type code, unknown codes are preserved in [`Type::Unknown`]
 impl From<u32> for Type {
     fn from(repr: u32) -> Self {
         match repr {
@@ -45,6 +49,7 @@ impl From<u32> for Type {
             0x0101 => Self::U16,
             0x0102 => Self::U32,
             0x0106 => Self::Area,
+            0x0107 => Self::Rect,
             repr => Self::Unknown(repr),
         }
     }
@@ -67,11 +72,19 @@ impl From<Type> for u32 {
             Type::U16 => 0x0101,
             Type::U32 => 0x0102,
             Type::Area => 0x0106,
+            Type::Rect => 0x0107,
             Type::Unknown(t) => t,
         }
     }
 }
 
+impl Type {
+    /// Returns whether values of this type are passed by pointer (arrays and structs)
+    pub fn is_compound(&self) -> bool {
+        u32::from(*self) >= 0x0100
+    }
+}
+
 impl fmt::Display for Type {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt::Debug::fmt(self, f)
@@ -80,6 +93,7 @@ impl fmt::Display for Type {
 
 bitflags::bitflags! {
     #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Flags: u32 {
         const DISABLED              = 0x0001;
         const GRABBED               = 0x0002;
@@ -117,6 +131,7 @@ impl fmt::Display for Flags {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Device control menu item
 pub enum MenuItem {
     Name(String),
@@ -157,6 +172,7 @@ impl TryFrom<(Type, v4l2_querymenu)> for MenuItem {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Device control description
 pub struct Description {
     /// Control identifier, set by the the application
@@ -177,6 +193,8 @@ pub struct Description {
     pub flags: Flags,
 
     /// Items for menu controls (only valid if typ is a menu type)
+    ///
+    /// This is `None` for menu controls if the driver failed to report any of the items.
     pub items: Option<Vec<(u32, MenuItem)>>,
 }
 
@@ -199,6 +217,51 @@ impl From<v4l2_query_ext_ctrl> for Description {
     }
 }
 
+impl Description {
+    /// Returns the default value, interpreted according to the control type
+    ///
+    /// Boolean controls yield [`Value::Boolean`], integer, menu (the index of the default item)
+    /// and bitmask controls yield [`Value::Integer`]. Buttons and control classes do not have a
+    /// value, neither do string and compound controls have a default which could be represented
+    /// by [`Description::default`], so [`Value::None`] is returned for those.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::control::{Description, Flags, Type, Value};
+    ///
+    /// let mut desc = Description {
+    ///     id: 0x00980918,
+    ///     typ: Type::Boolean,
+    ///     name: String::from("Power Line Frequency, Auto"),
+    ///     minimum: 0,
+    ///     maximum: 1,
+    ///     step: 1,
+    ///     default: 1,
+    ///     flags: Flags::empty(),
+    ///     items: None,
+    /// };
+    /// assert_eq!(desc.default_value(), Value::Boolean(true));
+    ///
+    /// desc.typ = Type::Menu;
+    /// desc.maximum = 3;
+    /// desc.default = 2;
+    /// assert_eq!(desc.default_value(), Value::Integer(2));
+    ///
+    /// desc.typ = Type::Button;
+    /// assert_eq!(desc.default_value(), Value::None);
+    /// ```
+    pub fn default_value(&self) -> Value {
+        match self.typ {
+            Type::Boolean => Value::Boolean(self.default != 0),
+            Type::Integer | Type::Integer64 | Type::Menu | Type::IntegerMenu | Type::Bitmask => {
+                Value::Integer(self.default)
+            }
+            _ => Value::None,
+        }
+    }
+}
+
 impl fmt::Display for Description {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "ID         : {}", self.id)?;
@@ -225,6 +288,39 @@ pub struct Control {
     pub value: Value,
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// Area used by compound controls, e.g. the size of a sensor unit cell
+pub struct Area {
+    /// width in pixels
+    pub width: u32,
+    /// height in pixels
+    pub height: u32,
+}
+
+impl fmt::Display for Area {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+impl From<v4l2_area> for Area {
+    fn from(area: v4l2_area) -> Self {
+        Self {
+            width: area.width,
+            height: area.height,
+        }
+    }
+}
+
+impl From<Area> for v4l2_area {
+    fn from(area: Area) -> Self {
+        Self {
+            width: area.width,
+            height: area.height,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 /// Device control value
 pub enum Value {
@@ -239,6 +335,68 @@ pub enum Value {
     CompoundU16(Vec<u16>),
     CompoundU32(Vec<u32>),
     CompoundPtr(Vec<u8>),
+    /* compound (struct) values */
+    Area(Area),
+    Rect(Rect),
+    /// Raw payload of a struct control, e.g. codec parameters of stateless decoders
+    ///
+    /// Use [`Value::to_struct`] and [`Value::from_struct`] to convert between the payload and the
+    /// matching struct from the bindings (e.g. `v4l2_ctrl_fwht_params`).
+    Struct(Vec<u8>),
+}
+
+impl Value {
+    /// Reinterprets the payload of a [`Value::Struct`] as `T`
+    ///
+    /// Returns `None` if this is not a struct value or the payload size does not match.
+    ///
+    /// # Safety
+    ///
+    /// `T` must be a plain C struct for which any bit pattern is valid, such as the control
+    /// structs of the bindings. The caller is responsible for choosing the struct which
+    /// corresponds to the control type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::control::Value;
+    /// use v4l::v4l_sys::v4l2_area;
+    ///
+    /// let area = v4l2_area {
+    ///     width: 640,
+    ///     height: 480,
+    /// };
+    /// let value = unsafe { Value::from_struct(&area) };
+    /// assert_eq!(value, Value::Struct([640u32.to_ne_bytes(), 480u32.to_ne_bytes()].concat()));
+    ///
+    /// let area = unsafe { value.to_struct::<v4l2_area>() }.unwrap();
+    /// assert_eq!((area.width, area.height), (640, 480));
+    ///
+    /// assert!(unsafe { Value::Integer(1).to_struct::<v4l2_area>() }.is_none());
+    /// ```
+    pub unsafe fn to_struct<T: Copy>(&self) -> Option<T> {
+        match self {
+            Value::Struct(payload) if payload.len() == mem::size_of::<T>() => {
+                Some(std::ptr::read_unaligned(payload.as_ptr() as *const T))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns a [`Value::Struct`] holding the bytes of `val`
+    ///
+    /// # Safety
+    ///
+    /// `T` must be a plain C struct without padding bytes, such as the control structs of the
+    /// bindings.
+    ///
+    /// # Arguments
+    ///
+    /// * `val` - Struct to copy into the payload
+    pub unsafe fn from_struct<T: Copy>(val: &T) -> Self {
+        let bytes = std::slice::from_raw_parts(val as *const T as *const u8, mem::size_of::<T>());
+        Value::Struct(bytes.to_vec())
+    }
 }
 
 impl TryInto<v4l2_control> for Control {
@@ -1,15 +1,39 @@
-use std::convert::TryFrom;
+use std::collections::VecDeque;
+use std::convert::{TryFrom, TryInto};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use std::{io, mem};
 
 use libc;
 
-use crate::capability::Capabilities;
-use crate::control::{self, Control, Description};
+use crate::buffer::{self, Metadata, Type, UnsupportedBufferType};
+use crate::capability::{Capabilities, Flags as CapabilityFlags};
+use crate::control::{self, Area, Control, Description};
+use crate::edid;
+use crate::event::{Event, Type as EventType};
+use crate::format::{AnyFormat, Format, FormatMplane, FourCC};
+use crate::fraction::Fraction;
+use crate::frameinterval::FrameIntervalEnum;
+use crate::framesize::{Discrete, FrameSize, FrameSizeEnum, DEFAULT_DISCRETE_LIMIT};
+use crate::input::Input;
+use crate::io::mmap::Stream as MmapStream;
+use crate::io::traits::CaptureStream;
+use crate::io::SourceChanged;
+use crate::jpeg::JpegCompression;
+use crate::mode::{Mode, MAX_MODES, MAX_MODE_INTERVALS};
+use crate::output::Output;
+use crate::priority::Priority;
+use crate::rect::Rect;
+use crate::selection::{CropCapabilities, Flags as SelectionFlags, Target};
+use crate::standard::{Description as StandardDescription, Standard};
+use crate::timings::BtTimings;
+use crate::tuner::{AudioMode, Tuner};
 use crate::v4l2;
 use crate::v4l2::videodev::v4l2_ext_controls;
 use crate::v4l_sys::*;
+use crate::video::Capture;
 
 /// Linux capture device abstraction
 pub struct Device {
@@ -72,22 +96,1633 @@ impl Device {
         })
     }
 
+    /// Returns a capture device by path, without libv4l format emulation
+    ///
+    /// With the `libv4l` feature, libv4l2 transparently adds emulated formats (flagged as
+    /// [`crate::format::description::Flags::EMULATED`]) and converts frames on the CPU. Devices
+    /// opened by this function only report and deliver the formats natively supported by the
+    /// hardware. Without the `libv4l` feature, this is the same as [`Device::with_path`].
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path (e.g. "/dev/video0")
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    /// let dev = Device::with_path_raw("/dev/video0");
+    /// ```
+    pub fn with_path_raw<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let fd = v4l2::open_raw(&path, libc::O_RDWR | libc::O_NONBLOCK)?;
+
+        Ok(Device {
+            handle: Arc::new(Handle::new(fd)),
+        })
+    }
+
+    /// Returns a device which takes ownership of an already opened file descriptor
+    ///
+    /// This is useful when the device cannot be opened by path, e.g. because the file descriptor
+    /// was handed over by a parent process, a sandbox broker or systemd socket activation. The
+    /// file descriptor must refer to a video4linux device which was opened for reading and
+    /// writing. It should be non-blocking too, since the streams rely on `poll` to wait for
+    /// buffers. The descriptor is validated by querying the device capabilities and closed when
+    /// the device (and all of its streams) are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `fd` - Owned file descriptor of the device
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::fs::OpenOptions;
+    /// use std::os::unix::io::OwnedFd;
+    ///
+    /// use v4l::device::Device;
+    ///
+    /// if let Ok(file) = OpenOptions::new().read(true).write(true).open("/dev/video0") {
+    ///     let dev = Device::from_fd(OwnedFd::from(file));
+    /// }
+    /// ```
+    pub fn from_fd(fd: OwnedFd) -> io::Result<Self> {
+        let dev = Device {
+            handle: Arc::new(Handle::new(fd.into_raw_fd())),
+        };
+        dev.query_caps()?;
+        Ok(dev)
+    }
+
     /// Returns the raw device handle
     pub fn handle(&self) -> Arc<Handle> {
         self.handle.clone()
     }
 
-    /// Returns video4linux framework defined information such as card, driver, etc.
-    pub fn query_caps(&self) -> io::Result<Capabilities> {
+    /// Returns video4linux framework defined information such as card, driver, etc.
+    pub fn query_caps(&self) -> io::Result<Capabilities> {
+        unsafe {
+            let mut v4l2_caps: v4l2_capability = mem::zeroed();
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_QUERYCAP,
+                &mut v4l2_caps as *mut _ as *mut std::os::raw::c_void,
+            )?;
+
+            Ok(Capabilities::from(v4l2_caps))
+        }
+    }
+
+    /// Returns the active format, regardless of whether the device is single- or multi-planar
+    ///
+    /// The buffer type is chosen based on the capability flags of the device. Capture queues are
+    /// preferred over output queues, so for mem2mem devices the format of the capture side is
+    /// returned. Queues which the driver advertises but does not implement (see
+    /// [`UnsupportedBufferType`]) are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(fmt) = dev.format_any() {
+    ///         println!("{}x{} {}", fmt.width(), fmt.height(), fmt.fourcc());
+    ///     }
+    /// }
+    /// ```
+    pub fn format_any(&self) -> io::Result<AnyFormat> {
+        let caps = self.query_caps()?.capabilities;
+        let candidates = [
+            (
+                Type::VideoCapture,
+                CapabilityFlags::VIDEO_CAPTURE | CapabilityFlags::VIDEO_M2M,
+            ),
+            (
+                Type::VideoCaptureMplane,
+                CapabilityFlags::VIDEO_CAPTURE_MPLANE | CapabilityFlags::VIDEO_M2M_MPLANE,
+            ),
+            (Type::VideoOutput, CapabilityFlags::VIDEO_OUTPUT),
+            (
+                Type::VideoOutputMplane,
+                CapabilityFlags::VIDEO_OUTPUT_MPLANE,
+            ),
+        ];
+
+        // Some drivers advertise more queues than they implement, so unsupported buffer types
+        // are skipped rather than treated as errors.
+        for &(typ, _) in candidates
+            .iter()
+            .filter(|(_, flags)| caps.intersects(*flags))
+        {
+            match self.format_of(typ) {
+                Err(e) if buffer::is_unsupported_buffer_type(&e) => continue,
+                res => return res,
+            }
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "device supports neither video capture nor video output",
+        ))
+    }
+
+    /// Returns the active format of the given buffer type
+    fn format_of(&self, typ: Type) -> io::Result<AnyFormat> {
+        unsafe {
+            let mut v4l2_fmt = v4l2_format {
+                type_: typ as u32,
+                ..mem::zeroed()
+            };
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_G_FMT,
+                &mut v4l2_fmt as *mut _ as *mut std::os::raw::c_void,
+            )
+            .map_err(|e| UnsupportedBufferType::map_format_error(typ, e))?;
+
+            match typ {
+                Type::VideoCaptureMplane | Type::VideoOutputMplane => {
+                    Ok(AnyFormat::Multi(FormatMplane::from(v4l2_fmt.fmt.pix_mp)))
+                }
+                _ => Ok(AnyFormat::Single(Format::from(v4l2_fmt.fmt.pix))),
+            }
+        }
+    }
+
+    /// Checks a multi-planar format and returns the format the driver would choose
+    ///
+    /// This runs `VIDIOC_TRY_FMT`, so the state of the device is not changed. Use it to probe
+    /// format parameters of multi-planar queues before committing to one of them. Formats with
+    /// more than [`crate::format::mplane::MAX_PLANES`] planes or flags beyond the first byte
+    /// are rejected with an error of kind [`io::ErrorKind::InvalidInput`].
+    ///
+    /// # Arguments
+    ///
+    /// * `buf_type` - Multi-planar buffer type, e.g. [`Type::VideoCaptureMplane`]
+    /// * `fmt` - Desired format
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::format::FormatMplane;
+    /// use v4l::FourCC;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     let fmt = FormatMplane::new(1920, 1080, FourCC::new(b"NM12"));
+    ///     if let Ok(fmt) = dev.try_format_mplane(Type::VideoCaptureMplane, &fmt) {
+    ///         println!("{}", fmt);
+    ///     }
+    /// }
+    /// ```
+    pub fn try_format_mplane(
+        &self,
+        buf_type: Type,
+        fmt: &FormatMplane,
+    ) -> io::Result<FormatMplane> {
+        if !buf_type.is_mplane() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "buffer type is not multi-planar",
+            ));
+        }
+        fmt.validate()?;
+
+        unsafe {
+            let mut v4l2_fmt = v4l2_format {
+                type_: buf_type as u32,
+                fmt: v4l2_format__bindgen_ty_1 {
+                    pix_mp: fmt.clone().into(),
+                },
+            };
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_TRY_FMT,
+                &mut v4l2_fmt as *mut _ as *mut std::os::raw::c_void,
+            )
+            .map_err(|e| UnsupportedBufferType::map_format_error(buf_type, e))?;
+
+            Ok(FormatMplane::from(v4l2_fmt.fmt.pix_mp))
+        }
+    }
+
+    /// Captures a number of frames and returns their bytes along with their metadata
+    ///
+    /// This sets up an mmap stream in the active format, drops the first few frames while the
+    /// device warms up and then captures `count` frames. Each frame is copied out of the stream
+    /// buffers, so memory usage grows with `count` times the image size of the format (e.g. about
+    /// 4 MiB per 1920x1080 YUYV frame). For longer captures, use a stream directly.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Number of frames to capture
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(frames) = dev.capture_frames(10) {
+    ///         for (buf, meta) in frames {
+    ///             println!("Frame {}: {} bytes", meta.sequence, buf.len());
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn capture_frames(&self, count: usize) -> io::Result<Vec<(Vec<u8>, Metadata)>> {
+        let mut stream = MmapStream::new(self, Type::VideoCapture)?;
+        // many devices deliver dark or garbled frames right after streaming was started
+        stream.set_warmup(3);
+
+        let mut frames = Vec::with_capacity(count);
+        for _ in 0..count {
+            let (buf, meta) = stream.next()?;
+            let len = (meta.bytesused as usize).min(buf.len());
+            frames.push((buf[..len].to_vec(), *meta));
+        }
+
+        Ok(frames)
+    }
+
+    /// Captures a single frame and returns its bytes
+    ///
+    /// This is a convenience function for when you just need a frame without setting up a stream
+    /// yourself. Streaming I/O (mmap) is used if the device supports it, otherwise the frame is
+    /// read through the `read()` interface. Frames are captured in the active format.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(frame) = dev.read_frame() {
+    ///         println!("Captured {} bytes", frame.len());
+    ///     }
+    /// }
+    /// ```
+    pub fn read_frame(&self) -> io::Result<Vec<u8>> {
+        let caps = self.query_caps()?;
+        if caps.supports_streaming() {
+            let mut stream = MmapStream::new(self, Type::VideoCapture)?;
+            let (buf, meta) = stream.next()?;
+            let len = (meta.bytesused as usize).min(buf.len());
+            return Ok(buf[..len].to_vec());
+        }
+
+        if !caps.supports_readwrite() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "device supports neither streaming nor read/write I/O",
+            ));
+        }
+
+        let mut buf = vec![0u8; self.format()?.size as usize];
+        // the device is opened in non-blocking mode, so wait for a frame to become available
+        self.handle.poll(libc::POLLIN, -1)?;
+        let len = io::Read::read(&mut Device::from(self.handle()), &mut buf)?;
+        buf.truncate(len);
+        Ok(buf)
+    }
+
+    /// Returns whether the device can capture frames in the given mode
+    ///
+    /// The format part is checked with [`Capture::try_format`], so the active format of the
+    /// device is not changed. The frame rate is checked against the frame intervals the device
+    /// advertises for the format. For stepwise intervals, the frame rate only has to lie within
+    /// the advertised bounds.
+    ///
+    /// Unsupported combinations yield `Ok(false)`, errors are reserved for failing ioctls.
+    ///
+    /// # Arguments
+    ///
+    /// * `fourcc` - Four character code (pixelformat)
+    /// * `width` - Width in pixels
+    /// * `height` - Height in pixels
+    /// * `fps` - Frames per second
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    /// use v4l::FourCC;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     let supported = dev.supports_mode(FourCC::new(b"MJPG"), 1920, 1080, 30);
+    /// }
+    /// ```
+    pub fn supports_mode(
+        &self,
+        fourcc: FourCC,
+        width: u32,
+        height: u32,
+        fps: u32,
+    ) -> io::Result<bool> {
+        let fmt = self.try_format(&Format::new(width, height, fourcc))?;
+        if fmt.fourcc != fourcc || fmt.width != width || fmt.height != height {
+            return Ok(false);
+        }
+
+        let intervals = match self.enum_frameintervals(fourcc, width, height) {
+            Ok(intervals) => intervals,
+            // EINVAL at index zero means there are no intervals for this combination
+            Err(e) if e.kind() == io::ErrorKind::InvalidInput => return Ok(false),
+            Err(e) => return Err(e),
+        };
+
+        // An interval of n/d seconds per frame matches when n * fps == d.
+        let fps = u64::from(fps);
+        Ok(intervals.iter().any(|interval| match &interval.interval {
+            FrameIntervalEnum::Discrete(frac) => {
+                u64::from(frac.numerator) * fps == u64::from(frac.denominator)
+            }
+            FrameIntervalEnum::Stepwise(stepwise) => {
+                // longer intervals mean lower frame rates
+                u64::from(stepwise.min.numerator) * fps <= u64::from(stepwise.min.denominator)
+                    && u64::from(stepwise.max.numerator) * fps
+                        >= u64::from(stepwise.max.denominator)
+            }
+        }))
+    }
+
+    /// Returns the frame sizes of a pixel format, falling back to the current resolution
+    ///
+    /// Some drivers (often those of simple capture cards) do not implement
+    /// `VIDIOC_ENUM_FRAMESIZES` even though they support formats. Instead of failing like
+    /// [`Capture::enum_framesizes`], this reports the resolution of the active format as the only
+    /// discrete frame size in that case. If the active format uses a different pixel format, the
+    /// resolution is validated with `VIDIOC_TRY_FMT` first; an empty list is returned if the
+    /// driver rejects the pixel format.
+    ///
+    /// # Arguments
+    ///
+    /// * `fourcc` - Pixel format
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    /// use v4l::FourCC;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(sizes) = dev.framesizes(FourCC::new(b"YUYV")) {
+    ///         for size in sizes {
+    ///             println!("{}", size);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn framesizes(&self, fourcc: FourCC) -> io::Result<Vec<FrameSize>> {
+        match self.enum_framesizes(fourcc) {
+            // EINVAL at index zero means the driver cannot enumerate frame sizes at all
+            Err(e) if matches!(e.raw_os_error(), Some(libc::EINVAL | libc::ENOTTY)) => {}
+            res => return res,
+        }
+
+        let mut fmt = self.format()?;
+        if fmt.fourcc != fourcc {
+            fmt = self.try_format(&Format::new(fmt.width, fmt.height, fourcc))?;
+            if fmt.fourcc != fourcc {
+                return Ok(Vec::new());
+            }
+        }
+
+        Ok(vec![FrameSize {
+            index: 0,
+            fourcc,
+            typ: v4l2_frmsizetypes_V4L2_FRMSIZE_TYPE_DISCRETE,
+            size: FrameSizeEnum::Discrete(Discrete {
+                width: fmt.width,
+                height: fmt.height,
+            }),
+        }])
+    }
+
+    /// Returns all capture modes (pixelformat, frame size and frame rate) of the device
+    ///
+    /// This expands the format, frame size and frame interval enumerations into a flat list.
+    /// Stepwise frame sizes are expanded into at most [`DEFAULT_DISCRETE_LIMIT`] discrete sizes
+    /// each, which are then validated with `VIDIOC_TRY_FMT` because drivers may round them.
+    /// Stepwise frame intervals are sampled into at most [`MAX_MODE_INTERVALS`] intervals across
+    /// their whole range. Sizes for which the driver does not enumerate any frame intervals are
+    /// left out.
+    ///
+    /// Note that this is expensive: depending on the driver, it may issue thousands of ioctls.
+    /// The result is capped at [`MAX_MODES`] entries.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(modes) = dev.supported_modes() {
+    ///         for mode in modes {
+    ///             println!("{}", mode);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn supported_modes(&self) -> io::Result<Vec<Mode>> {
+        let mut modes = Vec::new();
+
+        for desc in self.enum_formats()? {
+            let fourcc = desc.fourcc;
+            let sizes = match self.enum_framesizes(fourcc) {
+                Ok(sizes) => sizes,
+                Err(e) if e.kind() == io::ErrorKind::InvalidInput => continue,
+                Err(e) => return Err(e),
+            };
+
+            for size in sizes {
+                let stepwise = matches!(size.size, FrameSizeEnum::Stepwise(_));
+                for discrete in size.size.to_discrete_limited(DEFAULT_DISCRETE_LIMIT).0 {
+                    let (width, height) = (discrete.width, discrete.height);
+                    if stepwise {
+                        let fmt = self.try_format(&Format::new(width, height, fourcc))?;
+                        if fmt.fourcc != fourcc || fmt.width != width || fmt.height != height {
+                            continue;
+                        }
+                    }
+
+                    let intervals = match self.enum_frameintervals(fourcc, width, height) {
+                        Ok(intervals) => intervals,
+                        Err(e) if e.kind() == io::ErrorKind::InvalidInput => continue,
+                        Err(e) => return Err(e),
+                    };
+
+                    for interval in intervals {
+                        let fracs = interval.interval.to_discrete_limited(MAX_MODE_INTERVALS).0;
+                        for frac in fracs {
+                            modes.push(Mode {
+                                fourcc,
+                                width,
+                                height,
+                                fps: Fraction::new(frac.denominator, frac.numerator),
+                            });
+                            if modes.len() >= MAX_MODES {
+                                return Ok(modes);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(modes)
+    }
+
+    /// Returns the rectangle of a selection target
+    ///
+    /// # Arguments
+    ///
+    /// * `buf_type` - Type of the buffers the rectangle applies to (use the single-planar types
+    ///   for multi-planar devices)
+    /// * `target` - Selection target, e.g. the current crop area or its bounds
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::selection::Target;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(rect) = dev.selection(Type::VideoCapture, Target::CropBounds) {
+    ///         println!("Crop bounds: {}", rect);
+    ///     }
+    /// }
+    /// ```
+    pub fn selection(&self, buf_type: Type, target: Target) -> io::Result<Rect> {
+        unsafe {
+            let mut v4l2_sel = v4l2_selection {
+                type_: buf_type as u32,
+                target: target as u32,
+                ..mem::zeroed()
+            };
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_G_SELECTION,
+                &mut v4l2_sel as *mut _ as *mut std::os::raw::c_void,
+            )?;
+
+            Ok(Rect::from(v4l2_sel.r))
+        }
+    }
+
+    /// Sets the rectangle of a selection target and returns the one which was applied
+    ///
+    /// Drivers are free to adjust the rectangle. The flags constrain the direction in which
+    /// they may do so.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf_type` - Type of the buffers the rectangle applies to (use the single-planar types
+    ///   for multi-planar devices)
+    /// * `target` - Selection target, e.g. the crop or compose area
+    /// * `rect` - Requested rectangle
+    /// * `flags` - Constraints for adjusting the rectangle
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::selection::{Flags, Target};
+    /// use v4l::Rect;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     let rect = Rect::new(0, 0, 640, 480);
+    ///     if let Ok(rect) = dev.set_selection(Type::VideoCapture, Target::Crop, rect, Flags::LE) {
+    ///         println!("Active crop: {}", rect);
+    ///     }
+    /// }
+    /// ```
+    pub fn set_selection(
+        &self,
+        buf_type: Type,
+        target: Target,
+        rect: Rect,
+        flags: SelectionFlags,
+    ) -> io::Result<Rect> {
+        unsafe {
+            let mut v4l2_sel = v4l2_selection {
+                type_: buf_type as u32,
+                target: target as u32,
+                flags: flags.into(),
+                r: rect.into(),
+                ..mem::zeroed()
+            };
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_S_SELECTION,
+                &mut v4l2_sel as *mut _ as *mut std::os::raw::c_void,
+            )?;
+
+            Ok(Rect::from(v4l2_sel.r))
+        }
+    }
+
+    /// Returns the cropping capabilities, i.e. the bounds, default area and pixel aspect
+    ///
+    /// # Arguments
+    ///
+    /// * `buf_type` - Type of the buffers the capabilities apply to
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(caps) = dev.cropcap(Type::VideoCapture) {
+    ///         println!("Crop bounds: {}, pixel aspect: {}", caps.bounds, caps.pixel_aspect);
+    ///     }
+    /// }
+    /// ```
+    pub fn cropcap(&self, buf_type: Type) -> io::Result<CropCapabilities> {
+        unsafe {
+            let mut v4l2_cropcap = v4l2_cropcap {
+                type_: buf_type as u32,
+                ..mem::zeroed()
+            };
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_CROPCAP,
+                &mut v4l2_cropcap as *mut _ as *mut std::os::raw::c_void,
+            )?;
+
+            Ok(CropCapabilities::from(v4l2_cropcap))
+        }
+    }
+
+    /// Returns the bounds within which a region of interest (crop rectangle) may be placed
+    ///
+    /// Like [`Device::set_roi`], this uses the selection API and falls back to the crop API
+    /// (`VIDIOC_CROPCAP`) for older drivers.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf_type` - Type of the buffers the rectangle applies to
+    pub fn roi_bounds(&self, buf_type: Type) -> io::Result<Rect> {
+        unsafe {
+            let mut v4l2_sel = v4l2_selection {
+                type_: buf_type as u32,
+                target: V4L2_SEL_TGT_CROP_BOUNDS,
+                ..mem::zeroed()
+            };
+            match v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_G_SELECTION,
+                &mut v4l2_sel as *mut _ as *mut std::os::raw::c_void,
+            ) {
+                Ok(()) => return Ok(Rect::from(v4l2_sel.r)),
+                // ENOTTY means the driver does not implement the selection API at all
+                Err(e) if e.raw_os_error() == Some(libc::ENOTTY) => {}
+                Err(e) => return Err(e),
+            }
+
+            let mut v4l2_cropcap = v4l2_cropcap {
+                type_: buf_type as u32,
+                ..mem::zeroed()
+            };
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_CROPCAP,
+                &mut v4l2_cropcap as *mut _ as *mut std::os::raw::c_void,
+            )?;
+
+            Ok(Rect::from(v4l2_cropcap.bounds))
+        }
+    }
+
+    /// Sets the region of interest (crop rectangle) and returns the one which was applied
+    ///
+    /// The selection API (`VIDIOC_S_SELECTION`) is tried first. Older drivers which only
+    /// implement the deprecated crop API (`VIDIOC_S_CROP`) are handled transparently. Drivers are
+    /// free to adjust the rectangle, so the returned one may differ from the requested one.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf_type` - Type of the buffers the rectangle applies to
+    /// * `rect` - Requested region of interest
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::Rect;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(rect) = dev.set_roi(Type::VideoCapture, Rect::new(0, 0, 640, 480)) {
+    ///         println!("Active ROI: {}", rect);
+    ///     }
+    /// }
+    /// ```
+    pub fn set_roi(&self, buf_type: Type, rect: Rect) -> io::Result<Rect> {
+        unsafe {
+            let mut v4l2_sel = v4l2_selection {
+                type_: buf_type as u32,
+                target: V4L2_SEL_TGT_CROP,
+                r: rect.into(),
+                ..mem::zeroed()
+            };
+            match v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_S_SELECTION,
+                &mut v4l2_sel as *mut _ as *mut std::os::raw::c_void,
+            ) {
+                Ok(()) => return Ok(Rect::from(v4l2_sel.r)),
+                // ENOTTY means the driver does not implement the selection API at all
+                Err(e) if e.raw_os_error() == Some(libc::ENOTTY) => {}
+                Err(e) => return Err(e),
+            }
+
+            let mut v4l2_crop = v4l2_crop {
+                type_: buf_type as u32,
+                c: rect.into(),
+            };
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_S_CROP,
+                &mut v4l2_crop as *mut _ as *mut std::os::raw::c_void,
+            )?;
+
+            // VIDIOC_S_CROP is write-only, so query the rectangle the driver actually applied
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_G_CROP,
+                &mut v4l2_crop as *mut _ as *mut std::os::raw::c_void,
+            )?;
+
+            Ok(Rect::from(v4l2_crop.c))
+        }
+    }
+
+    /// Crops a square from the center of the capture area and returns the applied rectangle
+    ///
+    /// This is handy for inference pipelines where models expect square inputs. The rectangle is
+    /// centered within the crop bounds (see [`Device::roi_bounds`]) and applied with
+    /// [`Device::set_roi`], so the driver may still adjust it.
+    ///
+    /// An error of kind [`io::ErrorKind::InvalidInput`] is returned if the square does not fit
+    /// into the bounds.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Edge length of the square in pixels
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(rect) = dev.set_center_square_crop(224) {
+    ///         println!("Active ROI: {}", rect);
+    ///     }
+    /// }
+    /// ```
+    pub fn set_center_square_crop(&self, size: u32) -> io::Result<Rect> {
+        let bounds = self.roi_bounds(Type::VideoCapture)?;
+        if size > bounds.width || size > bounds.height {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{}x{} square exceeds crop bounds {}", size, size, bounds),
+            ));
+        }
+
+        let rect = Rect::new(
+            bounds.left + ((bounds.width - size) / 2) as i32,
+            bounds.top + ((bounds.height - size) / 2) as i32,
+            size,
+            size,
+        );
+        self.set_roi(Type::VideoCapture, rect)
+    }
+
+    /// Configures an HDMI (or other DV) capture device for the detected signal
+    ///
+    /// HDMI grabbers do not follow the incoming signal on their own. The timings of the signal
+    /// have to be queried and applied explicitly, otherwise frames are captured with the wrong
+    /// resolution. This function does exactly that and returns the resulting format, which can be
+    /// used to allocate buffers.
+    ///
+    /// If no signal is detected, an error of kind [`io::ErrorKind::NotConnected`] is returned.
+    /// Unstable signals yield an error of kind [`io::ErrorKind::WouldBlock`], so you may want to
+    /// retry after a short while.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(fmt) = dev.auto_configure_hdmi() {
+    ///         println!("Signal: {}x{}", fmt.width, fmt.height);
+    ///     }
+    /// }
+    /// ```
+    pub fn auto_configure_hdmi(&self) -> io::Result<Format> {
+        let mut v4l2_timings = v4l2_dv_timings {
+            type_: V4L2_DV_BT_656_1120,
+            __bindgen_anon_1: v4l2_dv_timings__bindgen_ty_1 {
+                bt: self.query_dv_timings()?.into(),
+            },
+        };
+
+        unsafe {
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_S_DV_TIMINGS,
+                &mut v4l2_timings as *mut _ as *mut std::os::raw::c_void,
+            )?;
+        }
+
+        // the driver adjusts the format to the new timings
+        self.format()
+    }
+
+    /// Returns the timings of the signal currently detected by the receiver
+    ///
+    /// Errors are mapped like in [`Device::auto_configure_hdmi`]: `NotConnected` if there is no
+    /// signal and `WouldBlock` if the receiver could not lock to it (yet).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(timings) = dev.query_dv_timings() {
+    ///         println!("{}", timings);
+    ///     }
+    /// }
+    /// ```
+    pub fn query_dv_timings(&self) -> io::Result<BtTimings> {
+        unsafe {
+            let mut v4l2_timings: v4l2_dv_timings = mem::zeroed();
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_QUERY_DV_TIMINGS,
+                &mut v4l2_timings as *mut _ as *mut std::os::raw::c_void,
+            )
+            .map_err(|e| match e.raw_os_error() {
+                Some(libc::ENOLINK) => {
+                    io::Error::new(io::ErrorKind::NotConnected, "no signal detected")
+                }
+                Some(libc::ENOLCK) => io::Error::new(io::ErrorKind::WouldBlock, "signal unstable"),
+                _ => e,
+            })?;
+
+            if v4l2_timings.type_ != V4L2_DV_BT_656_1120 {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "unknown DV timings type",
+                ));
+            }
+
+            Ok(BtTimings::from(v4l2_timings.__bindgen_anon_1.bt))
+        }
+    }
+
+    /// Returns the EDID of a receiver, all blocks concatenated
+    ///
+    /// The result is empty if no EDID is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `pad` - Pad (input) of the receiver, zero for most devices
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    /// use v4l::edid::BLOCK_SIZE;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(edid) = dev.read_edid(0) {
+    ///         println!("EDID of {} blocks", edid.len() / BLOCK_SIZE);
+    ///     }
+    /// }
+    /// ```
+    pub fn read_edid(&self, pad: u32) -> io::Result<Vec<u8>> {
+        unsafe {
+            // Passing zero blocks makes the driver report the number of available blocks
+            let mut v4l2_edid = v4l2_edid {
+                pad,
+                ..mem::zeroed()
+            };
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_G_EDID,
+                &mut v4l2_edid as *mut _ as *mut std::os::raw::c_void,
+            )?;
+            if v4l2_edid.blocks == 0 {
+                return Ok(Vec::new());
+            }
+
+            let mut data = vec![0u8; v4l2_edid.blocks as usize * edid::BLOCK_SIZE];
+            v4l2_edid.start_block = 0;
+            v4l2_edid.edid = data.as_mut_ptr();
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_G_EDID,
+                &mut v4l2_edid as *mut _ as *mut std::os::raw::c_void,
+            )?;
+
+            // the EDID may have shrunk in between
+            data.truncate(v4l2_edid.blocks as usize * edid::BLOCK_SIZE);
+            Ok(data)
+        }
+    }
+
+    /// Sets the EDID of a receiver
+    ///
+    /// The data must consist of whole blocks (see [`edid::verify`]), which is checked before
+    /// the device is accessed. Empty data clears the EDID, which usually makes the receiver
+    /// pull the hotplug detect line low.
+    ///
+    /// # Arguments
+    ///
+    /// * `pad` - Pad (input) of the receiver, zero for most devices
+    /// * `data` - EDID, all blocks concatenated
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     // 100 bytes are not a whole block
+    ///     assert!(dev.write_edid(0, &[0; 100]).is_err());
+    ///
+    ///     if let Ok(edid) = dev.read_edid(0) {
+    ///         dev.write_edid(0, &edid).unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub fn write_edid(&self, pad: u32, data: &[u8]) -> io::Result<()> {
+        edid::verify(data)?;
+
+        unsafe {
+            let mut v4l2_edid = v4l2_edid {
+                pad,
+                blocks: (data.len() / edid::BLOCK_SIZE) as u32,
+                // the driver only reads from the buffer when setting the EDID
+                edid: data.as_ptr() as *mut u8,
+                ..mem::zeroed()
+            };
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_S_EDID,
+                &mut v4l2_edid as *mut _ as *mut std::os::raw::c_void,
+            )
+            .map_err(|e| match e.raw_os_error() {
+                // the driver reports the maximum number of blocks it supports
+                Some(libc::E2BIG) => io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "EDID of {} blocks is too large, the device supports at most {} blocks",
+                        data.len() / edid::BLOCK_SIZE,
+                        v4l2_edid.blocks
+                    ),
+                ),
+                _ => e,
+            })
+        }
+    }
+
+    /// Returns the JPEG compression parameters
+    ///
+    /// Only some (mostly older) drivers implement this interface, newer ones expose the quality
+    /// as a control instead (`V4L2_CID_JPEG_COMPRESSION_QUALITY`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(comp) = dev.jpeg_compression() {
+    ///         println!("{}", comp);
+    ///     }
+    /// }
+    /// ```
+    pub fn jpeg_compression(&self) -> io::Result<JpegCompression> {
+        Ok(JpegCompression::from(self.v4l2_jpegcompression()?))
+    }
+
+    /// Sets the JPEG compression parameters
+    ///
+    /// The application and comment segments configured on the device are preserved. Drivers may
+    /// adjust the parameters, read them back with [`Device::jpeg_compression`] to get the
+    /// actual values.
+    ///
+    /// # Arguments
+    ///
+    /// * `comp` - Compression parameters
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    /// use v4l::jpeg::Markers;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut comp) = dev.jpeg_compression() {
+    ///         // Always include the Huffman and quantization tables
+    ///         comp.markers |= Markers::DHT | Markers::DQT;
+    ///         comp.quality = 90;
+    ///         let res = dev.set_jpeg_compression(&comp);
+    ///     }
+    /// }
+    /// ```
+    pub fn set_jpeg_compression(&self, comp: &JpegCompression) -> io::Result<()> {
+        let mut v4l2_comp = v4l2_jpegcompression {
+            quality: comp.quality,
+            jpeg_markers: comp.markers.into(),
+            ..self.v4l2_jpegcompression()?
+        };
+        unsafe {
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_S_JPEGCOMP,
+                &mut v4l2_comp as *mut _ as *mut std::os::raw::c_void,
+            )
+            .map_err(Self::map_jpegcomp_error)
+        }
+    }
+
+    fn v4l2_jpegcompression(&self) -> io::Result<v4l2_jpegcompression> {
+        unsafe {
+            let mut v4l2_comp: v4l2_jpegcompression = mem::zeroed();
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_G_JPEGCOMP,
+                &mut v4l2_comp as *mut _ as *mut std::os::raw::c_void,
+            )
+            .map_err(Self::map_jpegcomp_error)?;
+
+            Ok(v4l2_comp)
+        }
+    }
+
+    fn map_jpegcomp_error(e: io::Error) -> io::Error {
+        match e.raw_os_error() {
+            Some(libc::ENOTTY) => io::Error::new(
+                io::ErrorKind::Unsupported,
+                "device does not support JPEG compression parameters",
+            ),
+            _ => e,
+        }
+    }
+
+    /// Returns the video inputs of the device
+    ///
+    /// Capture cards often have several physical inputs (HDMI, composite, S-Video, ..), of which
+    /// only one is active at a time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(inputs) = dev.enum_inputs() {
+    ///         for input in inputs {
+    ///             println!("{}: {} ({})", input.index, input.name, input.status);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn enum_inputs(&self) -> io::Result<Vec<Input>> {
+        let mut inputs = Vec::new();
+        let mut v4l2_input: v4l2_input = unsafe { mem::zeroed() };
+
+        loop {
+            let ret = unsafe {
+                v4l2::ioctl(
+                    self.handle().fd(),
+                    v4l2::vidioc::VIDIOC_ENUMINPUT,
+                    &mut v4l2_input as *mut _ as *mut std::os::raw::c_void,
+                )
+            };
+
+            match ret {
+                Ok(()) => inputs.push(Input::from(v4l2_input)),
+                // EINVAL marks the end of the list, ENOTTY means there are no inputs at all
+                Err(e) if matches!(e.raw_os_error(), Some(libc::EINVAL | libc::ENOTTY)) => {
+                    return Ok(inputs)
+                }
+                Err(e) => return Err(e),
+            }
+
+            v4l2_input = v4l2_input {
+                index: v4l2_input.index + 1,
+                ..unsafe { mem::zeroed() }
+            };
+        }
+    }
+
+    /// Returns the index of the active video input
+    pub fn input(&self) -> io::Result<usize> {
+        let mut index: std::os::raw::c_int = 0;
+        unsafe {
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_G_INPUT,
+                &mut index as *mut _ as *mut std::os::raw::c_void,
+            )?;
+        }
+
+        Ok(index as usize)
+    }
+
+    /// Selects the active video input
+    ///
+    /// Switching inputs may change the current format and video standard. Drivers refuse to
+    /// switch with `EBUSY` while streaming.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the input, as reported by [`Device::enum_inputs`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     let res = dev.set_input(1);
+    /// }
+    /// ```
+    pub fn set_input(&self, index: usize) -> io::Result<()> {
+        let mut index: std::os::raw::c_int = index
+            .try_into()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "input index out of range"))?;
+        unsafe {
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_S_INPUT,
+                &mut index as *mut _ as *mut std::os::raw::c_void,
+            )
+        }
+    }
+
+    /// Returns the video outputs of the device
+    ///
+    /// Output devices such as encoder cards may drive several physical outputs, of which only
+    /// one is active at a time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(outputs) = dev.enum_outputs() {
+    ///         for output in outputs {
+    ///             println!("{}: {} ({})", output.index, output.name, output.typ);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn enum_outputs(&self) -> io::Result<Vec<Output>> {
+        let mut outputs = Vec::new();
+        let mut v4l2_output: v4l2_output = unsafe { mem::zeroed() };
+
+        loop {
+            let ret = unsafe {
+                v4l2::ioctl(
+                    self.handle().fd(),
+                    v4l2::vidioc::VIDIOC_ENUMOUTPUT,
+                    &mut v4l2_output as *mut _ as *mut std::os::raw::c_void,
+                )
+            };
+
+            match ret {
+                Ok(()) => outputs.push(Output::from(v4l2_output)),
+                // EINVAL marks the end of the list, ENOTTY means there are no outputs at all
+                Err(e) if matches!(e.raw_os_error(), Some(libc::EINVAL | libc::ENOTTY)) => {
+                    return Ok(outputs)
+                }
+                Err(e) => return Err(e),
+            }
+
+            v4l2_output = v4l2_output {
+                index: v4l2_output.index + 1,
+                ..unsafe { mem::zeroed() }
+            };
+        }
+    }
+
+    /// Returns the index of the active video output
+    pub fn output(&self) -> io::Result<usize> {
+        let mut index: std::os::raw::c_int = 0;
+        unsafe {
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_G_OUTPUT,
+                &mut index as *mut _ as *mut std::os::raw::c_void,
+            )?;
+        }
+
+        Ok(index as usize)
+    }
+
+    /// Selects the active video output
+    ///
+    /// Switching outputs may change the current format and video standard. Drivers refuse to
+    /// switch with `EBUSY` while streaming.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the output, as reported by [`Device::enum_outputs`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     let res = dev.set_output(1);
+    /// }
+    /// ```
+    pub fn set_output(&self, index: usize) -> io::Result<()> {
+        let mut index: std::os::raw::c_int = index.try_into().map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, "output index out of range")
+        })?;
+        unsafe {
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_S_OUTPUT,
+                &mut index as *mut _ as *mut std::os::raw::c_void,
+            )
+        }
+    }
+
+    /// Returns the analog video standards supported by the active input
+    ///
+    /// Inputs without analog standards (e.g. cameras or HDMI) yield an empty list.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(standards) = dev.enum_standards() {
+    ///         for std in standards {
+    ///             println!("{}: {} ({})", std.index, std.name, std.frame_period);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn enum_standards(&self) -> io::Result<Vec<StandardDescription>> {
+        let mut standards = Vec::new();
+        let mut v4l2_std: v4l2_standard = unsafe { mem::zeroed() };
+
+        loop {
+            let ret = unsafe {
+                v4l2::ioctl(
+                    self.handle().fd(),
+                    v4l2::vidioc::VIDIOC_ENUMSTD,
+                    &mut v4l2_std as *mut _ as *mut std::os::raw::c_void,
+                )
+            };
+
+            match ret {
+                Ok(()) => standards.push(StandardDescription::from(v4l2_std)),
+                // EINVAL marks the end of the list, ENODATA and ENOTTY mean there are no
+                // standards for this input at all
+                Err(e)
+                    if matches!(
+                        e.raw_os_error(),
+                        Some(libc::EINVAL | libc::ENODATA | libc::ENOTTY)
+                    ) =>
+                {
+                    return Ok(standards)
+                }
+                Err(e) => return Err(e),
+            }
+
+            v4l2_std = v4l2_standard {
+                index: v4l2_std.index + 1,
+                ..unsafe { mem::zeroed() }
+            };
+        }
+    }
+
+    /// Returns the video standard of the active input
+    pub fn standard(&self) -> io::Result<Standard> {
+        let mut id: v4l2_std_id = 0;
+        unsafe {
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_G_STD,
+                &mut id as *mut _ as *mut std::os::raw::c_void,
+            )
+            .map_err(Self::map_std_error)?;
+        }
+
+        Ok(Standard::from(id))
+    }
+
+    /// Selects the video standard of the active input
+    ///
+    /// The driver picks a standard matching the given bits, so e.g. [`Standard::PAL`] is a valid
+    /// argument even though it covers several variants. Use [`Device::standard`] to find out
+    /// which one got selected.
+    ///
+    /// # Arguments
+    ///
+    /// * `std` - Video standard
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    /// use v4l::standard::Standard;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     let res = dev.set_standard(Standard::NTSC_M);
+    /// }
+    /// ```
+    pub fn set_standard(&self, std: Standard) -> io::Result<()> {
+        let mut id: v4l2_std_id = std.into();
+        unsafe {
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_S_STD,
+                &mut id as *mut _ as *mut std::os::raw::c_void,
+            )
+            .map_err(Self::map_std_error)
+        }
+    }
+
+    fn map_std_error(e: io::Error) -> io::Error {
+        match e.raw_os_error() {
+            Some(libc::ENODATA | libc::ENOTTY) => io::Error::new(
+                io::ErrorKind::Unsupported,
+                "active input does not support analog video standards",
+            ),
+            _ => e,
+        }
+    }
+
+    /// Returns the tuners of a radio or TV device
+    ///
+    /// Devices without tuners yield an empty list.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(tuners) = dev.enum_tuners() {
+    ///         for tuner in tuners {
+    ///             println!("{}", tuner);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn enum_tuners(&self) -> io::Result<Vec<Tuner>> {
+        let mut tuners = Vec::new();
+
+        for index in 0.. {
+            match self.tuner(index) {
+                Ok(tuner) => tuners.push(tuner),
+                // EINVAL marks the end of the list, ENOTTY means there are no tuners at all
+                Err(e) if matches!(e.raw_os_error(), Some(libc::EINVAL | libc::ENOTTY)) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(tuners)
+    }
+
+    /// Returns a tuner, including its current signal strength
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the tuner, as reported by [`Device::enum_tuners`]
+    pub fn tuner(&self, index: u32) -> io::Result<Tuner> {
+        unsafe {
+            let mut v4l2_tuner = v4l2_tuner {
+                index,
+                ..mem::zeroed()
+            };
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_G_TUNER,
+                &mut v4l2_tuner as *mut _ as *mut std::os::raw::c_void,
+            )?;
+
+            Ok(Tuner::from(v4l2_tuner))
+        }
+    }
+
+    /// Selects the audio mode of a tuner
+    ///
+    /// This is the only tuner property which can be changed. Drivers fall back to a supported
+    /// mode if the requested one is not available.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the tuner
+    /// * `audmode` - Audio mode
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    /// use v4l::tuner::AudioMode;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     let res = dev.set_tuner(0, AudioMode::Stereo);
+    /// }
+    /// ```
+    pub fn set_tuner(&self, index: u32, audmode: AudioMode) -> io::Result<()> {
+        unsafe {
+            let mut v4l2_tuner = v4l2_tuner {
+                index,
+                audmode: audmode.into(),
+                ..mem::zeroed()
+            };
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_S_TUNER,
+                &mut v4l2_tuner as *mut _ as *mut std::os::raw::c_void,
+            )
+        }
+    }
+
+    /// Returns the current frequency of a tuner in tuner units
+    ///
+    /// Use [`Tuner::to_hz`] to convert the frequency.
+    ///
+    /// # Arguments
+    ///
+    /// * `tuner` - Index of the tuner
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let (Ok(tuner), Ok(freq)) = (dev.tuner(0), dev.frequency(0)) {
+    ///         println!("{}: {} Hz", tuner.name, tuner.to_hz(freq));
+    ///     }
+    /// }
+    /// ```
+    pub fn frequency(&self, tuner: u32) -> io::Result<u32> {
+        let mut v4l2_freq = self.v4l2_frequency(tuner)?;
+        unsafe {
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_G_FREQUENCY,
+                &mut v4l2_freq as *mut _ as *mut std::os::raw::c_void,
+            )?;
+        }
+
+        Ok(v4l2_freq.frequency)
+    }
+
+    /// Tunes to a frequency
+    ///
+    /// Drivers clamp the frequency to the range of the tuner, read it back with
+    /// [`Device::frequency`] to get the actual value.
+    ///
+    /// # Arguments
+    ///
+    /// * `tuner` - Index of the tuner
+    /// * `frequency` - Frequency in tuner units, see [`Tuner::from_hz`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::with_path("/dev/radio0");
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(tuner) = dev.tuner(0) {
+    ///         let res = dev.set_frequency(0, tuner.from_hz(99_100_000));
+    ///     }
+    /// }
+    /// ```
+    pub fn set_frequency(&self, tuner: u32, frequency: u32) -> io::Result<()> {
+        let mut v4l2_freq = v4l2_frequency {
+            frequency,
+            ..self.v4l2_frequency(tuner)?
+        };
         unsafe {
-            let mut v4l2_caps: v4l2_capability = mem::zeroed();
             v4l2::ioctl(
                 self.handle().fd(),
-                v4l2::vidioc::VIDIOC_QUERYCAP,
-                &mut v4l2_caps as *mut _ as *mut std::os::raw::c_void,
+                v4l2::vidioc::VIDIOC_S_FREQUENCY,
+                &mut v4l2_freq as *mut _ as *mut std::os::raw::c_void,
+            )
+        }
+    }
+
+    /// Returns a frequency struct for a tuner
+    ///
+    /// The type has to match the one of the tuner, which is why it is queried first.
+    fn v4l2_frequency(&self, tuner: u32) -> io::Result<v4l2_frequency> {
+        let typ = self.tuner(tuner)?.typ;
+        Ok(v4l2_frequency {
+            tuner,
+            type_: typ.into(),
+            ..unsafe { mem::zeroed() }
+        })
+    }
+
+    /// Returns the access priority of this device handle
+    pub fn priority(&self) -> io::Result<Priority> {
+        let mut prio: u32 = 0;
+        unsafe {
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_G_PRIORITY,
+                &mut prio as *mut _ as *mut std::os::raw::c_void,
             )?;
+        }
 
-            Ok(Capabilities::from(v4l2_caps))
+        Ok(Priority::from(prio))
+    }
+
+    /// Sets the access priority of this device handle
+    ///
+    /// With [`Priority::Record`], other handles (in this or other processes) can no longer
+    /// change the device state, e.g. the format while streaming. Only one handle can hold
+    /// this priority at a time, claiming it fails with `EBUSY` if it is already taken. The
+    /// priority is released once the device and all handles obtained from it are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `prio` - Priority
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    /// use v4l::priority::Priority;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if dev.set_priority(Priority::Record).is_ok() {
+    ///         assert_eq!(dev.priority().unwrap(), Priority::Record);
+    ///     }
+    /// }
+    /// ```
+    pub fn set_priority(&self, prio: Priority) -> io::Result<()> {
+        let mut prio: u32 = prio.into();
+        unsafe {
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_S_PRIORITY,
+                &mut prio as *mut _ as *mut std::os::raw::c_void,
+            )
+        }
+    }
+
+    /// Subscribes to an event type
+    ///
+    /// Pending events are signaled through `POLLPRI` on the device file descriptor and can be
+    /// retrieved with [`Device::dequeue_event`]. Streams which watch for source changes
+    /// dequeue events on their own; they consume source change events and keep all others for
+    /// [`Device::dequeue_event`], but these are no longer signaled through `POLLPRI`.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - Event type
+    /// * `id` - Object the events are reported for: the control id for [`EventType::Ctrl`], the
+    ///   input or pad index for [`EventType::SourceChange`] and zero otherwise
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    /// use v4l::event::Type;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if dev.subscribe_event(Type::SourceChange, 0).is_ok() {
+    ///         if let Ok(event) = dev.dequeue_event() {
+    ///             println!("{}", event);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn subscribe_event(&self, kind: EventType, id: u32) -> io::Result<()> {
+        self.handle().subscribe_event(kind.into(), id)
+    }
+
+    /// Unsubscribes from an event type
+    ///
+    /// Use [`EventType::All`] to unsubscribe from all events at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - Event type
+    /// * `id` - Object id which was used to subscribe
+    pub fn unsubscribe_event(&self, kind: EventType, id: u32) -> io::Result<()> {
+        self.handle().unsubscribe_event(kind.into(), id)
+    }
+
+    /// Returns the next pending event or `None` if there is none
+    ///
+    /// Unlike [`Device::dequeue_event`], this never blocks. It is meant to be called from
+    /// streaming loops, e.g. to learn about the resolution of a decoded stream (see
+    /// [`crate::format::description::Flags::DYN_RESOLUTION`]).
+    pub fn try_dequeue_event(&self) -> io::Result<Option<Event>> {
+        Ok(self.handle().dequeue_event()?.map(Event::from))
+    }
+
+    /// Waits for the next event and returns it
+    ///
+    /// This blocks until an event of one of the subscribed types is pending.
+    pub fn dequeue_event(&self) -> io::Result<Event> {
+        loop {
+            if let Some(event) = self.handle().dequeue_event()? {
+                return Ok(Event::from(event));
+            }
+
+            self.handle().poll(libc::POLLPRI, -1)?;
         }
     }
 
@@ -105,54 +1740,7 @@ impl Device {
                     v4l2::vidioc::VIDIOC_QUERY_EXT_CTRL,
                     &mut v4l2_ctrl as *mut _ as *mut std::os::raw::c_void,
                 ) {
-                    Ok(_) => {
-                        // get the basic control information
-                        let mut control = Description::from(v4l2_ctrl);
-
-                        // if this is a menu control, enumerate its items
-                        if control.typ == control::Type::Menu
-                            || control.typ == control::Type::IntegerMenu
-                        {
-                            let mut items = Vec::new();
-
-                            for i in (v4l2_ctrl.minimum..=v4l2_ctrl.maximum)
-                                .step_by(v4l2_ctrl.step as usize)
-                            {
-                                let mut v4l2_menu = v4l2_querymenu {
-                                    id: v4l2_ctrl.id,
-                                    index: i as u32,
-                                    ..mem::zeroed()
-                                };
-                                let res = v4l2::ioctl(
-                                    self.handle().fd(),
-                                    v4l2::vidioc::VIDIOC_QUERYMENU,
-                                    &mut v4l2_menu as *mut _ as *mut std::os::raw::c_void,
-                                );
-
-                                // BEWARE OF DRAGONS!
-                                // The API docs [1] state VIDIOC_QUERYMENU should may return EINVAL
-                                // for some indices between minimum and maximum when an item is not
-                                // supported by a driver.
-                                //
-                                // I have no idea why it is advertised in the first place then, but
-                                // have seen this happen with a Logitech C920 HD Pro webcam.
-                                // In case of errors, let's just skip the offending index.
-                                //
-                                // [1] https://github.com/torvalds/linux/blob/master/Documentation/userspace-api/media/v4l/vidioc-queryctrl.rst#description
-                                if res.is_err() {
-                                    continue;
-                                }
-
-                                let item =
-                                    control::MenuItem::try_from((control.typ, v4l2_menu)).unwrap();
-                                items.push((v4l2_menu.index, item));
-                            }
-
-                            control.items = Some(items);
-                        }
-
-                        controls.push(control);
-                    }
+                    Ok(_) => controls.push(self.describe(v4l2_ctrl)),
                     Err(e) => {
                         if controls.is_empty() || e.kind() != io::ErrorKind::InvalidInput {
                             return Err(e);
@@ -167,21 +1755,412 @@ impl Device {
         Ok(controls)
     }
 
+    /// Returns a listing of all controls in the style of `v4l2-ctl --list-ctrls-menus`
+    ///
+    /// Each control is printed on one line with its id, type, range, default and current value
+    /// as well as its flags, followed by the items of menu controls. Controls are grouped by
+    /// their class. The current values of write-only, button and compound controls are not
+    /// read. This is useful to attach to bug reports.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(dump) = dev.dump_controls() {
+    ///         print!("{}", dump);
+    ///     }
+    /// }
+    /// ```
+    pub fn dump_controls(&self) -> io::Result<String> {
+        let mut out = String::new();
+
+        for desc in self.query_controls()? {
+            if desc.typ == control::Type::CtrlClass {
+                out.push_str(&format!("\n{}\n\n", desc.name));
+                continue;
+            }
+
+            let readable = !desc.flags.contains(control::Flags::WRITE_ONLY)
+                && desc.typ != control::Type::Button
+                && !desc.typ.is_compound();
+            let value = if readable {
+                self.control(&desc).ok().map(|ctrl| ctrl.value)
+            } else {
+                None
+            };
+
+            out.push_str(&dump_control(&desc, value.as_ref()));
+            out.push('\n');
+            if let Some(items) = &desc.items {
+                for (index, item) in items {
+                    out.push_str(&format!("\t\t\t\t{}: {}\n", index, item));
+                }
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Returns the controls which can currently be changed by the user
+    ///
+    /// This is what a settings UI usually wants to display. A control is considered adjustable
+    /// if none of the [`control::Flags::DISABLED`], [`control::Flags::READ_ONLY`] and
+    /// [`control::Flags::INACTIVE`] flags are set and it is not a
+    /// [`control::Type::CtrlClass`] header. Note that inactive controls become adjustable once
+    /// their automatic mode is turned off, so call this again after changing such modes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(controls) = dev.adjustable_controls() {
+    ///         for ctrl in controls {
+    ///             println!("{}", ctrl.name);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn adjustable_controls(&self) -> io::Result<Vec<Description>> {
+        let fixed = control::Flags::DISABLED | control::Flags::READ_ONLY | control::Flags::INACTIVE;
+        Ok(self
+            .query_controls()?
+            .into_iter()
+            .filter(|ctrl| !ctrl.flags.intersects(fixed) && ctrl.typ != control::Type::CtrlClass)
+            .collect())
+    }
+
+    /// Returns the description of a single control
+    ///
+    /// This is useful to refresh the range or flags of a control (which may change at runtime,
+    /// e.g. when an auto mode is toggled) without enumerating all controls again. For menu
+    /// controls, the items are enumerated as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Control identifier, e.g. `V4L2_CID_BRIGHTNESS`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(desc) = dev.query_control(v4l::v4l_sys::V4L2_CID_BRIGHTNESS) {
+    ///         println!("{}", desc);
+    ///     }
+    /// }
+    /// ```
+    pub fn query_control(&self, id: u32) -> io::Result<Description> {
+        unsafe {
+            let mut v4l2_ctrl = v4l2_query_ext_ctrl {
+                id,
+                ..mem::zeroed()
+            };
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_QUERY_EXT_CTRL,
+                &mut v4l2_ctrl as *mut _ as *mut std::os::raw::c_void,
+            )?;
+
+            Ok(self.describe(v4l2_ctrl))
+        }
+    }
+
+    /// Returns the description of a control, including its menu items for menu controls
+    fn describe(&self, v4l2_ctrl: v4l2_query_ext_ctrl) -> Description {
+        // get the basic control information
+        let mut control = Description::from(v4l2_ctrl);
+
+        // if this is a menu control, enumerate its items
+        if control.typ == control::Type::Menu || control.typ == control::Type::IntegerMenu {
+            control.items = menu_items(&v4l2_ctrl, control.typ, |v4l2_menu| unsafe {
+                v4l2::ioctl(
+                    self.handle().fd(),
+                    v4l2::vidioc::VIDIOC_QUERYMENU,
+                    v4l2_menu as *mut _ as *mut std::os::raw::c_void,
+                )
+            });
+        }
+
+        control
+    }
+
+    /// Tunes a software defined radio to the given center frequency in Hz
+    ///
+    /// SDR devices (e.g. `/dev/swradio0`) expose two tuners: an RF tuner (`V4L2_TUNER_RF`)
+    /// which selects the center frequency and an ADC (`V4L2_TUNER_SDR`) which selects the
+    /// sampling rate, see [`Device::set_sampling_rate`]. This looks up the RF tuner, converts
+    /// the frequency into the units of the tuner and returns the frequency the driver actually
+    /// chose. Bandwidth and gains are regular controls of the RF tuner class, e.g.
+    /// `V4L2_CID_RF_TUNER_BANDWIDTH` or `V4L2_CID_RF_TUNER_LNA_GAIN`.
+    ///
+    /// # Arguments
+    ///
+    /// * `hz` - Center frequency in Hz
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// if let Ok(dev) = Device::with_path("/dev/swradio0") {
+    ///     if let Ok(freq) = dev.set_sdr_frequency(100_000_000) {
+    ///         println!("Tuned to {} Hz", freq);
+    ///     }
+    /// }
+    /// ```
+    pub fn set_sdr_frequency(&self, hz: u64) -> io::Result<u64> {
+        self.set_tuner_frequency(v4l2_tuner_type_V4L2_TUNER_RF, hz)
+    }
+
+    /// Sets the sampling rate of a software defined radio in Hz
+    ///
+    /// This sets the frequency of the ADC tuner (`V4L2_TUNER_SDR`) and returns the sampling rate
+    /// the driver actually chose.
+    ///
+    /// # Arguments
+    ///
+    /// * `hz` - Sampling rate in Hz
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// if let Ok(dev) = Device::with_path("/dev/swradio0") {
+    ///     if let Ok(rate) = dev.set_sampling_rate(2_048_000) {
+    ///         println!("Sampling at {} Hz", rate);
+    ///     }
+    /// }
+    /// ```
+    pub fn set_sampling_rate(&self, hz: u64) -> io::Result<u64> {
+        self.set_tuner_frequency(v4l2_tuner_type_V4L2_TUNER_SDR, hz)
+    }
+
+    fn set_tuner_frequency(&self, typ: u32, hz: u64) -> io::Result<u64> {
+        let tuner = self.find_tuner(typ)?;
+
+        // Frequencies are given in units of 62.5 kHz, 62.5 Hz (CAP_LOW) or 1 Hz (CAP_1HZ).
+        let (num, den) = if tuner.capability & V4L2_TUNER_CAP_1HZ != 0 {
+            (1, 1)
+        } else if tuner.capability & V4L2_TUNER_CAP_LOW != 0 {
+            (2, 125)
+        } else {
+            (2, 125_000)
+        };
+        let frequency = u32::try_from(hz * num / den).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("frequency {} Hz out of range", hz),
+            )
+        })?;
+
+        unsafe {
+            let mut v4l2_freq = v4l2_frequency {
+                tuner: tuner.index,
+                type_: typ,
+                frequency,
+                ..mem::zeroed()
+            };
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_S_FREQUENCY,
+                &mut v4l2_freq as *mut _ as *mut std::os::raw::c_void,
+            )?;
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_G_FREQUENCY,
+                &mut v4l2_freq as *mut _ as *mut std::os::raw::c_void,
+            )?;
+
+            Ok(u64::from(v4l2_freq.frequency) * den / num)
+        }
+    }
+
+    fn find_tuner(&self, typ: u32) -> io::Result<v4l2_tuner> {
+        let mut index = 0;
+        loop {
+            let mut v4l2_tuner = v4l2_tuner {
+                index,
+                ..unsafe { mem::zeroed() }
+            };
+            let ret = unsafe {
+                v4l2::ioctl(
+                    self.handle().fd(),
+                    v4l2::vidioc::VIDIOC_G_TUNER,
+                    &mut v4l2_tuner as *mut _ as *mut std::os::raw::c_void,
+                )
+            };
+
+            match ret {
+                Ok(()) if v4l2_tuner.type_ == typ => return Ok(v4l2_tuner),
+                Ok(()) => index += 1,
+                // EINVAL marks the end of the list, ENOTTY means there are no tuners at all
+                Err(e) if matches!(e.raw_os_error(), Some(libc::EINVAL | libc::ENOTTY)) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("no tuner of type {}", typ),
+                    ))
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Returns the pixel rate of the sensor in Hz
+    ///
+    /// This reads the `V4L2_CID_PIXEL_RATE` control, which is mostly exposed by MIPI CSI-2
+    /// sensors. It is needed to convert exposure and blanking values (given in lines or pixels)
+    /// into time. A negative value is rejected with an error of kind
+    /// [`io::ErrorKind::InvalidData`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(rate) = dev.pixel_rate() {
+    ///         println!("Pixel rate: {} Hz", rate);
+    ///     }
+    /// }
+    /// ```
+    pub fn pixel_rate(&self) -> io::Result<u64> {
+        let desc = self.query_control(V4L2_CID_PIXEL_RATE)?;
+        match self.control(&desc)?.value {
+            control::Value::Integer(rate) => u64::try_from(rate).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("negative pixel rate {}", rate),
+                )
+            }),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "V4L2_CID_PIXEL_RATE is not an integer control",
+            )),
+        }
+    }
+
+    /// Returns the link frequencies supported by the sensor in Hz
+    ///
+    /// This reads the items of the `V4L2_CID_LINK_FREQ` integer menu control. The index of a
+    /// frequency within the returned vector is not necessarily its menu index, use
+    /// [`Device::query_control`] if you need those. Negative frequencies are left out.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(freqs) = dev.link_frequencies() {
+    ///         println!("Link frequencies: {:?}", freqs);
+    ///     }
+    /// }
+    /// ```
+    pub fn link_frequencies(&self) -> io::Result<Vec<u64>> {
+        let desc = self.query_control(V4L2_CID_LINK_FREQ)?;
+        if desc.typ != control::Type::IntegerMenu {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "V4L2_CID_LINK_FREQ is not an integer menu control",
+            ));
+        }
+
+        Ok(desc
+            .items
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(_, item)| match item {
+                control::MenuItem::Value(freq) => u64::try_from(freq).ok(),
+                control::MenuItem::Name(_) => None,
+            })
+            .collect())
+    }
+
     /// Returns the current control value from its [`Description`]
     ///
     /// # Arguments
     ///
     /// * `desc` - Control description
     pub fn control(&self, desc: &Description) -> io::Result<Control> {
+        let mut ctrls = self.controls(std::slice::from_ref(desc))?;
+        Ok(ctrls.remove(0))
+    }
+
+    /// Returns the current values of several controls, read with a single ioctl
+    ///
+    /// This is considerably cheaper than reading the controls one by one, e.g. when a set of
+    /// sliders is refreshed every frame. The descriptions are needed to decode the values, query
+    /// them once upfront with [`Device::query_controls`] or [`Device::query_control`].
+    ///
+    /// # Arguments
+    ///
+    /// * `descs` - Control descriptions
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(descs) = dev.adjustable_controls() {
+    ///         if let Ok(ctrls) = dev.controls(&descs) {
+    ///             for (desc, ctrl) in descs.iter().zip(ctrls) {
+    ///                 println!("{}: {:?}", desc.name, ctrl.value);
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn controls(&self, descs: &[Description]) -> io::Result<Vec<Control>> {
+        if descs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // string and compound payloads are read into buffers sized after the control
+        // descriptions
+        let mut payloads = Vec::with_capacity(descs.len());
+        for desc in descs {
+            let payload = if Self::has_payload(desc) {
+                vec![0u8; self.payload_size(desc.id)?]
+            } else {
+                Vec::new()
+            };
+            payloads.push(payload);
+        }
+
+        let mut control_list: Vec<v4l2_ext_control> = descs
+            .iter()
+            .zip(payloads.iter_mut())
+            .map(|(desc, payload)| {
+                let mut v4l2_ctrl = v4l2_ext_control {
+                    id: desc.id,
+                    ..unsafe { mem::zeroed() }
+                };
+                if Self::has_payload(desc) {
+                    v4l2_ctrl.__bindgen_anon_1.ptr =
+                        payload.as_mut_ptr() as *mut std::os::raw::c_void;
+                    v4l2_ctrl.size = payload.len() as u32;
+                }
+                v4l2_ctrl
+            })
+            .collect();
+
         unsafe {
-            // query the actual control value
-            let mut v4l2_ctrl = v4l2_ext_control {
-                id: desc.id,
-                ..mem::zeroed()
-            };
             let mut v4l2_ctrls = v4l2_ext_controls {
-                count: 1,
-                controls: &mut v4l2_ctrl,
+                count: control_list.len() as u32,
+                controls: control_list.as_mut_ptr(),
                 ..mem::zeroed()
             };
             v4l2::ioctl(
@@ -189,27 +2168,100 @@ impl Device {
                 v4l2::vidioc::VIDIOC_G_EXT_CTRLS,
                 &mut v4l2_ctrls as *mut _ as *mut std::os::raw::c_void,
             )?;
+        }
 
-            let value = match desc.typ {
-                control::Type::Integer64 => {
-                    control::Value::Integer(v4l2_ctrl.__bindgen_anon_1.value64)
-                }
-                control::Type::Integer | control::Type::Menu => {
-                    control::Value::Integer(v4l2_ctrl.__bindgen_anon_1.value as i64)
-                }
-                control::Type::Boolean => {
-                    control::Value::Boolean(v4l2_ctrl.__bindgen_anon_1.value == 1)
-                }
-                _ => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::Other,
-                        "cannot handle control type",
-                    ))
-                }
-            };
+        descs
+            .iter()
+            .zip(control_list)
+            .zip(payloads)
+            .map(|((desc, v4l2_ctrl), payload)| {
+                let value = Self::decode_control(desc, &v4l2_ctrl, payload)?;
+                Ok(Control { id: desc.id, value })
+            })
+            .collect()
+    }
 
-            Ok(Control { id: desc.id, value })
+    /// Returns whether a control value is passed by pointer
+    fn has_payload(desc: &Description) -> bool {
+        desc.flags.contains(control::Flags::HAS_PAYLOAD)
+            || desc.typ.is_compound()
+            || desc.typ == control::Type::String
+    }
+
+    /// Interprets a control value returned by `VIDIOC_G_EXT_CTRLS` according to its type
+    fn decode_control(
+        desc: &Description,
+        v4l2_ctrl: &v4l2_ext_control,
+        payload: Vec<u8>,
+    ) -> io::Result<control::Value> {
+        let value = match desc.typ {
+            control::Type::String => {
+                // the payload is NUL terminated and may be padded
+                let len = payload
+                    .iter()
+                    .position(|&b| b == 0)
+                    .unwrap_or(payload.len());
+                control::Value::String(String::from_utf8_lossy(&payload[..len]).into_owned())
+            }
+            control::Type::U8 => control::Value::CompoundU8(payload),
+            control::Type::U16 => control::Value::CompoundU16(
+                payload
+                    .chunks_exact(2)
+                    .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+                    .collect(),
+            ),
+            control::Type::U32 => control::Value::CompoundU32(
+                payload
+                    .chunks_exact(4)
+                    .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect(),
+            ),
+            control::Type::Rect if payload.len() == mem::size_of::<v4l2_rect>() => {
+                control::Value::Rect(Rect::from(unsafe {
+                    std::ptr::read_unaligned(payload.as_ptr() as *const v4l2_rect)
+                }))
+            }
+            control::Type::Area if payload.len() == mem::size_of::<v4l2_area>() => {
+                control::Value::Area(Area::from(unsafe {
+                    std::ptr::read_unaligned(payload.as_ptr() as *const v4l2_area)
+                }))
+            }
+            _ if Self::has_payload(desc) => control::Value::Struct(payload),
+            control::Type::Integer64 => {
+                control::Value::Integer(unsafe { v4l2_ctrl.__bindgen_anon_1.value64 })
+            }
+            control::Type::Integer | control::Type::Menu => {
+                control::Value::Integer(unsafe { v4l2_ctrl.__bindgen_anon_1.value } as i64)
+            }
+            control::Type::Boolean => {
+                control::Value::Boolean(unsafe { v4l2_ctrl.__bindgen_anon_1.value } == 1)
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "cannot handle control type",
+                ))
+            }
+        };
+
+        Ok(value)
+    }
+
+    /// Returns the payload size in bytes of a string or compound control
+    fn payload_size(&self, id: u32) -> io::Result<usize> {
+        let mut v4l2_ctrl = v4l2_query_ext_ctrl {
+            id,
+            ..unsafe { mem::zeroed() }
+        };
+        unsafe {
+            v4l2::ioctl(
+                self.handle().fd(),
+                v4l2::vidioc::VIDIOC_QUERY_EXT_CTRL,
+                &mut v4l2_ctrl as *mut _ as *mut std::os::raw::c_void,
+            )?;
         }
+
+        Ok(v4l2_ctrl.elem_size as usize * v4l2_ctrl.elems as usize)
     }
 
     /// Modifies the control value
@@ -221,14 +2273,186 @@ impl Device {
         self.set_controls(vec![ctrl])
     }
 
+    /// Returns the current value of the control with the given name
+    ///
+    /// Names are matched case-insensitively against [`Description::name`], e.g. "brightness".
+    /// An error of kind [`io::ErrorKind::NotFound`] is returned if no control matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the control
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(ctrl) = dev.control_by_name("Brightness") {
+    ///         println!("Brightness: {:?}", ctrl.value);
+    ///     }
+    /// }
+    /// ```
+    pub fn control_by_name(&self, name: &str) -> io::Result<Control> {
+        let desc = self.find_control(name)?;
+        self.control(&desc)
+    }
+
+    /// Modifies the value of the control with the given name
+    ///
+    /// See [`Device::control_by_name`] for how names are matched.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the control
+    /// * `value` - New value
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::control::Value;
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     let res = dev.set_control_by_name("contrast", Value::Integer(32));
+    /// }
+    /// ```
+    pub fn set_control_by_name(&self, name: &str, value: control::Value) -> io::Result<()> {
+        let desc = self.find_control(name)?;
+        self.set_control(Control { id: desc.id, value })
+    }
+
+    /// Rotates and flips the image, e.g. for cameras which are mounted upside down
+    ///
+    /// This writes `V4L2_CID_ROTATE`, `V4L2_CID_HFLIP` and `V4L2_CID_VFLIP`. Controls which the
+    /// device does not offer, or which are disabled or read-only, are skipped. The ids of the
+    /// controls which were actually written are returned, so check them to find out whether the
+    /// image still needs to be transformed in software.
+    ///
+    /// # Arguments
+    ///
+    /// * `rotate` - Clockwise rotation in degrees, most drivers only accept multiples of 90
+    /// * `hflip` - Whether to mirror the image horizontally
+    /// * `vflip` - Whether to mirror the image vertically
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    /// use v4l::v4l_sys::{V4L2_CID_HFLIP, V4L2_CID_VFLIP};
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     // upside down is the same as flipping both axes
+    ///     if let Ok(applied) = dev.set_orientation(0, true, true) {
+    ///         if !applied.contains(&V4L2_CID_HFLIP) || !applied.contains(&V4L2_CID_VFLIP) {
+    ///             println!("flip the frames in software");
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn set_orientation(&self, rotate: u16, hflip: bool, vflip: bool) -> io::Result<Vec<u32>> {
+        let ctrls = vec![
+            (V4L2_CID_ROTATE, control::Value::Integer(i64::from(rotate))),
+            (V4L2_CID_HFLIP, control::Value::Boolean(hflip)),
+            (V4L2_CID_VFLIP, control::Value::Boolean(vflip)),
+        ];
+
+        let mut applied = Vec::new();
+        for (id, value) in ctrls {
+            let desc = match self.query_control(id) {
+                Ok(desc) => desc,
+                // EINVAL means the control does not exist
+                Err(e) if e.raw_os_error() == Some(libc::EINVAL) => continue,
+                Err(e) => return Err(e),
+            };
+            if desc
+                .flags
+                .intersects(control::Flags::DISABLED | control::Flags::READ_ONLY)
+            {
+                continue;
+            }
+
+            self.set_control(Control { id, value })?;
+            applied.push(id);
+        }
+
+        Ok(applied)
+    }
+
+    /// Returns the description of the control with the given name (case-insensitive)
+    fn find_control(&self, name: &str) -> io::Result<Description> {
+        self.query_controls()?
+            .into_iter()
+            .find(|desc| desc.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no control named {}", name),
+                )
+            })
+    }
+
     /// Modifies the control values atomically
     ///
+    /// If a control cannot be written because it is inactive (e.g. manual exposure while auto
+    /// exposure is on) or grabbed, the returned error names the control and, where known, the
+    /// automatic mode which has to be disabled first.
+    ///
     /// # Arguments
     ///
     /// * `ctrls` - Vec of the controls to be set
     pub fn set_controls(&self, ctrls: Vec<Control>) -> io::Result<()> {
+        self.ext_controls(v4l2::vidioc::VIDIOC_S_EXT_CTRLS, ctrls)
+    }
+
+    /// Validates the control values without applying them
+    ///
+    /// The driver checks the values against its constraints and the controls are left
+    /// untouched. If a value is rejected, the returned error names the offending control and
+    /// its index in `ctrls`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctrls` - Vec of the controls to be validated
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::control::{Control, Value};
+    /// use v4l::prelude::*;
+    ///
+    /// if let Ok(dev) = Device::new(0) {
+    ///     let descs = dev.query_controls().unwrap_or_default();
+    ///     if let Some(desc) = descs.iter().find(|desc| desc.name == "Brightness") {
+    ///         let ctrl = Control {
+    ///             id: desc.id,
+    ///             value: Value::Integer(desc.minimum),
+    ///         };
+    ///         if dev.try_controls(vec![ctrl]).is_ok() {
+    ///             println!("brightness can be set to its minimum");
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn try_controls(&self, ctrls: Vec<Control>) -> io::Result<()> {
+        self.ext_controls(v4l2::vidioc::VIDIOC_TRY_EXT_CTRLS, ctrls)
+    }
+
+    /// Passes the control values to the driver using one of the extended control ioctls
+    fn ext_controls(
+        &self,
+        request: v4l2::vidioc::_IOC_TYPE,
+        ctrls: Vec<Control>,
+    ) -> io::Result<()> {
         unsafe {
             let mut control_list: Vec<v4l2_ext_control> = vec![];
+            // backing storage for struct payloads, never grows beyond its initial capacity so
+            // the pointers handed to the driver stay valid
+            let mut rects: Vec<v4l2_rect> = Vec::with_capacity(ctrls.len());
+            let mut areas: Vec<v4l2_area> = Vec::with_capacity(ctrls.len());
             let mut class: Option<u32> = None;
 
             if ctrls.is_empty() {
@@ -287,6 +2511,21 @@ impl Device {
                         control.__bindgen_anon_1.ptr = val.as_ptr() as *mut std::os::raw::c_void;
                         control.size = (val.len() * std::mem::size_of::<u8>()) as u32;
                     }
+                    control::Value::Area(val) => {
+                        areas.push(v4l2_area::from(val));
+                        control.__bindgen_anon_1.p_area = areas.last_mut().unwrap();
+                        control.size = std::mem::size_of::<v4l2_area>() as u32;
+                    }
+                    control::Value::Rect(val) => {
+                        rects.push(v4l2_rect::from(val));
+                        control.__bindgen_anon_1.ptr =
+                            rects.last_mut().unwrap() as *mut _ as *mut std::os::raw::c_void;
+                        control.size = std::mem::size_of::<v4l2_rect>() as u32;
+                    }
+                    control::Value::Struct(ref val) => {
+                        control.__bindgen_anon_1.ptr = val.as_ptr() as *mut std::os::raw::c_void;
+                        control.size = val.len() as u32;
+                    }
                 };
 
                 control_list.push(control);
@@ -309,9 +2548,232 @@ impl Device {
 
             v4l2::ioctl(
                 self.handle().fd(),
-                v4l2::vidioc::VIDIOC_S_EXT_CTRLS,
+                request,
                 &mut controls as *mut _ as *mut std::os::raw::c_void,
             )
+            .map_err(|e| match e.raw_os_error() {
+                Some(libc::EACCES) | Some(libc::EBUSY) => {
+                    self.explain_control_error(control_list.iter().map(|ctrl| ctrl.id), e)
+                }
+                // error_idx equals count if the request failed before the values were checked
+                Some(libc::EINVAL) if (controls.error_idx as usize) < control_list.len() => {
+                    let idx = controls.error_idx as usize;
+                    let id = control_list[idx].id;
+                    let name = match self.query_control(id) {
+                        Ok(desc) => desc.name,
+                        Err(_) => format!("{:#x}", id),
+                    };
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("control {} (index {}) has an invalid value", name, idx),
+                    )
+                }
+                _ => e,
+            })
+        }
+    }
+
+    /// Turns errors of control writes into actionable ones by inspecting the control flags
+    ///
+    /// Controls which are inactive (because an automatic mode is on) or grabbed (e.g. by a
+    /// streaming process) are rejected by many drivers with `EACCES` or `EBUSY`.
+    fn explain_control_error(&self, ids: impl Iterator<Item = u32>, err: io::Error) -> io::Error {
+        for id in ids {
+            let desc = match self.query_control(id) {
+                Ok(desc) => desc,
+                Err(_) => continue,
+            };
+
+            if desc.flags.contains(control::Flags::INACTIVE) {
+                let hint = match auto_control(id).and_then(|auto| self.query_control(auto).ok()) {
+                    Some(auto) => format!("disable {} first", auto.name),
+                    None => "disable the corresponding automatic mode first".to_string(),
+                };
+                return io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("control {} is inactive; {}", desc.name, hint),
+                );
+            }
+
+            if desc.flags.contains(control::Flags::GRABBED) {
+                return io::Error::new(
+                    err.kind(),
+                    format!(
+                        "control {} is grabbed and cannot be changed right now (is the device \
+                        streaming?)",
+                        desc.name
+                    ),
+                );
+            }
+        }
+
+        err
+    }
+}
+
+/// Formats a control as a single line like v4l2-ctl does, without a trailing newline
+fn dump_control(desc: &Description, value: Option<&control::Value>) -> String {
+    let typ = match desc.typ {
+        control::Type::Integer => "int",
+        control::Type::Boolean => "bool",
+        control::Type::Menu => "menu",
+        control::Type::Button => "button",
+        control::Type::Integer64 => "int64",
+        control::Type::CtrlClass => "ctrl_class",
+        control::Type::String => "str",
+        control::Type::Bitmask => "bitmask",
+        control::Type::IntegerMenu => "intmenu",
+        control::Type::U8 => "u8",
+        control::Type::U16 => "u16",
+        control::Type::U32 => "u32",
+        control::Type::Area => "area",
+        control::Type::Rect => "rect",
+        control::Type::Unknown(_) => "unknown",
+    };
+    let mut line = format!(
+        "{:>31} {:#010x} ({}){:pad$}:",
+        control_key(&desc.name),
+        desc.id,
+        typ,
+        "",
+        pad = 7usize.saturating_sub(typ.len())
+    );
+
+    match desc.typ {
+        control::Type::Integer
+        | control::Type::Integer64
+        | control::Type::Menu
+        | control::Type::IntegerMenu => {
+            line.push_str(&format!(" min={} max={}", desc.minimum, desc.maximum));
+            if !matches!(desc.typ, control::Type::Menu | control::Type::IntegerMenu) {
+                line.push_str(&format!(" step={}", desc.step));
+            }
+            line.push_str(&format!(" default={}", desc.default));
+        }
+        control::Type::Boolean => line.push_str(&format!(" default={}", desc.default)),
+        control::Type::Bitmask => line.push_str(&format!(
+            " max={:#010x} default={:#010x}",
+            desc.maximum, desc.default
+        )),
+        control::Type::String => line.push_str(&format!(
+            " min={} max={} step={}",
+            desc.minimum, desc.maximum, desc.step
+        )),
+        _ => {}
+    }
+
+    match value {
+        Some(control::Value::Integer(val)) if desc.typ == control::Type::Bitmask => {
+            line.push_str(&format!(" value={:#010x}", val))
+        }
+        Some(control::Value::Integer(val)) => line.push_str(&format!(" value={}", val)),
+        Some(control::Value::Boolean(val)) => line.push_str(&format!(" value={}", *val as u8)),
+        Some(control::Value::String(val)) => line.push_str(&format!(" value='{}'", val)),
+        _ => {}
+    }
+
+    let flags = [
+        (control::Flags::DISABLED, "disabled"),
+        (control::Flags::GRABBED, "grabbed"),
+        (control::Flags::READ_ONLY, "read-only"),
+        (control::Flags::UPDATE, "update"),
+        (control::Flags::INACTIVE, "inactive"),
+        (control::Flags::SLIDER, "slider"),
+        (control::Flags::WRITE_ONLY, "write-only"),
+        (control::Flags::VOLATILE, "volatile"),
+        (control::Flags::HAS_PAYLOAD, "has-payload"),
+        (control::Flags::EXECUTE_ON_WRITE, "execute-on-write"),
+        (control::Flags::MODIFY_LAYOUT, "modify-layout"),
+    ];
+    let flags: Vec<&str> = flags
+        .iter()
+        .filter(|(flag, _)| desc.flags.contains(*flag))
+        .map(|(_, name)| *name)
+        .collect();
+    if !flags.is_empty() {
+        line.push_str(&format!(" flags={}", flags.join(", ")));
+    }
+
+    line
+}
+
+/// Turns a control name into the identifier used by v4l2-ctl, e.g. "White Balance, Auto" into
+/// "white_balance_auto"
+fn control_key(name: &str) -> String {
+    let mut key = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            key.push(c.to_ascii_lowercase());
+        } else if !key.is_empty() && !key.ends_with('_') {
+            key.push('_');
+        }
+    }
+
+    key.trim_end_matches('_').to_string()
+}
+
+/// Returns the automatic mode control which deactivates the given manual control
+fn auto_control(id: u32) -> Option<u32> {
+    match id {
+        V4L2_CID_EXPOSURE | V4L2_CID_EXPOSURE_ABSOLUTE => Some(V4L2_CID_EXPOSURE_AUTO),
+        V4L2_CID_WHITE_BALANCE_TEMPERATURE | V4L2_CID_RED_BALANCE | V4L2_CID_BLUE_BALANCE => {
+            Some(V4L2_CID_AUTO_WHITE_BALANCE)
+        }
+        V4L2_CID_FOCUS_ABSOLUTE | V4L2_CID_FOCUS_RELATIVE => Some(V4L2_CID_FOCUS_AUTO),
+        V4L2_CID_GAIN => Some(V4L2_CID_AUTOGAIN),
+        V4L2_CID_HUE => Some(V4L2_CID_HUE_AUTO),
+        V4L2_CID_BRIGHTNESS => Some(V4L2_CID_AUTOBRIGHTNESS),
+        _ => None,
+    }
+}
+
+impl From<Arc<Handle>> for Device {
+    /// Returns a device which shares the given handle
+    ///
+    /// This is useful to change device properties such as controls while a stream (which holds a
+    /// handle of its own) is active.
+    fn from(handle: Arc<Handle>) -> Self {
+        Device { handle }
+    }
+}
+
+impl AsRawFd for Device {
+    /// Returns the file descriptor of the device, e.g. to register it with an event loop
+    ///
+    /// The descriptor stays owned by the device, do not close it.
+    fn as_raw_fd(&self) -> RawFd {
+        self.handle.fd()
+    }
+}
+
+impl FromRawFd for Device {
+    /// Returns a device which takes ownership of an already opened file descriptor
+    ///
+    /// The descriptor is closed when the device (and all of its streams) are dropped, so it must
+    /// not be closed elsewhere. Unlike [`Device::from_fd`], the descriptor is not validated.
+    ///
+    /// # Safety
+    ///
+    /// The file descriptor must be open and owned by the caller, i.e. nothing else may close it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::fs::OpenOptions;
+    /// use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+    ///
+    /// use v4l::device::Device;
+    ///
+    /// if let Ok(file) = OpenOptions::new().read(true).write(true).open("/dev/video0") {
+    ///     let fd = file.into_raw_fd();
+    ///     let dev = unsafe { Device::from_raw_fd(fd) };
+    ///     assert_eq!(dev.as_raw_fd(), fd);
+    ///     assert_eq!(dev.handle().as_raw_fd(), fd);
+    /// }
+    /// ```
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Device {
+            handle: Arc::new(Handle::new(fd)),
         }
     }
 }
@@ -355,16 +2817,36 @@ impl io::Write for Device {
     }
 }
 
+/// Maximum number of events kept by [`Handle::source_changed`], older ones are dropped
+///
+/// The kernel drops the oldest events of a subscription once its queue is full as well.
+const MAX_KEPT_EVENTS: usize = 64;
+
+/// Keeps an event for [`Handle::dequeue_event`], dropping the oldest one if the queue is full
+fn keep_event(events: &mut VecDeque<v4l2_event>, event: v4l2_event) {
+    if events.len() >= MAX_KEPT_EVENTS {
+        events.pop_front();
+    }
+    events.push_back(event);
+}
+
 /// Device handle for low-level access.
 ///
 /// Acquiring a handle facilitates (possibly mutating) interactions with the device.
+/// Handles are reference counted and shared between a device and all of its streams, so it is
+/// safe to issue ioctls (e.g. to set controls) from one thread while another one is streaming.
 pub struct Handle {
     fd: std::os::raw::c_int,
+    /// Events dequeued while looking for source changes, returned by the next dequeue_event()
+    events: Mutex<VecDeque<v4l2_event>>,
 }
 
 impl Handle {
-    fn new(fd: std::os::raw::c_int) -> Self {
-        Self { fd }
+    pub(crate) fn new(fd: std::os::raw::c_int) -> Self {
+        Self {
+            fd,
+            events: Mutex::new(VecDeque::new()),
+        }
     }
 
     /// Returns the raw file descriptor
@@ -382,26 +2864,147 @@ impl Handle {
     ///               A value of zero returns immedately, even if the fd is not ready.
     ///               A negative value means infinite timeout (blocking).
     pub fn poll(&self, events: i16, timeout: i32) -> io::Result<i32> {
-        match unsafe {
-            libc::poll(
-                [libc::pollfd {
-                    fd: self.fd,
-                    events,
-                    revents: 0,
-                }]
-                .as_mut_ptr(),
-                1,
-                timeout,
-            )
-        } {
+        Ok(i32::from(self.poll_revents(events, timeout)? != 0))
+    }
+
+    /// Polls the file descriptor, returns the events which occurred (zero on timeout)
+    pub(crate) fn poll_revents(&self, events: i16, timeout: i32) -> io::Result<i16> {
+        let mut fds = [libc::pollfd {
+            fd: self.fd,
+            events,
+            revents: 0,
+        }];
+        match unsafe { libc::poll(fds.as_mut_ptr(), 1, timeout) } {
             -1 => Err(io::Error::last_os_error()),
             ret => {
                 // A return value of zero means that we timed out. A positive value signifies the
                 // number of fds with non-zero revents fields (aka I/O activity).
                 assert!(ret == 0 || ret == 1);
-                Ok(ret)
+                Ok(fds[0].revents)
+            }
+        }
+    }
+
+    /// Waits until a buffer can be dequeued, returns `false` on timeout
+    ///
+    /// With `watch_source_change`, pending events are checked as well and a source change is
+    /// reported as [`SourceChanged`] error. Wakeups caused by other events do not count as
+    /// ready, the wait continues until a buffer is done.
+    ///
+    /// # Arguments
+    ///
+    /// * `watch_source_change` - Whether to check for source changes
+    /// * `timeout` - Timeout in milliseconds, negative values block
+    pub(crate) fn wait_dequeue(&self, watch_source_change: bool, timeout: i32) -> io::Result<bool> {
+        // Events are signaled through POLLPRI
+        let events = if watch_source_change {
+            libc::POLLIN | libc::POLLPRI
+        } else {
+            libc::POLLIN
+        };
+        let deadline =
+            (timeout > 0).then(|| Instant::now() + Duration::from_millis(timeout as u64));
+
+        let mut timeout = timeout;
+        loop {
+            let revents = self.poll_revents(events, timeout)?;
+            if revents == 0 {
+                return Ok(false);
+            }
+            if revents & libc::POLLPRI != 0 && self.source_changed()? {
+                return Err(SourceChanged.into());
+            }
+            // Anything but an event (e.g. POLLERR) is left to VIDIOC_DQBUF to report
+            if revents & !libc::POLLPRI != 0 {
+                return Ok(true);
+            }
+
+            if let Some(deadline) = deadline {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                timeout = remaining.as_millis() as i32;
+            }
+        }
+    }
+
+    /// Subscribes to an event type (`V4L2_EVENT_*`) for the object with the given id
+    pub(crate) fn subscribe_event(&self, typ: u32, id: u32) -> io::Result<()> {
+        unsafe {
+            let mut v4l2_sub = v4l2_event_subscription {
+                type_: typ,
+                id,
+                ..mem::zeroed()
+            };
+            v4l2::ioctl(
+                self.fd,
+                v4l2::vidioc::VIDIOC_SUBSCRIBE_EVENT,
+                &mut v4l2_sub as *mut _ as *mut std::os::raw::c_void,
+            )
+        }
+    }
+
+    /// Unsubscribes from an event type (`V4L2_EVENT_*`) for the object with the given id
+    pub(crate) fn unsubscribe_event(&self, typ: u32, id: u32) -> io::Result<()> {
+        unsafe {
+            let mut v4l2_sub = v4l2_event_subscription {
+                type_: typ,
+                id,
+                ..mem::zeroed()
+            };
+            v4l2::ioctl(
+                self.fd,
+                v4l2::vidioc::VIDIOC_UNSUBSCRIBE_EVENT,
+                &mut v4l2_sub as *mut _ as *mut std::os::raw::c_void,
+            )
+        }
+    }
+
+    /// Dequeues a pending event, returns `None` if there is none
+    pub(crate) fn dequeue_event(&self) -> io::Result<Option<v4l2_event>> {
+        if let Some(event) = self.events.lock().unwrap().pop_front() {
+            return Ok(Some(event));
+        }
+
+        self.dequeue_kernel_event()
+    }
+
+    fn dequeue_kernel_event(&self) -> io::Result<Option<v4l2_event>> {
+        unsafe {
+            let mut v4l2_event: v4l2_event = mem::zeroed();
+            match v4l2::ioctl(
+                self.fd,
+                v4l2::vidioc::VIDIOC_DQEVENT,
+                &mut v4l2_event as *mut _ as *mut std::os::raw::c_void,
+            ) {
+                Ok(()) => Ok(Some(v4l2_event)),
+                Err(e) if e.raw_os_error() == Some(libc::ENOENT) => Ok(None),
+                Err(e) => Err(e),
+            }
+        }
+    }
+
+    /// Dequeues all pending events, returns whether the source resolution changed
+    ///
+    /// The kernel hands out events in order only, so all of them are dequeued. Source change
+    /// events are consumed, all others are kept for [`Handle::dequeue_event`]. Note that kept
+    /// events are no longer signaled through `POLLPRI`. At most [`MAX_KEPT_EVENTS`] are kept,
+    /// older ones are dropped.
+    pub(crate) fn source_changed(&self) -> io::Result<bool> {
+        let mut changed = false;
+        while let Some(event) = self.dequeue_kernel_event()? {
+            if event.type_ != V4L2_EVENT_SOURCE_CHANGE {
+                keep_event(&mut self.events.lock().unwrap(), event);
+            } else if unsafe { event.u.src_change.changes } & V4L2_EVENT_SRC_CH_RESOLUTION != 0 {
+                changed = true;
             }
         }
+
+        Ok(changed)
+    }
+}
+
+impl AsRawFd for Handle {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
     }
 }
 
@@ -410,3 +3013,145 @@ impl Drop for Handle {
         v4l2::close(self.fd).unwrap();
     }
 }
+
+/// Enumerates the items of a menu control
+///
+/// Returns `None` if the query failed for every single index, since such a menu is unusable and
+/// must not look like a valid (empty) one.
+///
+/// # Arguments
+///
+/// * `v4l2_ctrl` - Menu control
+/// * `typ` - Type of the control, either a menu or an integer menu
+/// * `query` - Queries a menu item, i.e. issues `VIDIOC_QUERYMENU`
+fn menu_items<F>(
+    v4l2_ctrl: &v4l2_query_ext_ctrl,
+    typ: control::Type,
+    mut query: F,
+) -> Option<Vec<(u32, control::MenuItem)>>
+where
+    F: FnMut(&mut v4l2_querymenu) -> io::Result<()>,
+{
+    let mut items = Vec::new();
+
+    for i in (v4l2_ctrl.minimum..=v4l2_ctrl.maximum).step_by(v4l2_ctrl.step as usize) {
+        let mut v4l2_menu = v4l2_querymenu {
+            id: v4l2_ctrl.id,
+            index: i as u32,
+            ..unsafe { mem::zeroed() }
+        };
+
+        // BEWARE OF DRAGONS!
+        // The API docs [1] state VIDIOC_QUERYMENU should may return EINVAL
+        // for some indices between minimum and maximum when an item is not
+        // supported by a driver.
+        //
+        // I have no idea why it is advertised in the first place then, but
+        // have seen this happen with a Logitech C920 HD Pro webcam.
+        // In case of errors, let's just skip the offending index.
+        //
+        // [1] https://github.com/torvalds/linux/blob/master/Documentation/userspace-api/media/v4l/vidioc-queryctrl.rst#description
+        if query(&mut v4l2_menu).is_err() {
+            continue;
+        }
+
+        let item = control::MenuItem::try_from((typ, v4l2_menu)).unwrap();
+        items.push((v4l2_menu.index, item));
+    }
+
+    // Some drivers fail the query for every single index.
+    let queried = v4l2_ctrl.maximum >= v4l2_ctrl.minimum;
+    if !items.is_empty() || !queried {
+        Some(items)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn kept_events_are_capped() {
+        let mut events = VecDeque::new();
+        for sequence in 0..MAX_KEPT_EVENTS as u32 + 10 {
+            let event = v4l2_event {
+                sequence,
+                ..unsafe { mem::zeroed() }
+            };
+            keep_event(&mut events, event);
+        }
+
+        // the oldest events are dropped
+        assert_eq!(events.len(), MAX_KEPT_EVENTS);
+        assert_eq!(events.front().map(|e| e.sequence), Some(10));
+        assert_eq!(
+            events.back().map(|e| e.sequence),
+            Some(MAX_KEPT_EVENTS as u32 + 9)
+        );
+    }
+
+    #[test]
+    fn dequeue_wait_honors_timeout() {
+        // The read end of an empty pipe stands in for a device without finished buffers, the
+        // write end is kept open so it does not signal POLLHUP.
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        let handle = Handle::new(fds[0]);
+        let mut writer = unsafe { File::from_raw_fd(fds[1]) };
+
+        let start = Instant::now();
+        assert!(!handle.wait_dequeue(false, 50).unwrap());
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert!(!handle.wait_dequeue(true, 0).unwrap());
+
+        writer.write_all(&[0]).unwrap();
+        assert!(handle.wait_dequeue(false, 0).unwrap());
+        assert!(handle.wait_dequeue(true, -1).unwrap());
+    }
+
+    fn menu_ctrl(minimum: i64, maximum: i64) -> v4l2_query_ext_ctrl {
+        v4l2_query_ext_ctrl {
+            id: V4L2_CID_POWER_LINE_FREQUENCY,
+            minimum,
+            maximum,
+            step: 1,
+            ..unsafe { mem::zeroed() }
+        }
+    }
+
+    #[test]
+    fn menu_items_all_failed() {
+        let ctrl = menu_ctrl(0, 3);
+        let items = menu_items(&ctrl, control::Type::Menu, |_| {
+            Err(io::Error::from_raw_os_error(libc::EINVAL))
+        });
+        assert!(items.is_none());
+    }
+
+    #[test]
+    fn menu_items_some_failed() {
+        let ctrl = menu_ctrl(0, 3);
+        let items = menu_items(&ctrl, control::Type::Menu, |v4l2_menu| {
+            if v4l2_menu.index == 1 {
+                return Err(io::Error::from_raw_os_error(libc::EINVAL));
+            }
+            unsafe { v4l2_menu.__bindgen_anon_1.name[0] = b'a' as _ };
+            Ok(())
+        })
+        .unwrap();
+        let indices: Vec<u32> = items.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indices, vec![0, 2, 3]);
+    }
+
+    #[test]
+    fn menu_items_empty_range() {
+        let ctrl = menu_ctrl(1, 0);
+        let items = menu_items(&ctrl, control::Type::Menu, |_| unreachable!());
+        assert_eq!(items.unwrap().len(), 0);
+    }
+}
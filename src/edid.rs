@@ -0,0 +1,60 @@
+//! Extended Display Identification Data (EDID) of HDMI, DisplayPort and DVI receivers
+//!
+//! A capture device announces the timings it accepts through its EDID, which sources read to
+//! pick their output mode. See [`crate::device::Device::read_edid`] and
+//! [`crate::device::Device::write_edid`].
+
+use std::io;
+
+/// Size of one EDID block in bytes
+pub const BLOCK_SIZE: usize = 128;
+
+/// Maximum number of blocks supported by the V4L2 API
+pub const MAX_BLOCKS: usize = 256;
+
+/// Verifies that data can be written as EDID
+///
+/// The data must consist of whole blocks, at most [`MAX_BLOCKS`] of them. Empty data is valid
+/// and clears the EDID. Other lengths are rejected with an error of kind
+/// [`io::ErrorKind::InvalidInput`].
+///
+/// # Arguments
+///
+/// * `data` - EDID to write
+///
+/// # Example
+///
+/// ```
+/// use v4l::edid::{verify, BLOCK_SIZE};
+///
+/// assert!(verify(&[0; 2 * BLOCK_SIZE]).is_ok());
+/// assert!(verify(&[]).is_ok());
+/// assert!(verify(&[0; 100]).is_err());
+/// assert!(verify(&vec![0; 257 * BLOCK_SIZE]).is_err());
+/// ```
+pub fn verify(data: &[u8]) -> io::Result<()> {
+    // usize::is_multiple_of() needs Rust 1.87
+    #[allow(clippy::manual_is_multiple_of)]
+    if data.len() % BLOCK_SIZE != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "EDID of {} bytes is not a multiple of the block size ({} bytes)",
+                data.len(),
+                BLOCK_SIZE
+            ),
+        ));
+    }
+    if data.len() / BLOCK_SIZE > MAX_BLOCKS {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "EDID of {} blocks exceeds the maximum of {} blocks",
+                data.len() / BLOCK_SIZE,
+                MAX_BLOCKS
+            ),
+        ));
+    }
+
+    Ok(())
+}
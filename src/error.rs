@@ -0,0 +1,137 @@
+//! Classification of the errors returned by this crate
+//!
+//! All fallible functions return [`io::Result`] carrying the error code of the failed ioctl.
+//! Convert an error into an [`Error`] to tell the common V4L2 failure cases apart without
+//! matching on errno values.
+
+use std::{error, fmt, io};
+
+/// Result type using the classified [`Error`]
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Classified V4L2 error, each variant wraps the original error
+///
+/// Both operating system errors and errors created by this crate (based on their
+/// [`io::ErrorKind`]) are classified.
+///
+/// # Example
+///
+/// ```
+/// use v4l::device::Device;
+/// use v4l::error::Error;
+///
+/// fn claim(dev: &Device) -> v4l::error::Result<()> {
+///     dev.set_input(1)?;
+///     Ok(())
+/// }
+///
+/// let dev = Device::new(0);
+/// if let Ok(dev) = dev {
+///     match claim(&dev) {
+///         Ok(()) => println!("switched input"),
+///         Err(Error::Busy(_)) => println!("device is streaming"),
+///         Err(Error::InvalidArgument(_)) => println!("no such input"),
+///         Err(e) => println!("failed: {}", e),
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub enum Error {
+    /// The device or driver does not implement the operation (e.g. `ENOTTY`)
+    Unsupported(io::Error),
+    /// The device is in use, e.g. by a streaming or higher priority client (`EBUSY`)
+    Busy(io::Error),
+    /// An argument was rejected (e.g. `EINVAL`)
+    InvalidArgument(io::Error),
+    /// The device did not respond in time (e.g. `ETIMEDOUT`)
+    Timeout(io::Error),
+    /// Any other error
+    Io(io::Error),
+}
+
+impl Error {
+    /// Returns the wrapped error
+    pub fn io_error(&self) -> &io::Error {
+        match self {
+            Error::Unsupported(e)
+            | Error::Busy(e)
+            | Error::InvalidArgument(e)
+            | Error::Timeout(e)
+            | Error::Io(e) => e,
+        }
+    }
+
+    /// Returns the OS error code, if the error originates from a system call
+    pub fn raw_os_error(&self) -> Option<i32> {
+        self.io_error().raw_os_error()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.io_error(), f)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(self.io_error())
+    }
+}
+
+/// Classifies an error by its OS error code or, lacking one, by its kind
+///
+/// # Example
+///
+/// ```
+/// use std::io;
+/// use v4l::error::Error;
+///
+/// let err = Error::from(io::Error::from_raw_os_error(libc::ENOTTY));
+/// assert!(matches!(err, Error::Unsupported(_)));
+/// assert_eq!(err.raw_os_error(), Some(libc::ENOTTY));
+///
+/// let err = Error::from(io::Error::from_raw_os_error(libc::EBUSY));
+/// assert!(matches!(err, Error::Busy(_)));
+///
+/// let err = Error::from(io::Error::new(io::ErrorKind::TimedOut, "VIDIOC_DQBUF"));
+/// assert!(matches!(err, Error::Timeout(_)));
+///
+/// let err = Error::from(io::Error::from_raw_os_error(libc::EIO));
+/// assert!(matches!(err, Error::Io(_)));
+///
+/// // converts back into the original error
+/// let err = io::Error::from(Error::from(io::Error::from_raw_os_error(libc::EINVAL)));
+/// assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+/// ```
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        match err.raw_os_error() {
+            Some(libc::ENOTTY | libc::ENOSYS | libc::EOPNOTSUPP | libc::ENODATA) => {
+                Error::Unsupported(err)
+            }
+            Some(libc::EBUSY) => Error::Busy(err),
+            Some(libc::EINVAL | libc::ERANGE | libc::E2BIG) => Error::InvalidArgument(err),
+            Some(libc::ETIMEDOUT | libc::ETIME) => Error::Timeout(err),
+            Some(_) => Error::Io(err),
+            None => match err.kind() {
+                io::ErrorKind::Unsupported => Error::Unsupported(err),
+                io::ErrorKind::InvalidInput => Error::InvalidArgument(err),
+                io::ErrorKind::TimedOut => Error::Timeout(err),
+                _ => Error::Io(err),
+            },
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Unsupported(e)
+            | Error::Busy(e)
+            | Error::InvalidArgument(e)
+            | Error::Timeout(e)
+            | Error::Io(e) => e,
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt;
+
+use crate::control;
+use crate::v4l_sys::*;
+
+/// Event type
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Type {
+    /// All events, only valid for unsubscribing
+    All,
+    Vsync,
+    Eos,
+    Ctrl,
+    FrameSync,
+    SourceChange,
+    MotionDet,
+    Unknown(u32),
+}
+
+/// Converts a raw event type code, unknown codes are preserved in [`Type::Unknown`]
+impl From<u32> for Type {
+    fn from(repr: u32) -> Self {
+        match repr {
+            0 => Self::All,
+            1 => Self::Vsync,
+            2 => Self::Eos,
+            3 => Self::Ctrl,
+            4 => Self::FrameSync,
+            5 => Self::SourceChange,
+            6 => Self::MotionDet,
+            repr => Self::Unknown(repr),
+        }
+    }
+}
+
+impl From<Type> for u32 {
+    fn from(t: Type) -> Self {
+        match t {
+            Type::All => 0,
+            Type::Vsync => 1,
+            Type::Eos => 2,
+            Type::Ctrl => 3,
+            Type::FrameSync => 4,
+            Type::SourceChange => 5,
+            Type::MotionDet => 6,
+            Type::Unknown(t) => t,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+bitflags::bitflags! {
+    /// Changes reported by control events
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct CtrlChanges: u32 {
+        const VALUE         = 0x00000001;
+        const FLAGS         = 0x00000002;
+        const RANGE         = 0x00000004;
+        const DIMENSIONS    = 0x00000008;
+    }
+}
+
+impl From<u32> for CtrlChanges {
+    fn from(flags: u32) -> Self {
+        Self::from_bits_retain(flags)
+    }
+}
+
+impl From<CtrlChanges> for u32 {
+    fn from(flags: CtrlChanges) -> Self {
+        flags.bits()
+    }
+}
+
+impl fmt::Display for CtrlChanges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+bitflags::bitflags! {
+    /// Changes reported by source change events
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct SourceChanges: u32 {
+        const RESOLUTION    = 0x00000001;
+    }
+}
+
+impl From<u32> for SourceChanges {
+    fn from(flags: u32) -> Self {
+        Self::from_bits_retain(flags)
+    }
+}
+
+impl From<SourceChanges> for u32 {
+    fn from(flags: SourceChanges) -> Self {
+        flags.bits()
+    }
+}
+
+impl fmt::Display for SourceChanges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Debug)]
+/// Event as returned by [`crate::v4l2::vidioc::VIDIOC_DQEVENT`]
+pub enum Event {
+    /// Vertical sync of an output device
+    Vsync,
+    /// Last buffer of the stream has been dequeued (e.g. by a decoder)
+    Eos,
+    /// A control changed
+    CtrlChanged {
+        /// Control identifier
+        id: u32,
+        /// What changed
+        changes: CtrlChanges,
+        /// Current value (only valid for integer, boolean, menu and button controls)
+        value: i64,
+        /// Current control flags
+        flags: control::Flags,
+        /// Minimum value, inclusive
+        minimum: i32,
+        /// Maximum value, inclusive
+        maximum: i32,
+        /// Step size
+        step: i32,
+        /// Default value
+        default: i32,
+    },
+    /// Start of a frame has been received
+    FrameSync {
+        /// Sequence number of the frame
+        sequence: u32,
+    },
+    /// The source (e.g. the HDMI signal) changed, buffers may have to be reallocated
+    SourceChange {
+        /// Pad or input index the event applies to
+        id: u32,
+        /// What changed
+        changes: SourceChanges,
+    },
+    /// Any other event, including motion detection and driver specific ones
+    Other {
+        /// Event type
+        typ: Type,
+        /// Object the event applies to
+        id: u32,
+        /// Raw event payload
+        data: [u8; 64],
+    },
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Vsync => write!(f, "vsync"),
+            Event::Eos => write!(f, "end of stream"),
+            Event::CtrlChanged {
+                id, changes, value, ..
+            } => write!(f, "control {:#x} changed ({}): {}", id, changes, value),
+            Event::FrameSync { sequence } => write!(f, "frame sync: {}", sequence),
+            Event::SourceChange { id, changes } => {
+                write!(f, "source {} changed ({})", id, changes)
+            }
+            Event::Other { typ, id, .. } => write!(f, "{} event for {}", typ, id),
+        }
+    }
+}
+
+impl From<v4l2_event> for Event {
+    fn from(event: v4l2_event) -> Self {
+        unsafe {
+            match Type::from(event.type_) {
+                Type::Vsync => Event::Vsync,
+                Type::Eos => Event::Eos,
+                Type::Ctrl => {
+                    let ctrl = event.u.ctrl;
+                    let value = if control::Type::from(ctrl.type_) == control::Type::Integer64 {
+                        ctrl.__bindgen_anon_1.value64
+                    } else {
+                        ctrl.__bindgen_anon_1.value as i64
+                    };
+                    Event::CtrlChanged {
+                        id: event.id,
+                        changes: CtrlChanges::from(ctrl.changes),
+                        value,
+                        flags: control::Flags::from(ctrl.flags),
+                        minimum: ctrl.minimum,
+                        maximum: ctrl.maximum,
+                        step: ctrl.step,
+                        default: ctrl.default_value,
+                    }
+                }
+                Type::FrameSync => Event::FrameSync {
+                    sequence: event.u.frame_sync.frame_sequence,
+                },
+                Type::SourceChange => Event::SourceChange {
+                    id: event.id,
+                    changes: SourceChanges::from(event.u.src_change.changes),
+                },
+                typ => Event::Other {
+                    typ,
+                    id: event.id,
+                    data: event.u.data,
+                },
+            }
+        }
+    }
+}
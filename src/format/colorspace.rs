@@ -1,7 +1,8 @@
 use std::convert::TryFrom;
 use std::fmt;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 /// Colorspace for pixels.
 ///
@@ -54,6 +55,7 @@ impl fmt::Display for Colorspace {
     }
 }
 
+/// Converts a raw colorspace code
 impl TryFrom<u32> for Colorspace {
     type Error = ();
 
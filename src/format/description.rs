@@ -8,7 +8,26 @@ bitflags::bitflags! {
     pub struct Flags : u32 {
         const COMPRESSED            = 0x0001;
         const EMULATED              = 0x0002;
+        /// The (decoder) accepts arbitrarily sized chunks of the bitstream instead of whole
+        /// frames per buffer.
         const CONTINUOUS_BITSTREAM  = 0x0004;
+        /// The resolution of this (coded) format is parsed from the bitstream at runtime.
+        ///
+        /// Stateful decoders report this for formats such as H.264. The decoded resolution is
+        /// not known until the decoder has seen the stream headers, so the capture queue can only
+        /// be set up afterwards:
+        ///
+        /// 1. Set the coded format on the output queue. The resolution can be left as is.
+        /// 2. Subscribe to [`crate::event::Type::SourceChange`] events.
+        /// 3. Start streaming on the output queue and feed it the bitstream until a source
+        ///    change event arrives (see [`crate::device::Device::try_dequeue_event`]).
+        /// 4. Read the decoded format from the capture queue and allocate its buffers, e.g. by
+        ///    creating a [`crate::io::mmap::Stream`] for it.
+        /// 5. Decode while watching for further source changes (see
+        ///    [`crate::io::mmap::Stream::watch_source_change`]), which require the capture
+        ///    buffers to be reallocated.
+        ///
+        /// See the `decode_mmap` example for a skeleton implementation.
         const DYN_RESOLUTION        = 0x0008;
     }
 }
@@ -1,7 +1,8 @@
 use std::convert::TryFrom;
 use std::fmt;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 /// Represents how fields are interlaced (if they are)
 pub enum FieldOrder {
@@ -44,6 +45,7 @@ impl fmt::Display for FieldOrder {
     }
 }
 
+/// Converts a raw field order code
 impl TryFrom<u32> for FieldOrder {
     type Error = ();
 
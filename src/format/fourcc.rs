@@ -1,6 +1,9 @@
-use std::{fmt, str};
+use std::convert::TryFrom;
+use std::{error, fmt, io, str};
 
-#[derive(Debug, Default, Copy, Clone, Eq)]
+pub mod consts;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 /// Four character code representing a pixelformat
 pub struct FourCC {
     pub repr: [u8; 4],
@@ -20,7 +23,7 @@ impl FourCC {
     /// use v4l::format::FourCC;
     /// let fourcc = FourCC::new(b"YUYV");
     /// ```
-    pub fn new(repr: &[u8; 4]) -> FourCC {
+    pub const fn new(repr: &[u8; 4]) -> FourCC {
         FourCC { repr: *repr }
     }
 
@@ -36,8 +39,140 @@ impl FourCC {
     pub fn str(&self) -> Result<&str, str::Utf8Error> {
         str::from_utf8(&self.repr)
     }
+
+    /// Returns a four character code from an arbitrary string
+    ///
+    /// Unlike [`str::parse`], this never fails: non-ASCII characters are replaced by `?`,
+    /// shorter strings are padded with spaces (as in `"Y10 "`) and longer ones are truncated.
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - Pixelformat name, e.g. "YUYV"
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::format::FourCC;
+    ///
+    /// assert_eq!(FourCC::from_str_lossy("Y10"), FourCC::new(b"Y10 "));
+    /// assert_eq!(FourCC::from_str_lossy("MJPEG"), FourCC::new(b"MJPE"));
+    /// assert_eq!(FourCC::from_str_lossy("Y\u{fc}YV"), FourCC::new(b"Y?YV"));
+    /// ```
+    pub fn from_str_lossy(s: &str) -> FourCC {
+        let mut repr = *b"    ";
+        for (dst, c) in repr.iter_mut().zip(s.chars()) {
+            *dst = if c.is_ascii() { c as u8 } else { b'?' };
+        }
+        FourCC { repr }
+    }
+
+    /// Returns a table of well known pixelformats along with a human readable description
+    ///
+    /// This is handy for listing format choices in command line help or completion. The table
+    /// is not exhaustive, devices may well support formats which are not listed here.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::format::FourCC;
+    ///
+    /// let known = FourCC::all_known();
+    /// assert!(known.iter().any(|(fourcc, _)| *fourcc == FourCC::new(b"YUYV")));
+    /// for (fourcc, name) in known {
+    ///     println!("{}: {}", fourcc, name);
+    /// }
+    /// ```
+    pub fn all_known() -> &'static [(FourCC, &'static str)] {
+        KNOWN
+    }
+
+    /// Returns the average number of bytes per pixel or `None` for compressed and unknown formats
+    ///
+    /// Only common uncompressed formats are covered. For planar formats, the chroma planes are
+    /// included, e.g. NV12 yields 1.5.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::format::FourCC;
+    ///
+    /// assert_eq!(FourCC::new(b"GREY").bytes_per_pixel(), Some(1.0));
+    /// assert_eq!(FourCC::new(b"YUYV").bytes_per_pixel(), Some(2.0));
+    /// assert_eq!(FourCC::new(b"RGB3").bytes_per_pixel(), Some(3.0));
+    /// assert_eq!(FourCC::new(b"RGB4").bytes_per_pixel(), Some(4.0));
+    /// assert_eq!(FourCC::new(b"NV12").bytes_per_pixel(), Some(1.5));
+    /// assert_eq!(FourCC::new(b"MJPG").bytes_per_pixel(), None);
+    /// assert_eq!(FourCC::new(b"H264").bytes_per_pixel(), None);
+    /// ```
+    pub fn bytes_per_pixel(&self) -> Option<f32> {
+        self.bits_per_pixel().map(|(bits, _)| bits as f32 / 8.0)
+    }
+
+    /// Returns the bits per pixel of the whole image and of the first plane, which the stride
+    /// refers to
+    pub(crate) fn bits_per_pixel(&self) -> Option<(u32, u32)> {
+        let bits = match &self.repr {
+            b"GREY" | b"BA81" | b"GBRG" | b"GRBG" | b"RGGB" => (8, 8),
+            b"Y10 " | b"Y12 " | b"Y16 " | b"RGBP" | b"RGBO" | b"RGBR" => (16, 16),
+            b"YUYV" | b"YVYU" | b"UYVY" | b"VYUY" => (16, 16),
+            b"RGB3" | b"BGR3" => (24, 24),
+            b"RGB4" | b"BGR4" | b"AR24" | b"XR24" | b"AB24" | b"XB24" => (32, 32),
+            b"NV12" | b"NV21" | b"YU12" | b"YV12" => (12, 8),
+            b"NV16" | b"NV61" | b"422P" => (16, 8),
+            b"NV24" | b"NV42" => (24, 8),
+            _ => return None,
+        };
+        Some(bits)
+    }
+}
+
+macro_rules! known {
+    ($($repr:literal => $name:literal,)*) => {
+        &[$((FourCC { repr: *$repr }, $name),)*]
+    };
 }
 
+/// Descriptions as used by the kernel (see v4l_fill_fmtdesc in v4l2-ioctl.c)
+static KNOWN: &[(FourCC, &str)] = known! {
+    b"RGB3" => "24-bit RGB 8-8-8",
+    b"BGR3" => "24-bit BGR 8-8-8",
+    b"AR24" => "32-bit BGRA 8-8-8-8",
+    b"XR24" => "32-bit BGRX 8-8-8-8",
+    b"AB24" => "32-bit RGBA 8-8-8-8",
+    b"XB24" => "32-bit RGBX 8-8-8-8",
+    b"RGBP" => "16-bit RGB 5-6-5",
+    b"GREY" => "8-bit Greyscale",
+    b"Y10 " => "10-bit Greyscale",
+    b"Y12 " => "12-bit Greyscale",
+    b"Y16 " => "16-bit Greyscale",
+    b"YUYV" => "YUYV 4:2:2",
+    b"YVYU" => "YVYU 4:2:2",
+    b"UYVY" => "UYVY 4:2:2",
+    b"VYUY" => "VYUY 4:2:2",
+    b"NV12" => "Y/UV 4:2:0",
+    b"NV21" => "Y/VU 4:2:0",
+    b"NV16" => "Y/UV 4:2:2",
+    b"NV61" => "Y/VU 4:2:2",
+    b"NV24" => "Y/UV 4:4:4",
+    b"YU12" => "Planar YUV 4:2:0",
+    b"YV12" => "Planar YVU 4:2:0",
+    b"422P" => "Planar YUV 4:2:2",
+    b"NM12" => "Y/UV 4:2:0 (N-C)",
+    b"BA81" => "8-bit Bayer BGBG/GRGR",
+    b"GBRG" => "8-bit Bayer GBGB/RGRG",
+    b"GRBG" => "8-bit Bayer GRGR/BGBG",
+    b"RGGB" => "8-bit Bayer RGRG/GBGB",
+    b"MJPG" => "Motion-JPEG",
+    b"JPEG" => "JFIF JPEG",
+    b"H264" => "H.264",
+    b"HEVC" => "HEVC",
+    b"VP80" => "VP8",
+    b"VP90" => "VP9",
+    b"MPG2" => "MPEG-2 ES",
+    b"MPG4" => "MPEG-4 Part 2 ES",
+    b"UVCH" => "UVC Payload Header Metadata",
+};
+
 impl fmt::Display for FourCC {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let string = str::from_utf8(&self.repr);
@@ -48,9 +183,98 @@ impl fmt::Display for FourCC {
     }
 }
 
-impl PartialEq for FourCC {
-    fn eq(&self, other: &FourCC) -> bool {
-        self.repr.iter().zip(other.repr.iter()).all(|(a, b)| a == b)
+/// Error returned when parsing a [`FourCC`] from a string which is not exactly four ASCII bytes
+///
+/// It converts into an [`io::Error`] of kind [`io::ErrorKind::InvalidInput`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFourCCError(pub String);
+
+impl fmt::Display for ParseFourCCError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid four character code {:?}", self.0)
+    }
+}
+
+impl error::Error for ParseFourCCError {}
+
+impl From<ParseFourCCError> for io::Error {
+    fn from(err: ParseFourCCError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidInput, err)
+    }
+}
+
+/// Parses exactly four ASCII characters, the inverse of the [`fmt::Display`] implementation
+///
+/// # Example
+///
+/// ```
+/// use v4l::format::FourCC;
+///
+/// let fourcc: FourCC = "YUYV".parse().unwrap();
+/// assert_eq!(fourcc, FourCC::new(b"YUYV"));
+/// assert_eq!(fourcc.to_string().parse::<FourCC>(), Ok(fourcc));
+/// assert_eq!("Y10 ".parse::<FourCC>(), Ok(FourCC::new(b"Y10 ")));
+///
+/// assert!("Y10".parse::<FourCC>().is_err());
+/// assert!("MJPEG".parse::<FourCC>().is_err());
+/// assert!("Y\u{fc}YV".parse::<FourCC>().is_err());
+/// ```
+impl str::FromStr for FourCC {
+    type Err = ParseFourCCError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match <[u8; 4]>::try_from(s.as_bytes()) {
+            Ok(repr) if s.is_ascii() => Ok(FourCC { repr }),
+            _ => Err(ParseFourCCError(s.to_string())),
+        }
+    }
+}
+
+/// Serializes as string, e.g. "YUYV", in human readable formats
+///
+/// Codes which are not made of ASCII characters (and all codes in binary formats) are serialized
+/// as their raw `u32` value instead, so they survive a round trip unchanged.
+#[cfg(feature = "serde")]
+impl serde::Serialize for FourCC {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.str() {
+            Ok(s) if self.repr.is_ascii() && serializer.is_human_readable() => {
+                serializer.serialize_str(s)
+            }
+            _ => serializer.serialize_u32(u32::from(*self)),
+        }
+    }
+}
+
+/// Deserializes from a string of four ASCII characters or from the raw `u32` value
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FourCC {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = FourCC;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "four ASCII characters or a u32 pixelformat code")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<FourCC, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<FourCC, E> {
+                u32::try_from(v)
+                    .map(FourCC::from)
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(Visitor)
+        } else {
+            deserializer.deserialize_u32(Visitor)
+        }
     }
 }
 
@@ -65,3 +289,28 @@ impl From<FourCC> for u32 {
         Self::from_le_bytes(fourcc.repr)
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use serde::de::{value::Error, Deserialize, IntoDeserializer};
+
+    use super::*;
+
+    #[test]
+    fn deserialize_str_and_code() {
+        let de = IntoDeserializer::<Error>::into_deserializer("YUYV");
+        assert_eq!(FourCC::deserialize(de), Ok(FourCC::new(b"YUYV")));
+
+        let code = u32::from(FourCC::new(&[b'Y', 0xfc, b'Y', b'V']));
+        let de = IntoDeserializer::<Error>::into_deserializer(code);
+        assert_eq!(
+            FourCC::deserialize(de),
+            Ok(FourCC::new(&[b'Y', 0xfc, b'Y', b'V']))
+        );
+
+        let de = IntoDeserializer::<Error>::into_deserializer(u64::from(u32::MAX) + 1);
+        assert!(FourCC::deserialize(de).is_err());
+        let de = IntoDeserializer::<Error>::into_deserializer("MJPEG");
+        assert!(FourCC::deserialize(de).is_err());
+    }
+}
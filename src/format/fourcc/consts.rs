@@ -0,0 +1,111 @@
+//! Well known pixelformats as associated constants of [`FourCC`]
+//!
+//! The constants are named after the `V4L2_PIX_FMT_*` macros of the kernel, with the exception
+//! of [`FourCC::MJPG`] which matches its four character code. Being `const`, they can be used in
+//! `match` arms:
+//!
+//! ```
+//! use v4l::format::FourCC;
+//!
+//! let fourcc = FourCC::new(b"MJPG");
+//! match fourcc {
+//!     FourCC::RGB24 => println!("raw frame"),
+//!     FourCC::MJPG | FourCC::JPEG => println!("compressed frame"),
+//!     _ => println!("unsupported format"),
+//! }
+//! assert_eq!(FourCC::RGB24, FourCC::new(b"RGB3"));
+//! ```
+
+use super::FourCC;
+
+impl FourCC {
+    /* Packed YUV */
+    /// YUYV 4:2:2
+    pub const YUYV: FourCC = FourCC::new(b"YUYV");
+    /// YVYU 4:2:2
+    pub const YVYU: FourCC = FourCC::new(b"YVYU");
+    /// UYVY 4:2:2
+    pub const UYVY: FourCC = FourCC::new(b"UYVY");
+    /// VYUY 4:2:2
+    pub const VYUY: FourCC = FourCC::new(b"VYUY");
+
+    /* Semi-planar and planar YUV */
+    /// Y/UV 4:2:0
+    pub const NV12: FourCC = FourCC::new(b"NV12");
+    /// Y/VU 4:2:0
+    pub const NV21: FourCC = FourCC::new(b"NV21");
+    /// Y/UV 4:2:2
+    pub const NV16: FourCC = FourCC::new(b"NV16");
+    /// Y/VU 4:2:2
+    pub const NV61: FourCC = FourCC::new(b"NV61");
+    /// Y/UV 4:4:4
+    pub const NV24: FourCC = FourCC::new(b"NV24");
+    /// Y/VU 4:4:4
+    pub const NV42: FourCC = FourCC::new(b"NV42");
+    /// Planar YUV 4:2:0
+    pub const YUV420: FourCC = FourCC::new(b"YU12");
+    /// Planar YVU 4:2:0
+    pub const YVU420: FourCC = FourCC::new(b"YV12");
+    /// Planar YUV 4:2:2
+    pub const YUV422P: FourCC = FourCC::new(b"422P");
+    /// Y/UV 4:2:0 with non-contiguous planes
+    pub const NV12M: FourCC = FourCC::new(b"NM12");
+
+    /* Greyscale */
+    /// 8-bit greyscale
+    pub const GREY: FourCC = FourCC::new(b"GREY");
+    /// 10-bit greyscale
+    pub const Y10: FourCC = FourCC::new(b"Y10 ");
+    /// 12-bit greyscale
+    pub const Y12: FourCC = FourCC::new(b"Y12 ");
+    /// 16-bit greyscale
+    pub const Y16: FourCC = FourCC::new(b"Y16 ");
+
+    /* RGB */
+    /// 16-bit RGB 5-6-5
+    pub const RGB565: FourCC = FourCC::new(b"RGBP");
+    /// 24-bit RGB 8-8-8
+    pub const RGB24: FourCC = FourCC::new(b"RGB3");
+    /// 24-bit BGR 8-8-8
+    pub const BGR24: FourCC = FourCC::new(b"BGR3");
+    /// 32-bit RGB 8-8-8-8 (deprecated, alpha channel is ambiguous)
+    pub const RGB32: FourCC = FourCC::new(b"RGB4");
+    /// 32-bit BGR 8-8-8-8 (deprecated, alpha channel is ambiguous)
+    pub const BGR32: FourCC = FourCC::new(b"BGR4");
+    /// 32-bit BGRA 8-8-8-8
+    pub const ABGR32: FourCC = FourCC::new(b"AR24");
+    /// 32-bit BGRX 8-8-8-8
+    pub const XBGR32: FourCC = FourCC::new(b"XR24");
+    /// 32-bit RGBA 8-8-8-8
+    pub const RGBA32: FourCC = FourCC::new(b"AB24");
+    /// 32-bit RGBX 8-8-8-8
+    pub const RGBX32: FourCC = FourCC::new(b"XB24");
+
+    /* Bayer */
+    /// 8-bit Bayer BGBG/GRGR
+    pub const SBGGR8: FourCC = FourCC::new(b"BA81");
+    /// 8-bit Bayer GBGB/RGRG
+    pub const SGBRG8: FourCC = FourCC::new(b"GBRG");
+    /// 8-bit Bayer GRGR/BGBG
+    pub const SGRBG8: FourCC = FourCC::new(b"GRBG");
+    /// 8-bit Bayer RGRG/GBGB
+    pub const SRGGB8: FourCC = FourCC::new(b"RGGB");
+
+    /* Compressed */
+    /// Motion-JPEG
+    pub const MJPG: FourCC = FourCC::new(b"MJPG");
+    /// JFIF JPEG
+    pub const JPEG: FourCC = FourCC::new(b"JPEG");
+    /// H.264
+    pub const H264: FourCC = FourCC::new(b"H264");
+    /// HEVC (H.265)
+    pub const HEVC: FourCC = FourCC::new(b"HEVC");
+    /// VP8
+    pub const VP8: FourCC = FourCC::new(b"VP80");
+    /// VP9
+    pub const VP9: FourCC = FourCC::new(b"VP90");
+    /// MPEG-2 ES
+    pub const MPEG2: FourCC = FourCC::new(b"MPG2");
+    /// MPEG-4 Part 2 ES
+    pub const MPEG4: FourCC = FourCC::new(b"MPG4");
+}
@@ -12,7 +12,10 @@ pub mod field;
 pub use field::FieldOrder;
 
 pub mod fourcc;
-pub use fourcc::FourCC;
+pub use fourcc::{FourCC, ParseFourCCError};
+
+pub mod mplane;
+pub use mplane::{FormatMplane, PlaneFormat};
 
 pub mod quantization;
 pub use quantization::Quantization;
@@ -22,6 +25,7 @@ pub use transfer::TransferFunction;
 
 bitflags::bitflags! {
     #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Flags : u32 {
         const PREMUL_ALPHA  = 0x00000001;
     }
@@ -45,7 +49,8 @@ impl fmt::Display for Flags {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Streaming format (single-planar)
 pub struct Format {
     /// width in pixels
@@ -102,6 +107,42 @@ impl Format {
             transfer: TransferFunction::Default,
         }
     }
+
+    /// Returns the number of bytes an image of this format is expected to occupy
+    ///
+    /// This is `stride * height`, with the chroma planes added for planar formats since the
+    /// stride only covers the first plane. If the stride is not set, it is derived from the
+    /// width. Formats without a known [`FourCC::bytes_per_pixel`] (e.g. compressed ones) yield
+    /// `stride * height` as is, which is zero if the driver does not report a stride.
+    ///
+    /// Unlike [`Format::size`], which is the maximum size reported by the driver and may include
+    /// padding, this can be used to validate the bytes used by a frame before decoding it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::{Format, FourCC};
+    ///
+    /// let fmt = Format::new(640, 480, FourCC::new(b"YUYV"));
+    /// assert_eq!(fmt.expected_size(), 640 * 480 * 2);
+    ///
+    /// let mut fmt = Format::new(640, 480, FourCC::new(b"NV12"));
+    /// fmt.stride = 768;
+    /// assert_eq!(fmt.expected_size(), 768 * 480 * 3 / 2);
+    /// ```
+    pub fn expected_size(&self) -> u32 {
+        match self.fourcc.bits_per_pixel() {
+            Some((bits, line_bits)) => {
+                let stride = if self.stride > 0 {
+                    u64::from(self.stride)
+                } else {
+                    u64::from(self.width) * u64::from(line_bits) / 8
+                };
+                (stride * u64::from(self.height) * u64::from(bits) / u64::from(line_bits)) as u32
+            }
+            None => self.stride * self.height,
+        }
+    }
 }
 
 impl fmt::Display for Format {
@@ -136,6 +177,27 @@ impl From<v4l2_pix_format> for Format {
     }
 }
 
+/// Every field survives the conversion to the C struct and back:
+///
+/// ```
+/// use v4l::format::{Colorspace, FieldOrder, Flags, Quantization, TransferFunction};
+/// use v4l::v4l_sys::v4l2_pix_format;
+/// use v4l::{Format, FourCC};
+///
+/// let fmt = Format {
+///     width: 1920,
+///     height: 1080,
+///     fourcc: FourCC::new(b"YUYV"),
+///     field_order: FieldOrder::InterlacedBT,
+///     stride: 3840,
+///     size: 4147200,
+///     flags: Flags::PREMUL_ALPHA,
+///     colorspace: Colorspace::Rec709,
+///     quantization: Quantization::LimitedRange,
+///     transfer: TransferFunction::SRGB,
+/// };
+/// assert_eq!(Format::from(v4l2_pix_format::from(fmt)), fmt);
+/// ```
 impl From<Format> for v4l2_pix_format {
     fn from(format: Format) -> Self {
         Self {
@@ -146,6 +208,8 @@ impl From<Format> for v4l2_pix_format {
             bytesperline: format.stride,
             sizeimage: format.size,
             colorspace: format.colorspace as u32,
+            // flags, quantization and xfer_func are only honored with this magic value set
+            priv_: V4L2_PIX_FMT_PRIV_MAGIC,
             flags: format.flags.into(),
             quantization: format.quantization as u32,
             xfer_func: format.transfer as u32,
@@ -153,3 +217,45 @@ impl From<Format> for v4l2_pix_format {
         }
     }
 }
+
+#[derive(Debug, Clone)]
+/// Streaming format of either a single- or a multi-planar device
+pub enum AnyFormat {
+    Single(Format),
+    Multi(FormatMplane),
+}
+
+impl AnyFormat {
+    /// Returns the width in pixels
+    pub fn width(&self) -> u32 {
+        match self {
+            Self::Single(fmt) => fmt.width,
+            Self::Multi(fmt) => fmt.width,
+        }
+    }
+
+    /// Returns the height in pixels
+    pub fn height(&self) -> u32 {
+        match self {
+            Self::Single(fmt) => fmt.height,
+            Self::Multi(fmt) => fmt.height,
+        }
+    }
+
+    /// Returns the pixelformat code
+    pub fn fourcc(&self) -> FourCC {
+        match self {
+            Self::Single(fmt) => fmt.fourcc,
+            Self::Multi(fmt) => fmt.fourcc,
+        }
+    }
+}
+
+impl fmt::Display for AnyFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Single(fmt) => fmt.fmt(f),
+            Self::Multi(fmt) => fmt.fmt(f),
+        }
+    }
+}
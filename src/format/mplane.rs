@@ -0,0 +1,295 @@
+use std::{convert::TryFrom, fmt, io, mem};
+
+use crate::format::{Colorspace, FieldOrder, Flags, FourCC, Quantization, TransferFunction};
+use crate::v4l_sys::*;
+
+/// Maximum number of planes a multi-planar format may consist of
+pub const MAX_PLANES: usize = 8;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+/// Memory layout of a single plane
+pub struct PlaneFormat {
+    /// bytes per line
+    pub stride: u32,
+    /// maximum number of bytes required to store the plane
+    pub size: u32,
+}
+
+impl From<v4l2_plane_pix_format> for PlaneFormat {
+    fn from(fmt: v4l2_plane_pix_format) -> Self {
+        Self {
+            stride: fmt.bytesperline,
+            size: fmt.sizeimage,
+        }
+    }
+}
+
+impl From<PlaneFormat> for v4l2_plane_pix_format {
+    fn from(format: PlaneFormat) -> Self {
+        Self {
+            bytesperline: format.stride,
+            sizeimage: format.size,
+            ..unsafe { mem::zeroed() }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Streaming format (multi-planar)
+pub struct FormatMplane {
+    /// width in pixels
+    pub width: u32,
+    /// height in pixels
+    pub height: u32,
+    /// pixelformat code
+    pub fourcc: FourCC,
+    /// field order for interlacing
+    pub field_order: FieldOrder,
+
+    /// memory layout of the planes, at most [`MAX_PLANES`]
+    pub planes: Vec<PlaneFormat>,
+
+    /// flags set by the application or driver
+    pub flags: Flags,
+
+    /// supplements the pixelformat (fourcc) information
+    pub colorspace: Colorspace,
+    /// the way colors are mapped
+    pub quantization: Quantization,
+    /// the transfer function for the colorspace
+    pub transfer: TransferFunction,
+}
+
+impl FormatMplane {
+    /// Returns a multi-planar format
+    ///
+    /// The number of planes and their layout is determined by the driver once the format is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width in pixels
+    /// * `height` - Height in pixels
+    /// * `fourcc` - Four character code (pixelformat)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::format::FormatMplane;
+    /// use v4l::FourCC;
+    /// let fmt = FormatMplane::new(640, 480, FourCC::new(b"NM12"));
+    /// ```
+    pub fn new(width: u32, height: u32, fourcc: FourCC) -> Self {
+        FormatMplane {
+            width,
+            height,
+            fourcc,
+            field_order: FieldOrder::Any,
+            planes: Vec::new(),
+            flags: Flags::empty(),
+            colorspace: Colorspace::Default,
+            quantization: Quantization::Default,
+            transfer: TransferFunction::Default,
+        }
+    }
+
+    /// Checks whether the format can be passed to the driver without losing information
+    ///
+    /// The C struct only has room for [`MAX_PLANES`] planes and a single byte of flags. Formats
+    /// exceeding either are rejected with an error of kind [`io::ErrorKind::InvalidInput`].
+    pub(crate) fn validate(&self) -> io::Result<()> {
+        if self.planes.len() > MAX_PLANES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "format has {} planes, at most {} are supported",
+                    self.planes.len(),
+                    MAX_PLANES
+                ),
+            ));
+        }
+
+        if u8::try_from(u32::from(self.flags)).is_err() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "format flags {:#x} do not fit into a multi-planar format",
+                    u32::from(self.flags)
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for FormatMplane {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "width          : {}", self.width)?;
+        writeln!(f, "height         : {}", self.height)?;
+        writeln!(f, "fourcc         : {}", self.fourcc)?;
+        writeln!(f, "field          : {}", self.field_order)?;
+        for (i, plane) in self.planes.iter().enumerate() {
+            writeln!(
+                f,
+                "plane {}        : {} stride, {} size",
+                i, plane.stride, plane.size
+            )?;
+        }
+        writeln!(f, "colorspace     : {}", self.colorspace)?;
+        writeln!(f, "quantization   : {}", self.quantization)?;
+        writeln!(f, "transfer       : {}", self.transfer)?;
+        Ok(())
+    }
+}
+
+impl From<v4l2_pix_format_mplane> for FormatMplane {
+    fn from(fmt: v4l2_pix_format_mplane) -> Self {
+        let num_planes = (fmt.num_planes as usize).min(MAX_PLANES);
+        Self {
+            width: fmt.width,
+            height: fmt.height,
+            fourcc: FourCC::from(fmt.pixelformat),
+            field_order: FieldOrder::try_from(fmt.field).expect("Invalid field order"),
+            planes: fmt.plane_fmt[..num_planes]
+                .iter()
+                .map(|plane| PlaneFormat::from(*plane))
+                .collect(),
+            flags: Flags::from(fmt.flags as u32),
+            colorspace: Colorspace::try_from(fmt.colorspace).expect("Invalid colorspace"),
+            // Codes unknown to this crate are left for the driver to decide
+            quantization: Quantization::try_from(u32::from(fmt.quantization))
+                .unwrap_or(Quantization::Default),
+            transfer: TransferFunction::try_from(u32::from(fmt.xfer_func))
+                .unwrap_or(TransferFunction::Default),
+        }
+    }
+}
+
+/// Every field survives the conversion to the C struct and back:
+///
+/// ```
+/// use v4l::format::{
+///     Colorspace, FieldOrder, Flags, FormatMplane, PlaneFormat, Quantization, TransferFunction,
+/// };
+/// use v4l::v4l_sys::v4l2_pix_format_mplane;
+/// use v4l::FourCC;
+///
+/// let fmt = FormatMplane {
+///     width: 1920,
+///     height: 1080,
+///     fourcc: FourCC::new(b"NM12"),
+///     field_order: FieldOrder::Alternate,
+///     planes: vec![
+///         PlaneFormat {
+///             stride: 1920,
+///             size: 2073600,
+///         },
+///         PlaneFormat {
+///             stride: 1984,
+///             size: 1071360,
+///         },
+///     ],
+///     flags: Flags::PREMUL_ALPHA,
+///     colorspace: Colorspace::Rec2020,
+///     quantization: Quantization::FullRange,
+///     transfer: TransferFunction::SMPTE2084,
+/// };
+/// let raw = v4l2_pix_format_mplane::from(fmt.clone());
+/// assert_eq!(raw.num_planes, 2);
+/// assert_eq!(FormatMplane::from(raw), fmt);
+/// ```
+///
+/// The C struct stores flags, quantization and transfer function as `u8`. All defined codes fit
+/// into that range, so no information is lost for any of them. Flags which do not fit are
+/// dropped, [`crate::device::Device::try_format_mplane`] rejects such formats instead.
+///
+/// ```
+/// use std::convert::TryFrom;
+///
+/// use v4l::format::{Colorspace, FormatMplane, Quantization, TransferFunction};
+/// use v4l::v4l_sys::v4l2_pix_format_mplane;
+/// use v4l::FourCC;
+///
+/// let roundtrip = |fmt: FormatMplane| FormatMplane::from(v4l2_pix_format_mplane::from(fmt));
+/// let base = FormatMplane::new(640, 480, FourCC::new(b"NM12"));
+///
+/// for code in 0..=u32::from(u8::MAX) {
+///     if let Ok(colorspace) = Colorspace::try_from(code) {
+///         let fmt = FormatMplane { colorspace, ..base.clone() };
+///         assert_eq!(roundtrip(fmt.clone()), fmt);
+///     }
+///     if let Ok(quantization) = Quantization::try_from(code) {
+///         let fmt = FormatMplane { quantization, ..base.clone() };
+///         assert_eq!(roundtrip(fmt.clone()), fmt);
+///     }
+///     if let Ok(transfer) = TransferFunction::try_from(code) {
+///         let fmt = FormatMplane { transfer, ..base.clone() };
+///         assert_eq!(roundtrip(fmt.clone()), fmt);
+///     }
+/// }
+/// ```
+impl From<FormatMplane> for v4l2_pix_format_mplane {
+    fn from(format: FormatMplane) -> Self {
+        let mut plane_fmt: [v4l2_plane_pix_format; MAX_PLANES] = unsafe { mem::zeroed() };
+        let num_planes = format.planes.len().min(MAX_PLANES);
+        for (dst, src) in plane_fmt.iter_mut().zip(&format.planes) {
+            *dst = (*src).into();
+        }
+
+        Self {
+            width: format.width,
+            height: format.height,
+            pixelformat: format.fourcc.into(),
+            field: format.field_order as u32,
+            colorspace: format.colorspace as u32,
+            plane_fmt,
+            num_planes: num_planes as u8,
+            // The C struct only has room for a single byte here, see FormatMplane::validate()
+            flags: u8::try_from(u32::from(format.flags) & u32::from(u8::MAX)).unwrap(),
+            quantization: u8::try_from(format.quantization as u32).unwrap(),
+            xfer_func: u8::try_from(format.transfer as u32).unwrap(),
+            ..unsafe { mem::zeroed() }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_flags() {
+        let mut fmt = FormatMplane::new(640, 480, FourCC::new(b"NM12"));
+        fmt.flags = Flags::PREMUL_ALPHA;
+        assert!(fmt.validate().is_ok());
+        assert_eq!(v4l2_pix_format_mplane::from(fmt.clone()).flags, 1);
+
+        // bits beyond the first byte cannot be represented
+        fmt.flags = Flags::from(0x101);
+        let err = fmt.validate().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(v4l2_pix_format_mplane::from(fmt).flags, 1);
+    }
+
+    #[test]
+    fn validate_planes() {
+        let mut fmt = FormatMplane::new(640, 480, FourCC::new(b"NM12"));
+        fmt.planes = vec![PlaneFormat::default(); MAX_PLANES];
+        assert!(fmt.validate().is_ok());
+
+        fmt.planes.push(PlaneFormat::default());
+        let err = fmt.validate().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn unknown_codes() {
+        let mut raw =
+            v4l2_pix_format_mplane::from(FormatMplane::new(640, 480, FourCC::new(b"NM12")));
+        raw.quantization = 200;
+        raw.xfer_func = 200;
+        let fmt = FormatMplane::from(raw);
+        assert_eq!(fmt.quantization, Quantization::Default);
+        assert_eq!(fmt.transfer, TransferFunction::Default);
+    }
+}
@@ -1,7 +1,8 @@
 use std::convert::TryFrom;
 use std::fmt;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 /// Quantization for the colorspace.
 ///
@@ -26,6 +27,7 @@ impl fmt::Display for Quantization {
     }
 }
 
+/// Converts a raw quantization code
 impl TryFrom<u32> for Quantization {
     type Error = ();
 
@@ -1,7 +1,8 @@
 use std::convert::TryFrom;
 use std::fmt;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 /// Transfer function for the colorspace. The driver decides this for capture streams and the user
 /// sets it for output streams.
@@ -14,7 +15,7 @@ pub enum TransferFunction {
     SRGB = 2,
     /// opRGB transfer function
     OPRGB = 3,
-    /// SMPTE 230M transfer function
+    /// SMPTE 240M transfer function
     SMPTE240M = 4,
     /// No transfer function
     None = 5,
@@ -39,6 +40,7 @@ impl fmt::Display for TransferFunction {
     }
 }
 
+/// Converts a raw transfer function code
 impl TryFrom<u32> for TransferFunction {
     type Error = ();
 
@@ -2,6 +2,7 @@ use crate::v4l_sys::*;
 use std::fmt;
 
 #[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Fraction used for timing settings
 pub struct Fraction {
     pub numerator: u32,
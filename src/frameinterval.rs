@@ -1,10 +1,12 @@
 use std::convert::TryFrom;
 use std::fmt;
 
+use crate::framesize::{spread, DEFAULT_DISCRETE_LIMIT};
 use crate::{format::FourCC, fraction::Fraction};
 use crate::{v4l_sys, v4l_sys::*};
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Format description as returned by [`crate::v4l2::vidioc::VIDIOC_ENUM_FRAMEINTERVALS`]
 pub struct FrameInterval {
     pub index: u32,
@@ -22,11 +24,120 @@ impl fmt::Display for FrameInterval {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FrameIntervalEnum {
     Discrete(Fraction),
     Stepwise(Stepwise),
 }
 
+impl FrameIntervalEnum {
+    /// Returns all discrete frame intervals, at most [`DEFAULT_DISCRETE_LIMIT`] of them
+    ///
+    /// Use [`FrameIntervalEnum::to_discrete_limited`] to choose a different limit and to find out
+    /// whether the list was truncated.
+    pub fn to_discrete(&self) -> Vec<Fraction> {
+        self.to_discrete_limited(DEFAULT_DISCRETE_LIMIT).0
+    }
+
+    /// Returns at most `limit` discrete frame intervals and whether the list was truncated
+    ///
+    /// If the range holds more intervals than that, it is sampled evenly instead, including its
+    /// minimum and maximum. Intervals which cannot be represented as a fraction of `u32` values
+    /// are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Maximum number of frame intervals to return
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::fraction::Fraction;
+    /// use v4l::frameinterval::{FrameIntervalEnum, Stepwise};
+    ///
+    /// // 30 fps down to 1 fps
+    /// let interval = FrameIntervalEnum::Stepwise(Stepwise {
+    ///     min: Fraction::new(1, 30),
+    ///     max: Fraction::new(1, 1),
+    ///     step: Fraction::new(1, 10_000_000),
+    /// });
+    /// let (intervals, truncated) = interval.to_discrete_limited(8);
+    /// assert_eq!(intervals.len(), 8);
+    /// assert!(truncated);
+    /// ```
+    pub fn to_discrete_limited(&self, limit: usize) -> (Vec<Fraction>, bool) {
+        let stepwise = match self {
+            Self::Discrete(frac) if limit > 0 => return (vec![*frac], false),
+            Self::Discrete(_) => return (Vec::new(), true),
+            Self::Stepwise(stepwise) => stepwise,
+        };
+
+        let (min, max, step) = (stepwise.min, stepwise.max, stepwise.step);
+        if min.denominator == 0 || max.denominator == 0 {
+            return (Vec::new(), limit == 0);
+        }
+
+        // Work on numerators of a common denominator, step sizes of zero are treated as the
+        // smallest possible step
+        let denominator = lcm(
+            lcm(u128::from(min.denominator), u128::from(max.denominator)),
+            u128::from(step.denominator.max(1)),
+        );
+        let scale = |frac: Fraction| {
+            u128::from(frac.numerator) * (denominator / u128::from(frac.denominator.max(1)))
+        };
+        let (first, last, step) = (scale(min), scale(max), scale(step).max(1));
+        if last < first {
+            return (Vec::new(), false);
+        }
+
+        let count = u64::try_from((last - first) / step + 1).unwrap_or(u64::MAX);
+        let mut truncated = count > limit as u64;
+        let mut numerators: Vec<u128> = spread(count, count.min(limit as u64))
+            .map(|k| first + u128::from(k) * step)
+            .collect();
+        // The maximum is not necessarily a whole number of steps away from the minimum
+        if matches!(numerators.last(), Some(&numerator) if numerator != last) {
+            if numerators.len() < limit {
+                numerators.push(last);
+            } else if let Some(numerator) = numerators.last_mut() {
+                *numerator = last;
+                truncated = true;
+            }
+        }
+
+        let intervals = numerators
+            .into_iter()
+            .filter_map(|numerator| reduce(numerator, denominator))
+            .collect();
+        (intervals, truncated)
+    }
+}
+
+/// Returns the greatest common divisor
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+/// Returns the least common multiple of two positive numbers
+fn lcm(a: u128, b: u128) -> u128 {
+    a / gcd(a, b) * b
+}
+
+/// Returns the reduced fraction, or `None` if it does not fit into `u32` values
+fn reduce(numerator: u128, denominator: u128) -> Option<Fraction> {
+    let divisor = gcd(numerator, denominator).max(1);
+    Some(Fraction::new(
+        u32::try_from(numerator / divisor).ok()?,
+        u32::try_from(denominator / divisor).ok()?,
+    ))
+}
+
 impl fmt::Display for FrameIntervalEnum {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -63,6 +174,7 @@ impl TryFrom<v4l2_frmivalenum> for FrameIntervalEnum {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stepwise {
     /// Minimum frame interval (in seconds).
     pub min: Fraction,
@@ -93,3 +205,73 @@ impl TryFrom<v4l2_frmivalenum> for FrameInterval {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fractions(intervals: &[Fraction]) -> Vec<(u32, u32)> {
+        intervals
+            .iter()
+            .map(|frac| (frac.numerator, frac.denominator))
+            .collect()
+    }
+
+    #[test]
+    fn stepwise_within_limit() {
+        let interval = FrameIntervalEnum::Stepwise(Stepwise {
+            min: Fraction::new(1, 30),
+            max: Fraction::new(1, 10),
+            step: Fraction::new(1, 30),
+        });
+        let (intervals, truncated) = interval.to_discrete_limited(8);
+        assert!(!truncated);
+        assert_eq!(fractions(&intervals), vec![(1, 30), (1, 15), (1, 10)]);
+    }
+
+    #[test]
+    fn stepwise_sampled() {
+        let interval = FrameIntervalEnum::Stepwise(Stepwise {
+            min: Fraction::new(1, 30),
+            max: Fraction::new(1, 1),
+            step: Fraction::new(1, 10_000_000),
+        });
+        let (intervals, truncated) = interval.to_discrete_limited(4);
+        assert!(truncated);
+        // the whole range is covered, not just a prefix of it
+        let intervals = fractions(&intervals);
+        assert_eq!(intervals.len(), 4);
+        assert_eq!(intervals[0], (1, 30));
+        assert_eq!(intervals[3], (1, 1));
+    }
+
+    #[test]
+    fn stepwise_uneven() {
+        // the maximum is not a whole number of steps away from the minimum
+        let interval = FrameIntervalEnum::Stepwise(Stepwise {
+            min: Fraction::new(1, 30),
+            max: Fraction::new(1, 1),
+            step: Fraction::new(1, 1),
+        });
+        let (intervals, truncated) = interval.to_discrete_limited(2);
+        assert!(!truncated);
+        assert_eq!(fractions(&intervals), vec![(1, 30), (1, 1)]);
+
+        let (intervals, truncated) = interval.to_discrete_limited(1);
+        assert!(truncated);
+        assert_eq!(fractions(&intervals), vec![(1, 1)]);
+
+        let (intervals, truncated) = interval.to_discrete_limited(0);
+        assert!(truncated);
+        assert!(intervals.is_empty());
+    }
+
+    #[test]
+    fn reduce_fractions() {
+        assert_eq!(gcd(300, 9000), 300);
+        assert_eq!(lcm(30, 10_000_000), 30_000_000);
+        let frac = reduce(333_334, 10_000_000).unwrap();
+        assert_eq!((frac.numerator, frac.denominator), (166_667, 5_000_000));
+        assert!(reduce(1, u128::from(u32::MAX) + 2).is_none());
+    }
+}
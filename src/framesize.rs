@@ -6,6 +6,7 @@ use crate::v4l_sys;
 use crate::v4l_sys::*;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Format description as returned by [`crate::v4l2::vidioc::VIDIOC_ENUM_FRAMESIZES`]
 pub struct FrameSize {
     pub index: u32,
@@ -21,32 +22,179 @@ impl fmt::Display for FrameSize {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FrameSizeEnum {
     Discrete(Discrete),
     Stepwise(Stepwise),
 }
 
+/// Default limit for the number of frame sizes generated from a stepwise range
+pub const DEFAULT_DISCRETE_LIMIT: usize = 256;
+
 impl FrameSizeEnum {
+    /// Returns all discrete frame sizes, at most [`DEFAULT_DISCRETE_LIMIT`] of them
+    ///
+    /// Continuous ranges with small steps easily describe millions of frame sizes. Use
+    /// [`FrameSizeEnum::to_discrete_limited`] to choose a different limit and to find out whether
+    /// the list was truncated.
     pub fn to_discrete(self) -> impl IntoIterator<Item = Discrete> {
-        match self {
-            Self::Discrete(discrete) => vec![discrete],
-            Self::Stepwise(stepwise) => {
-                let mut discrete = Vec::new();
-
-                for width in
-                    (stepwise.min_width..=stepwise.max_width).step_by(stepwise.step_width as usize)
-                {
-                    for height in (stepwise.min_height..=stepwise.max_height)
-                        .step_by(stepwise.step_height as usize)
-                    {
-                        discrete.push(Discrete { width, height });
-                    }
-                }
+        self.to_discrete_limited(DEFAULT_DISCRETE_LIMIT).0
+    }
 
-                discrete
-            }
+    /// Returns at most `limit` discrete frame sizes and whether the list was truncated
+    ///
+    /// If the range holds more sizes than that, it is sampled evenly instead: the widths and
+    /// heights are picked from the whole range, including its smallest and largest size.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Maximum number of frame sizes to return
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::framesize::{FrameSizeEnum, Stepwise};
+    ///
+    /// let size = FrameSizeEnum::Stepwise(Stepwise {
+    ///     min_width: 1,
+    ///     max_width: 3840,
+    ///     step_width: 1,
+    ///     min_height: 1,
+    ///     max_height: 2160,
+    ///     step_height: 1,
+    /// });
+    /// let (sizes, truncated) = size.to_discrete_limited(16);
+    /// assert!(sizes.len() <= 16);
+    /// assert!(truncated);
+    ///
+    /// let largest = sizes.last().unwrap();
+    /// assert_eq!((largest.width, largest.height), (3840, 2160));
+    /// ```
+    pub fn to_discrete_limited(&self, limit: usize) -> (Vec<Discrete>, bool) {
+        let stepwise = match self {
+            Self::Discrete(discrete) if limit > 0 => return (vec![*discrete], false),
+            Self::Discrete(_) => return (Vec::new(), true),
+            Self::Stepwise(stepwise) => stepwise,
+        };
+
+        let widths = step_count(stepwise.min_width, stepwise.max_width, stepwise.step_width);
+        let heights = step_count(
+            stepwise.min_height,
+            stepwise.max_height,
+            stepwise.step_height,
+        );
+        let limit = limit as u64;
+        if widths.saturating_mul(heights) <= limit {
+            return (self.iter_discrete().collect(), false);
         }
+        if limit == 0 {
+            return (Vec::new(), true);
+        }
+
+        // Sample a grid which roughly follows the aspect of the range
+        let aspect = widths as f64 / heights as f64;
+        let cols = ((limit as f64 * aspect).sqrt() as u64).clamp(1, widths.min(limit));
+        let rows = (limit / cols).min(heights);
+        let cols = (limit / rows).min(widths);
+
+        let discrete = spread(widths, cols)
+            .flat_map(|col| {
+                let width = u64::from(stepwise.min_width) + col * u64::from(stepwise.step_width);
+                spread(heights, rows).map(move |row| {
+                    let height =
+                        u64::from(stepwise.min_height) + row * u64::from(stepwise.step_height);
+                    Discrete {
+                        width: width as u32,
+                        height: height as u32,
+                    }
+                })
+            })
+            .collect();
+        (discrete, true)
     }
+
+    /// Returns an iterator over all discrete frame sizes without consuming the enum
+    ///
+    /// Step sizes of zero (as reported by some broken drivers) are treated as one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::framesize::{FrameSizeEnum, Stepwise};
+    ///
+    /// let size = FrameSizeEnum::Stepwise(Stepwise {
+    ///     min_width: 640,
+    ///     max_width: 642,
+    ///     step_width: 0,
+    ///     min_height: 480,
+    ///     max_height: 480,
+    ///     step_height: 0,
+    /// });
+    /// assert_eq!(size.iter_discrete().count(), 3);
+    /// ```
+    pub fn iter_discrete(&self) -> impl Iterator<Item = Discrete> + '_ {
+        let (min_width, max_width, step_width, min_height, max_height, step_height) = match self {
+            Self::Discrete(discrete) => (
+                discrete.width,
+                discrete.width,
+                1,
+                discrete.height,
+                discrete.height,
+                1,
+            ),
+            Self::Stepwise(stepwise) => (
+                stepwise.min_width,
+                stepwise.max_width,
+                stepwise.step_width.max(1),
+                stepwise.min_height,
+                stepwise.max_height,
+                stepwise.step_height.max(1),
+            ),
+        };
+
+        (min_width..=max_width)
+            .step_by(step_width as usize)
+            .flat_map(move |width| {
+                (min_height..=max_height)
+                    .step_by(step_height as usize)
+                    .map(move |height| Discrete { width, height })
+            })
+    }
+}
+
+/// Returns the number of values in a stepwise range
+///
+/// Step sizes of zero (as reported by some broken drivers) are treated as one.
+///
+/// # Arguments
+///
+/// * `min` - First value
+/// * `max` - Last value
+/// * `step` - Distance between the values
+pub(crate) fn step_count(min: u32, max: u32, step: u32) -> u64 {
+    if max < min {
+        return 0;
+    }
+
+    u64::from(max - min) / u64::from(step.max(1)) + 1
+}
+
+/// Returns `samples` indices spread evenly over `0..count`, including the first and last one
+///
+/// A single sample picks the last index.
+///
+/// # Arguments
+///
+/// * `count` - Number of indices to pick from, must be positive
+/// * `samples` - Number of indices to pick, at most `count`
+pub(crate) fn spread(count: u64, samples: u64) -> impl Iterator<Item = u64> + Clone {
+    (0..samples).map(move |i| {
+        if samples > 1 {
+            (u128::from(i) * u128::from(count - 1) / u128::from(samples - 1)) as u64
+        } else {
+            count - 1
+        }
+    })
 }
 
 impl fmt::Display for FrameSizeEnum {
@@ -90,7 +238,8 @@ impl TryFrom<v4l2_frmsizeenum> for FrameSizeEnum {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Discrete {
     /// Width of the frame (in pixels).
     pub width: u32,
@@ -106,6 +255,7 @@ impl fmt::Display for Discrete {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stepwise {
     /// Minimum frame width (in pixels).
     pub min_width: u32,
@@ -149,3 +299,94 @@ impl TryFrom<v4l2_frmsizeenum> for FrameSize {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uhd(step: u32) -> FrameSizeEnum {
+        FrameSizeEnum::Stepwise(Stepwise {
+            min_width: 16,
+            max_width: 3840,
+            step_width: step,
+            min_height: 16,
+            max_height: 2160,
+            step_height: step,
+        })
+    }
+
+    #[test]
+    fn spread_indices() {
+        assert_eq!(
+            spread(10, 10).collect::<Vec<_>>(),
+            (0..10).collect::<Vec<_>>()
+        );
+        assert_eq!(spread(10, 4).collect::<Vec<_>>(), vec![0, 3, 6, 9]);
+        assert_eq!(spread(10, 1).collect::<Vec<_>>(), vec![9]);
+        assert_eq!(
+            spread(u64::from(u32::MAX), 2).last(),
+            Some(u64::from(u32::MAX) - 1)
+        );
+    }
+
+    #[test]
+    fn step_counts() {
+        assert_eq!(step_count(16, 3840, 1), 3825);
+        assert_eq!(step_count(16, 3840, 8), 479);
+        assert_eq!(step_count(640, 640, 0), 1);
+        assert_eq!(step_count(641, 640, 1), 0);
+        assert_eq!(step_count(0, u32::MAX, 1), 1 << 32);
+    }
+
+    #[test]
+    fn stepwise_within_limit() {
+        let size = FrameSizeEnum::Stepwise(Stepwise {
+            min_width: 640,
+            max_width: 1280,
+            step_width: 640,
+            min_height: 480,
+            max_height: 960,
+            step_height: 480,
+        });
+        let (sizes, truncated) = size.to_discrete_limited(4);
+        assert!(!truncated);
+        let sizes: Vec<_> = sizes.iter().map(|d| (d.width, d.height)).collect();
+        assert_eq!(
+            sizes,
+            vec![(640, 480), (640, 960), (1280, 480), (1280, 960)]
+        );
+    }
+
+    #[test]
+    fn stepwise_sampled() {
+        for &limit in &[1, 2, 16, 256] {
+            let (sizes, truncated) = uhd(1).to_discrete_limited(limit);
+            assert!(truncated);
+            assert!(!sizes.is_empty() && sizes.len() <= limit);
+
+            // the whole range is covered, not just a prefix of it
+            let last = sizes.last().unwrap();
+            assert_eq!((last.width, last.height), (3840, 2160));
+            if limit >= 4 {
+                assert_eq!((sizes[0].width, sizes[0].height), (16, 16));
+            }
+        }
+
+        // the samples are part of the range
+        for size in uhd(8).to_discrete_limited(256).0 {
+            assert_eq!((size.width - 16) % 8, 0);
+            assert_eq!((size.height - 16) % 8, 0);
+        }
+    }
+
+    #[test]
+    fn limit_zero() {
+        assert!(uhd(1).to_discrete_limited(0).0.is_empty());
+        let discrete = FrameSizeEnum::Discrete(Discrete {
+            width: 640,
+            height: 480,
+        });
+        assert_eq!(discrete.to_discrete_limited(0).0.len(), 0);
+        assert_eq!(discrete.to_discrete_limited(1).0.len(), 1);
+    }
+}
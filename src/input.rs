@@ -0,0 +1,168 @@
+use std::{fmt, str};
+
+use crate::standard::Standard;
+use crate::v4l_sys::*;
+
+/// Input type
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Type {
+    Tuner,
+    Camera,
+    Touch,
+    Unknown(u32),
+}
+
+/// Converts a raw input type code, unknown codes are preserved in [`Type::Unknown`]
+impl From<u32> for Type {
+    fn from(repr: u32) -> Self {
+        match repr {
+            1 => Self::Tuner,
+            2 => Self::Camera,
+            3 => Self::Touch,
+            repr => Self::Unknown(repr),
+        }
+    }
+}
+
+impl From<Type> for u32 {
+    fn from(t: Type) -> Self {
+        match t {
+            Type::Tuner => 1,
+            Type::Camera => 2,
+            Type::Touch => 3,
+            Type::Unknown(t) => t,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct Status : u32 {
+        /* general */
+        const NO_POWER      = 0x00000001;
+        const NO_SIGNAL     = 0x00000002;
+        const NO_COLOR      = 0x00000004;
+
+        /* sensor orientation */
+        const HFLIP         = 0x00000010;
+        const VFLIP         = 0x00000020;
+
+        /* analog video */
+        const NO_H_LOCK     = 0x00000100;
+        const COLOR_KILL    = 0x00000200;
+        const NO_V_LOCK     = 0x00000400;
+        const NO_STD_LOCK   = 0x00000800;
+
+        /* digital video */
+        const NO_SYNC       = 0x00010000;
+        const NO_EQU        = 0x00020000;
+        const NO_CARRIER    = 0x00040000;
+
+        /* VCR and set-top box */
+        const MACROVISION   = 0x01000000;
+        const NO_ACCESS     = 0x02000000;
+        const VTR           = 0x04000000;
+    }
+}
+
+impl From<u32> for Status {
+    fn from(flags: u32) -> Self {
+        Self::from_bits_retain(flags)
+    }
+}
+
+impl From<Status> for u32 {
+    fn from(flags: Status) -> Self {
+        flags.bits()
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct Capabilities : u32 {
+        const DV_TIMINGS    = 0x00000002;
+        const STD           = 0x00000004;
+        const NATIVE_SIZE   = 0x00000008;
+    }
+}
+
+impl From<u32> for Capabilities {
+    fn from(flags: u32) -> Self {
+        Self::from_bits_retain(flags)
+    }
+}
+
+impl From<Capabilities> for u32 {
+    fn from(flags: Capabilities) -> Self {
+        flags.bits()
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Debug)]
+/// Video input as returned by [`crate::v4l2::vidioc::VIDIOC_ENUMINPUT`]
+pub struct Input {
+    /// Index of the input, used to select it
+    pub index: u32,
+    /// Name of the input, e.g. "HDMI 1" or "Composite"
+    pub name: String,
+    /// Type of the input
+    pub typ: Type,
+    /// Bitmask of the audio inputs associated with this input
+    pub audioset: u32,
+    /// Index of the tuner, only valid for tuner inputs
+    pub tuner: u32,
+    /// Supported analog video standards
+    pub std: Standard,
+    /// Current status of the input
+    pub status: Status,
+    /// Input capabilities
+    pub capabilities: Capabilities,
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "index        : {}", self.index)?;
+        writeln!(f, "name         : {}", self.name)?;
+        writeln!(f, "type         : {}", self.typ)?;
+        writeln!(f, "std          : {}", self.std)?;
+        writeln!(f, "status       : {}", self.status)?;
+        writeln!(f, "capabilities : {}", self.capabilities)?;
+        Ok(())
+    }
+}
+
+impl From<v4l2_input> for Input {
+    fn from(input: v4l2_input) -> Self {
+        Self {
+            index: input.index,
+            name: str::from_utf8(&input.name)
+                .unwrap()
+                .trim_matches(char::from(0))
+                .to_string(),
+            typ: Type::from(input.type_),
+            audioset: input.audioset,
+            tuner: input.tuner,
+            std: Standard::from(input.std),
+            status: Status::from(input.status),
+            capabilities: Capabilities::from(input.capabilities),
+        }
+    }
+}
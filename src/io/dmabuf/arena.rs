@@ -0,0 +1,229 @@
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd};
+use std::{io, mem, sync::Arc};
+
+use crate::buffer;
+use crate::device::Handle;
+use crate::memory::{self, Memory};
+use crate::v4l2;
+use crate::v4l_sys::*;
+
+/// Buffer exported as dma-buf
+#[derive(Debug)]
+pub struct Buffer {
+    /// index of the buffer in the driver queue
+    pub index: usize,
+    /// dma-buf file descriptor, closed when the buffer is dropped
+    fd: OwnedFd,
+    /// size of the buffer in bytes
+    pub length: usize,
+}
+
+impl Buffer {
+    /// Returns the dma-buf file descriptor
+    ///
+    /// The descriptor is borrowed from the arena and closed along with it. Use
+    /// [`BorrowedFd::try_clone_to_owned`] if it needs to outlive the stream.
+    pub fn fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+impl AsFd for Buffer {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+impl AsRawFd for Buffer {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+/// Manage dma-buf buffers
+///
+/// The driver allocates the memory, which is then exported as dma-buf file descriptors
+/// (`VIDIOC_EXPBUF`) and imported again (`V4L2_MEMORY_DMABUF`). This way, the very same buffers
+/// can be shared with other devices such as GPUs or encoders.
+///
+/// All file descriptors are closed once the arena is dropped.
+pub struct Arena {
+    handle: Arc<Handle>,
+    bufs: Vec<Buffer>,
+    pub buf_type: buffer::Type,
+}
+
+impl Arena {
+    /// Returns a new buffer manager instance
+    ///
+    /// You usually do not need to use this directly.
+    /// A DmaBufStream creates its own manager instance by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `handle` - Device handle to get its file descriptor
+    /// * `buf_type` - Type of the buffers
+    pub fn new(handle: Arc<Handle>, buf_type: buffer::Type) -> Self {
+        Arena {
+            handle,
+            bufs: Vec::new(),
+            buf_type,
+        }
+    }
+
+    /// Returns the number of buffers
+    pub fn len(&self) -> usize {
+        self.bufs.len()
+    }
+
+    /// Returns whether there are no buffers
+    pub fn is_empty(&self) -> bool {
+        self.bufs.is_empty()
+    }
+
+    /// Returns a buffer or `None` if the index is out of bounds
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer
+    pub fn get(&self, index: usize) -> Option<&Buffer> {
+        self.bufs.get(index)
+    }
+
+    /// Returns all buffers
+    pub fn buffers(&self) -> &[Buffer] {
+        &self.bufs
+    }
+
+    fn request(&self, memory: Memory, count: u32) -> io::Result<u32> {
+        let mut v4l2_reqbufs = v4l2_requestbuffers {
+            count,
+            type_: self.buf_type as u32,
+            memory: memory as u32,
+            ..unsafe { mem::zeroed() }
+        };
+        unsafe {
+            v4l2::ioctl(
+                self.handle.fd(),
+                v4l2::vidioc::VIDIOC_REQBUFS,
+                &mut v4l2_reqbufs as *mut _ as *mut std::os::raw::c_void,
+            )?;
+        }
+        memory::verify_request(
+            memory,
+            v4l2_reqbufs.memory,
+            v4l2_reqbufs.capabilities.into(),
+        )?;
+
+        Ok(v4l2_reqbufs.count)
+    }
+
+    fn export(&self, index: u32) -> io::Result<Buffer> {
+        let mut v4l2_buf = v4l2_buffer {
+            index,
+            type_: self.buf_type as u32,
+            memory: Memory::Mmap as u32,
+            ..unsafe { mem::zeroed() }
+        };
+        let mut v4l2_expbuf = v4l2_exportbuffer {
+            index,
+            type_: self.buf_type as u32,
+            flags: (libc::O_CLOEXEC | libc::O_RDWR) as u32,
+            ..unsafe { mem::zeroed() }
+        };
+        unsafe {
+            v4l2::ioctl(
+                self.handle.fd(),
+                v4l2::vidioc::VIDIOC_QUERYBUF,
+                &mut v4l2_buf as *mut _ as *mut std::os::raw::c_void,
+            )?;
+            v4l2::ioctl(
+                self.handle.fd(),
+                v4l2::vidioc::VIDIOC_EXPBUF,
+                &mut v4l2_expbuf as *mut _ as *mut std::os::raw::c_void,
+            )?;
+        }
+
+        Ok(Buffer {
+            index: index as usize,
+            // VIDIOC_EXPBUF hands over a new file descriptor which nobody else owns
+            fd: unsafe { OwnedFd::from_raw_fd(v4l2_expbuf.fd) },
+            length: v4l2_buf.length as usize,
+        })
+    }
+
+    pub fn allocate(&mut self, count: u32) -> io::Result<u32> {
+        memory::verify_count(count)?;
+        // Let the driver allocate the memory and export it.
+        let requested = count;
+        let count = self.request(Memory::Mmap, count)?;
+        memory::verify_granted(requested, count)?;
+        for index in 0..count {
+            match self.export(index) {
+                Ok(buf) => self.bufs.push(buf),
+                Err(e) => {
+                    self.close();
+                    let _ = self.request(Memory::Mmap, 0);
+                    return Err(e);
+                }
+            }
+        }
+
+        // The exported buffers stay alive as long as their file descriptors are open, so the
+        // queue can be switched over to importing them.
+        let res = self
+            .request(Memory::Mmap, 0)
+            .and_then(|_| self.request(Memory::DmaBuf, count));
+        let imported = match res {
+            Ok(imported) => imported,
+            Err(e) => {
+                self.close();
+                return Err(e);
+            }
+        };
+        if let Err(e) = memory::verify_granted(count, imported) {
+            self.close();
+            return Err(e);
+        }
+
+        // The driver may hand out fewer slots than there are buffers.
+        self.bufs.truncate(imported as usize);
+
+        Ok(self.bufs.len() as u32)
+    }
+
+    pub fn release(&mut self) -> io::Result<()> {
+        // free all buffers by requesting 0
+        let res = self.request(Memory::DmaBuf, 0);
+        self.close();
+        res.map(|_| ())
+    }
+
+    fn close(&mut self) {
+        // dropping the buffers closes their file descriptors
+        self.bufs.clear();
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        if self.is_empty() {
+            // nothing to do
+            return;
+        }
+
+        if let Err(e) = self.release() {
+            if let Some(code) = e.raw_os_error() {
+                // ENODEV means the file descriptor wrapped in the handle became invalid, most
+                // likely because the device was unplugged or the connection (USB, PCI, ..)
+                // broke down. Handle this case gracefully by ignoring it.
+                if code == 19 {
+                    /* ignore */
+                    return;
+                }
+            }
+
+            panic!("{:?}", e)
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod arena;
+pub use arena::Buffer;
+
+pub mod stream;
+pub use stream::Stream;
@@ -0,0 +1,352 @@
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::time::Duration;
+use std::{fmt, io, mem, sync::Arc};
+
+use crate::buffer::{Flags, Metadata, Type};
+use crate::device::{Device, Handle};
+use crate::io::dmabuf::arena::{Arena, Buffer};
+use crate::io::traits::{CaptureStream, Stream as StreamTrait};
+use crate::io::{poll_timeout, stream_on, Queue, QueueState};
+use crate::memory::Memory;
+use crate::v4l2;
+use crate::v4l_sys::*;
+
+/// Stream of dma-buf buffers
+///
+/// Instead of the frame bytes, this stream hands out the dma-buf file descriptors of the buffers
+/// the frames were captured into. These can be imported into other APIs (e.g. EGL, Vulkan or
+/// a hardware encoder) without copying. The file descriptors stay owned by the stream and are
+/// closed when it is dropped, so duplicate them if they need to outlive the stream.
+///
+/// An arena instance is used internally for buffer handling.
+pub struct Stream {
+    handle: Arc<Handle>,
+    arena: Arena,
+    buf_type: Type,
+    buf_meta: Vec<Metadata>,
+    timeout: Option<i32>,
+
+    state: QueueState,
+}
+
+impl Stream {
+    /// Returns a stream for frame capturing
+    ///
+    /// # Arguments
+    ///
+    /// * `dev` - Capture device ref to get its file descriptor
+    /// * `buf_type` - Type of the buffers
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::dmabuf::Stream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     let stream = Stream::new(&dev, Type::VideoCapture);
+    /// }
+    /// ```
+    pub fn new(dev: &Device, buf_type: Type) -> io::Result<Self> {
+        Stream::with_buffers(dev, buf_type, 4)
+    }
+
+    /// Returns a stream for frame capturing with a custom number of buffers
+    ///
+    /// The driver must support both exporting (`VIDIOC_EXPBUF`) and importing
+    /// (`V4L2_MEMORY_DMABUF`) buffers.
+    ///
+    /// # Arguments
+    ///
+    /// * `dev` - Device ref to get its file descriptor
+    /// * `buf_type` - Type of the buffers
+    /// * `buf_count` - Number of buffers to request, at most 32 (`VIDEO_MAX_FRAME`)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::os::unix::io::AsRawFd;
+    ///
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::dmabuf::Stream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(stream) = Stream::with_buffers(&dev, Type::VideoCapture, 4) {
+    ///         for buf in stream.buffers() {
+    ///             println!("buffer {}: fd {}, {} bytes", buf.index, buf.fd().as_raw_fd(), buf.length);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn with_buffers(dev: &Device, buf_type: Type, buf_count: u32) -> io::Result<Self> {
+        let mut arena = Arena::new(dev.handle(), buf_type);
+        let count = arena.allocate(buf_count)?;
+        let mut buf_meta = Vec::new();
+        buf_meta.resize(count as usize, Metadata::default());
+
+        Ok(Stream {
+            handle: dev.handle(),
+            arena,
+            buf_type,
+            buf_meta,
+            timeout: None,
+            state: QueueState::default(),
+        })
+    }
+
+    /// Returns the raw device handle
+    pub fn handle(&self) -> Arc<Handle> {
+        self.handle.clone()
+    }
+
+    /// Returns the number of buffers granted by the driver
+    ///
+    /// This may differ from the count passed to [`Stream::with_buffers`], since drivers are free
+    /// to allocate fewer or more buffers.
+    pub fn allocated_buffers(&self) -> usize {
+        self.arena.len()
+    }
+
+    /// Returns the file descriptor of the device
+    ///
+    /// See [`crate::io::mmap::Stream::fd`] for how to use it with an external event loop.
+    pub fn fd(&self) -> RawFd {
+        self.handle.fd()
+    }
+
+    /// Returns all buffers of the stream
+    ///
+    /// Use this to import all dma-bufs up front, e.g. when creating EGL images.
+    pub fn buffers(&self) -> &[Buffer] {
+        self.arena.buffers()
+    }
+
+    /// Sets a timeout of the v4l file handle.
+    ///
+    /// If no buffer becomes ready within the given duration, dequeueing fails with an error of
+    /// kind [`io::ErrorKind::TimedOut`]. The duration is rounded up to the next full millisecond
+    /// and clamped to `i32::MAX` milliseconds.
+    pub fn set_timeout(&mut self, duration: Duration) {
+        self.timeout = Some(poll_timeout(duration));
+    }
+
+    /// Clears the timeout of the v4l file handle.
+    pub fn clear_timeout(&mut self) {
+        self.timeout = None;
+    }
+
+    fn buffer_desc(&self) -> v4l2_buffer {
+        v4l2_buffer {
+            type_: self.buf_type as u32,
+            memory: Memory::DmaBuf as u32,
+            ..unsafe { mem::zeroed() }
+        }
+    }
+}
+
+impl fmt::Debug for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stream")
+            .field("buf_type", &self.buf_type)
+            .field("memory", &format_args!("{}", Memory::DmaBuf))
+            .field("buf_count", &self.arena.len())
+            .field("active", &self.state.active)
+            .finish()
+    }
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        if let Err(e) = self.stop() {
+            if let Some(code) = e.raw_os_error() {
+                // ENODEV means the file descriptor wrapped in the handle became invalid, most
+                // likely because the device was unplugged or the connection (USB, PCI, ..)
+                // broke down. Handle this case gracefully by ignoring it.
+                if code == 19 {
+                    /* ignore */
+                    return;
+                }
+            }
+
+            panic!("{:?}", e)
+        }
+    }
+}
+
+impl AsRawFd for Stream {
+    /// Returns the file descriptor of the device, see [`Stream::fd`]
+    fn as_raw_fd(&self) -> RawFd {
+        self.handle.fd()
+    }
+}
+
+impl StreamTrait for Stream {
+    type Item = Buffer;
+
+    fn start(&mut self) -> io::Result<()> {
+        stream_on(&self.handle, self.buf_type, self.state.start_retries)?;
+
+        self.state.active = true;
+        Ok(())
+    }
+
+    fn stop(&mut self) -> io::Result<()> {
+        unsafe {
+            let mut typ = self.buf_type as u32;
+            v4l2::ioctl(
+                self.handle.fd(),
+                v4l2::vidioc::VIDIOC_STREAMOFF,
+                &mut typ as *mut _ as *mut std::os::raw::c_void,
+            )?;
+        }
+
+        self.state.active = false;
+        self.state.held = false;
+        Ok(())
+    }
+}
+
+impl Queue for Stream {
+    fn state(&self) -> &QueueState {
+        &self.state
+    }
+
+    fn state_mut(&mut self) -> &mut QueueState {
+        &mut self.state
+    }
+
+    fn handle_ref(&self) -> &Handle {
+        &self.handle
+    }
+
+    fn buffer_count(&self) -> usize {
+        self.arena.len()
+    }
+
+    fn meta(&self, index: usize) -> &Metadata {
+        &self.buf_meta[index]
+    }
+
+    fn query_flags(&self, index: usize) -> io::Result<Flags> {
+        let mut v4l2_buf = v4l2_buffer {
+            index: index as u32,
+            ..self.buffer_desc()
+        };
+        unsafe {
+            v4l2::ioctl(
+                self.handle.fd(),
+                v4l2::vidioc::VIDIOC_QUERYBUF,
+                &mut v4l2_buf as *mut _ as *mut std::os::raw::c_void,
+            )?;
+        }
+
+        Ok(Flags::from(v4l2_buf.flags))
+    }
+
+    fn queue_buffer(&mut self, index: usize) -> io::Result<()> {
+        let buf = self.arena.get(index).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid buffer index {}", index),
+            )
+        })?;
+        let mut v4l2_buf = v4l2_buffer {
+            index: index as u32,
+            m: v4l2_buffer__bindgen_ty_1 {
+                fd: buf.as_raw_fd(),
+            },
+            length: buf.length as u32,
+            ..self.buffer_desc()
+        };
+        unsafe {
+            v4l2::ioctl(
+                self.handle.fd(),
+                v4l2::vidioc::VIDIOC_QBUF,
+                &mut v4l2_buf as *mut _ as *mut std::os::raw::c_void,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn dequeue_ready(&mut self) -> io::Result<usize> {
+        let mut v4l2_buf = self.buffer_desc();
+        unsafe {
+            v4l2::ioctl(
+                self.handle.fd(),
+                v4l2::vidioc::VIDIOC_DQBUF,
+                &mut v4l2_buf as *mut _ as *mut std::os::raw::c_void,
+            )?;
+        }
+        let index = v4l2_buf.index as usize;
+
+        self.buf_meta[index] = Metadata {
+            bytesused: v4l2_buf.bytesused,
+            flags: v4l2_buf.flags.into(),
+            field: v4l2_buf.field,
+            timestamp: v4l2_buf.timestamp.into(),
+            sequence: v4l2_buf.sequence,
+        };
+
+        Ok(index)
+    }
+}
+
+impl<'a> CaptureStream<'a> for Stream {
+    fn queue(&mut self, index: usize) -> io::Result<()> {
+        self.queue_buffer(index)
+    }
+
+    fn dequeue(&mut self) -> io::Result<usize> {
+        self.dequeue_within(self.timeout.unwrap_or(-1))
+    }
+
+    fn try_next(&'a mut self) -> io::Result<Option<(&Self::Item, &Metadata)>> {
+        if !self.try_fetch()? {
+            // Nothing ready yet, the next call continues from here
+            return Ok(None);
+        }
+
+        // The index used to access the buffer elements is given to us by v4l2, so we assume it
+        // will always be valid.
+        let buf = self.arena.get(self.state.index).unwrap();
+        let meta = &self.buf_meta[self.state.index];
+        Ok(Some((buf, meta)))
+    }
+
+    fn next_latest(&'a mut self) -> io::Result<(&Self::Item, &Metadata)> {
+        self.fetch_latest(self.timeout.unwrap_or(-1))?;
+
+        // The index used to access the buffer elements is given to us by v4l2, so we assume it
+        // will always be valid.
+        let buf = self.arena.get(self.state.index).unwrap();
+        let meta = &self.buf_meta[self.state.index];
+        Ok((buf, meta))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.state.active {
+            return Ok(());
+        }
+
+        self.requeue()
+    }
+
+    fn restart(&mut self) -> io::Result<()> {
+        self.requeue()
+    }
+
+    fn next(&'a mut self) -> io::Result<(&Self::Item, &Metadata)> {
+        self.fetch(self.timeout.unwrap_or(-1))?;
+
+        // The index used to access the buffer elements is given to us by v4l2, so we assume it
+        // will always be valid.
+        let buf = self.arena.get(self.state.index).unwrap();
+        let meta = &self.buf_meta[self.state.index];
+        Ok((buf, meta))
+    }
+}
@@ -0,0 +1,109 @@
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+
+use crate::buffer::{Metadata, Type};
+use crate::device::Device;
+use crate::io::mmap::Stream as MmapStream;
+use crate::io::traits::{CaptureStream, OutputStream, Stream as StreamTrait};
+
+/// Capture and output stream on a single device
+///
+/// Mem2mem devices (e.g. codecs or scalers) and loopback devices expose a capture and an output
+/// queue on the same file descriptor. Both queues need to be driven in a coordinated fashion:
+/// streaming must be turned on for the output queue before the capture queue, and turned off
+/// in the opposite order. This stream takes care of that, while the buffers are handled by two
+/// memory mapped streams sharing the handle of the device.
+pub struct DuplexStream<'a> {
+    // Fields are dropped in declaration order, so the capture queue is stopped first.
+    capture: MmapStream<'a>,
+    output: MmapStream<'a>,
+}
+
+impl<'a> DuplexStream<'a> {
+    /// Returns a stream for the video capture and video output queues of a device
+    ///
+    /// # Arguments
+    ///
+    /// * `dev` - Device ref to get its file descriptor
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    /// use v4l::io::duplex::DuplexStream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     let stream = DuplexStream::new(&dev);
+    /// }
+    /// ```
+    pub fn new(dev: &Device) -> io::Result<Self> {
+        DuplexStream::with_buffers(dev, Type::VideoCapture, Type::VideoOutput, 4)
+    }
+
+    /// Returns a stream for the given queues with a custom number of buffers
+    ///
+    /// # Arguments
+    ///
+    /// * `dev` - Device ref to get its file descriptor
+    /// * `capture_type` - Type of the capture buffers
+    /// * `output_type` - Type of the output buffers
+    /// * `buf_count` - Number of buffers to request for each queue
+    pub fn with_buffers(
+        dev: &Device,
+        capture_type: Type,
+        output_type: Type,
+        buf_count: u32,
+    ) -> io::Result<Self> {
+        Ok(DuplexStream {
+            capture: MmapStream::with_buffers(dev, capture_type, buf_count)?,
+            output: MmapStream::with_buffers(dev, output_type, buf_count)?,
+        })
+    }
+
+    /// Returns the file descriptor of the device
+    ///
+    /// Both queues share it: `POLLIN` signals a buffer produced by the device, `POLLOUT` an
+    /// output buffer the device is done with. See [`crate::io::mmap::Stream::fd`] for how to use
+    /// it with an external event loop.
+    pub fn fd(&self) -> RawFd {
+        self.capture.fd()
+    }
+
+    /// Returns an output buffer to be filled and passed to the device
+    ///
+    /// Streaming is turned on for the output queue on the first call. The buffer is handed to
+    /// the device on the next call.
+    pub fn next_output(&mut self) -> io::Result<(&mut [u8], &mut Metadata)> {
+        OutputStream::next(&mut self.output)
+    }
+
+    /// Returns a buffer produced by the device
+    ///
+    /// Streaming is turned on for the capture queue on the first call. Since the output queue
+    /// has to be streaming first, an error of kind [`io::ErrorKind::InvalidInput`] is returned if
+    /// [`DuplexStream::next_output`] was not called before.
+    pub fn next_capture(&mut self) -> io::Result<(&[u8], &Metadata)> {
+        if !self.output.is_active() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "output queue must be streaming before the capture queue (call next_output first)",
+            ));
+        }
+
+        CaptureStream::next(&mut self.capture)
+    }
+
+    /// Turns off streaming for both queues, capture first
+    pub fn stop(&mut self) -> io::Result<()> {
+        self.capture.stop()?;
+        self.output.stop()
+    }
+}
+
+impl<'a> AsRawFd for DuplexStream<'a> {
+    /// Returns the file descriptor of the device, see [`DuplexStream::fd`]
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd()
+    }
+}
@@ -1,19 +1,33 @@
+use std::convert::TryFrom;
 use std::{io, mem, ptr, slice, sync::Arc};
 
 use crate::buffer;
 use crate::device::Handle;
-use crate::memory::Memory;
+use crate::memory::{self, Flags as MemoryFlags, Memory};
 use crate::v4l2;
 use crate::v4l_sys::*;
 
+/// Memory mappings of a single buffer
+struct Buffer<'a> {
+    /// Mapped regions, one per plane unless the planes are contiguous
+    maps: Vec<&'a mut [u8]>,
+    /// Planes given as (map index, offset, length)
+    planes: Vec<(usize, usize, usize)>,
+}
+
 /// Manage mapped buffers
 ///
+/// Multi-planar buffers are usually mapped plane by plane. Some drivers lay out all planes in a
+/// single contiguous allocation though, reporting the same memory offset for each of them. Such
+/// buffers are mapped only once and the planes are located by their sizes.
+///
 /// All buffers are unmapped in the Drop impl.
 /// In case of errors during unmapping, we panic because there is memory corruption going on.
 pub struct Arena<'a> {
     handle: Arc<Handle>,
-    pub bufs: Vec<&'a mut [u8]>,
+    bufs: Vec<Buffer<'a>>,
     pub buf_type: buffer::Type,
+    flags: MemoryFlags,
 }
 
 impl<'a> Arena<'a> {
@@ -31,6 +45,117 @@ impl<'a> Arena<'a> {
             handle,
             bufs: Vec::new(),
             buf_type,
+            flags: MemoryFlags::empty(),
+        }
+    }
+
+    /// Sets the memory flags used when buffers are requested
+    ///
+    /// Must be called before [`Arena::allocate`] to have an effect.
+    ///
+    /// # Arguments
+    ///
+    /// * `flags` - Memory flags, e.g. [`MemoryFlags::NON_COHERENT`]
+    pub fn set_flags(&mut self, flags: MemoryFlags) {
+        self.flags = flags;
+    }
+
+    /// Returns the number of buffers
+    pub fn len(&self) -> usize {
+        self.bufs.len()
+    }
+
+    /// Returns whether there are no buffers
+    pub fn is_empty(&self) -> bool {
+        self.bufs.is_empty()
+    }
+
+    /// Returns a buffer or `None` if the index is out of bounds
+    ///
+    /// For multi-planar buffers, this is the first mapping: all planes if they are contiguous,
+    /// the first plane otherwise. Use [`Arena::planes`] to access the individual planes.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        self.bufs.get(index).map(|buf| &*buf.maps[0])
+    }
+
+    /// Returns a buffer or `None` if the index is out of bounds
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut [u8]> {
+        self.bufs.get_mut(index).map(|buf| &mut *buf.maps[0])
+    }
+
+    /// Returns a buffer without bounds checking
+    ///
+    /// # Safety
+    ///
+    /// The index must be less than [`Arena::len`].
+    pub unsafe fn get_unchecked(&self, index: usize) -> &[u8] {
+        self.bufs.get_unchecked(index).maps.get_unchecked(0)
+    }
+
+    /// Returns a buffer without bounds checking
+    ///
+    /// # Safety
+    ///
+    /// The index must be less than [`Arena::len`].
+    pub unsafe fn get_unchecked_mut(&mut self, index: usize) -> &mut [u8] {
+        self.bufs.get_unchecked_mut(index).maps.get_unchecked_mut(0)
+    }
+
+    /// Returns the planes of a buffer or `None` if the index is out of bounds
+    ///
+    /// Single-planar buffers consist of exactly one plane.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer
+    pub fn planes(&self, index: usize) -> Option<Vec<&[u8]>> {
+        self.bufs.get(index).map(|buf| {
+            buf.planes
+                .iter()
+                .map(|&(map, offset, len)| &buf.maps[map][offset..offset + len])
+                .collect()
+        })
+    }
+
+    /// Returns the number of planes per buffer
+    pub fn num_planes(&self) -> usize {
+        self.bufs.first().map_or(1, |buf| buf.planes.len())
+    }
+
+    /// Returns the planes of a buffer as (map index, offset, length)
+    pub(crate) fn plane_layout(&self, index: usize) -> Option<&[(usize, usize, usize)]> {
+        self.bufs.get(index).map(|buf| buf.planes.as_slice())
+    }
+
+    fn map(&self, index: u32, length: u32, offset: u32) -> io::Result<&'a mut [u8]> {
+        unsafe {
+            let ptr = v4l2::mmap(
+                ptr::null_mut(),
+                length as usize,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                self.handle.fd(),
+                offset as libc::off_t,
+            )
+            .map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("failed to map buffer {} ({} bytes): {}", index, length, e),
+                )
+            })?;
+
+            Ok(slice::from_raw_parts_mut::<u8>(
+                ptr as *mut u8,
+                length as usize,
+            ))
         }
     }
 
@@ -51,8 +176,17 @@ impl<'a> Arena<'a> {
     }
 
     pub fn allocate(&mut self, count: u32) -> io::Result<u32> {
+        memory::verify_count(count)?;
+        // The C struct only has room for a single byte of flags
+        let flags = u8::try_from(u32::from(self.flags)).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unsupported memory flags {}", self.flags),
+            )
+        })?;
         let mut v4l2_reqbufs = v4l2_requestbuffers {
             count,
+            flags,
             ..self.requestbuffers_desc()
         };
         unsafe {
@@ -60,34 +194,71 @@ impl<'a> Arena<'a> {
                 self.handle.fd(),
                 v4l2::vidioc::VIDIOC_REQBUFS,
                 &mut v4l2_reqbufs as *mut _ as *mut std::os::raw::c_void,
-            )?;
+            )
+            // Some drivers reject buffer allocation until a format has been negotiated.
+            .map_err(memory::map_request_error)?;
         }
+        memory::verify_request(
+            Memory::Mmap,
+            v4l2_reqbufs.memory,
+            v4l2_reqbufs.capabilities.into(),
+        )?;
+        memory::verify_granted(count, v4l2_reqbufs.count)?;
 
         for index in 0..v4l2_reqbufs.count {
+            let mut v4l2_planes: [v4l2_plane; VIDEO_MAX_PLANES as usize] = unsafe { mem::zeroed() };
             let mut v4l2_buf = v4l2_buffer {
                 index,
                 ..self.buffer_desc()
             };
+            if self.buf_type.is_mplane() {
+                v4l2_buf.length = VIDEO_MAX_PLANES;
+                v4l2_buf.m.planes = v4l2_planes.as_mut_ptr();
+            }
             unsafe {
                 v4l2::ioctl(
                     self.handle.fd(),
                     v4l2::vidioc::VIDIOC_QUERYBUF,
                     &mut v4l2_buf as *mut _ as *mut std::os::raw::c_void,
                 )?;
+            }
 
-                let ptr = v4l2::mmap(
-                    ptr::null_mut(),
-                    v4l2_buf.length as usize,
-                    libc::PROT_READ | libc::PROT_WRITE,
-                    libc::MAP_SHARED,
-                    self.handle.fd(),
-                    v4l2_buf.m.offset as libc::off_t,
-                )?;
+            let buf = if !self.buf_type.is_mplane() {
+                let map = self.map(index, v4l2_buf.length, unsafe { v4l2_buf.m.offset })?;
+                let len = map.len();
+                Buffer {
+                    maps: vec![map],
+                    planes: vec![(0, 0, len)],
+                }
+            } else {
+                let planes = &v4l2_planes[..v4l2_buf.length as usize];
+                let offset = |plane: &v4l2_plane| unsafe { plane.m.mem_offset };
+                let offsets: Vec<u32> = planes.iter().map(offset).collect();
+                let lengths: Vec<u32> = planes.iter().map(|p| p.length).collect();
+                let contiguous = is_contiguous(&offsets);
 
-                let slice =
-                    slice::from_raw_parts_mut::<u8>(ptr as *mut u8, v4l2_buf.length as usize);
-                self.bufs.push(slice);
-            }
+                let mut buf = Buffer {
+                    maps: Vec::new(),
+                    planes: layout(&lengths, contiguous),
+                };
+                if contiguous {
+                    // One allocation holding all planes back to back
+                    buf.maps
+                        .push(self.map(index, lengths.iter().sum(), offsets[0])?);
+                } else {
+                    for (&length, &offset) in lengths.iter().zip(&offsets) {
+                        match self.map(index, length, offset) {
+                            Ok(map) => buf.maps.push(map),
+                            Err(e) => {
+                                Self::unmap(&buf)?;
+                                return Err(e);
+                            }
+                        }
+                    }
+                }
+                buf
+            };
+            self.bufs.push(buf);
         }
 
         Ok(v4l2_reqbufs.count)
@@ -95,9 +266,7 @@ impl<'a> Arena<'a> {
 
     pub fn release(&mut self) -> io::Result<()> {
         for buf in &self.bufs {
-            unsafe {
-                v4l2::munmap(buf.as_ptr() as *mut core::ffi::c_void, buf.len())?;
-            }
+            Self::unmap(buf)?;
         }
 
         // free all buffers by requesting 0
@@ -116,11 +285,58 @@ impl<'a> Arena<'a> {
         self.bufs.clear();
         Ok(())
     }
+
+    fn unmap(buf: &Buffer) -> io::Result<()> {
+        for map in &buf.maps {
+            unsafe {
+                v4l2::munmap(map.as_ptr() as *mut core::ffi::c_void, map.len())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns whether the planes of a buffer share a single allocation
+///
+/// Drivers report the same memory offset for all planes in that case.
+///
+/// # Arguments
+///
+/// * `offsets` - Memory offset of each plane
+fn is_contiguous(offsets: &[u32]) -> bool {
+    offsets.len() > 1 && offsets.iter().all(|&offset| offset == offsets[0])
+}
+
+/// Returns the location of each plane as (map index, offset, length)
+///
+/// Contiguous planes are laid out back to back in a single mapping, all others get a mapping
+/// of their own.
+///
+/// # Arguments
+///
+/// * `lengths` - Length of each plane
+/// * `contiguous` - Whether the planes share a single allocation
+fn layout(lengths: &[u32], contiguous: bool) -> Vec<(usize, usize, usize)> {
+    let mut start = 0;
+    lengths
+        .iter()
+        .enumerate()
+        .map(|(i, &length)| {
+            let length = length as usize;
+            if contiguous {
+                start += length;
+                (0, start - length, length)
+            } else {
+                (i, 0, length)
+            }
+        })
+        .collect()
 }
 
 impl<'a> Drop for Arena<'a> {
     fn drop(&mut self) {
-        if self.bufs.is_empty() {
+        if self.is_empty() {
             // nothing to do
             return;
         }
@@ -140,3 +356,35 @@ impl<'a> Drop for Arena<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contiguous_planes() {
+        // NV12 on a contiguous-mplane driver: both planes at the same offset
+        assert!(is_contiguous(&[0x1000, 0x1000]));
+        assert_eq!(
+            layout(&[640 * 480, 640 * 240], true),
+            vec![(0, 0, 640 * 480), (0, 640 * 480, 640 * 240)]
+        );
+    }
+
+    #[test]
+    fn separate_planes() {
+        // NV12M: each plane has its own offset and mapping
+        assert!(!is_contiguous(&[0x0, 0x4b000]));
+        assert_eq!(
+            layout(&[640 * 480, 640 * 240], false),
+            vec![(0, 0, 640 * 480), (1, 0, 640 * 240)]
+        );
+    }
+
+    #[test]
+    fn single_plane() {
+        // A single plane is mapped on its own, regardless of its offset
+        assert!(!is_contiguous(&[0x1000]));
+        assert_eq!(layout(&[1024], false), vec![(0, 0, 1024)]);
+    }
+}
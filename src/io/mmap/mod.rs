@@ -1,4 +1,4 @@
-pub(crate) mod arena;
+pub mod arena;
 
 pub mod stream;
 pub use stream::Stream;
@@ -1,27 +1,42 @@
-use std::convert::TryInto;
-use std::time::Duration;
-use std::{io, mem, sync::Arc};
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use std::{fmt, io, mem, sync::Arc};
 
-use crate::buffer::{Metadata, Type};
+use crate::buffer::{Flags, Metadata, Type};
 use crate::device::{Device, Handle};
 use crate::io::mmap::arena::Arena;
 use crate::io::traits::{CaptureStream, OutputStream, Stream as StreamTrait};
-use crate::memory::Memory;
+use crate::io::{
+    output_interval, poll_timeout, save_frames_to_dir, stream_on, Queue, QueueState, RequeuePolicy,
+    StreamStats,
+};
+use crate::memory::{Flags as MemoryFlags, Memory};
 use crate::v4l2;
 use crate::v4l_sys::*;
 
+type Planes = [v4l2_plane; VIDEO_MAX_PLANES as usize];
+
 /// Stream of mapped buffers
 ///
 /// An arena instance is used internally for buffer handling.
 pub struct Stream<'a> {
     handle: Arc<Handle>,
     arena: Arena<'a>,
-    arena_index: usize,
     buf_type: Type,
     buf_meta: Vec<Metadata>,
+    /// Bytes used in each plane of each buffer, updated on dequeue
+    plane_bytesused: Vec<Vec<u32>>,
+    /// Offset of the data in each plane of each buffer, updated on dequeue
+    plane_data_offset: Vec<Vec<u32>>,
     timeout: Option<i32>,
+    stats: StreamStats,
+    last_sequence: Option<u32>,
+    request_fd: Option<RawFd>,
+    frame_interval: Option<Duration>,
+    underruns: u64,
 
-    active: bool,
+    state: QueueState,
 }
 
 impl<'a> Stream<'a> {
@@ -48,8 +63,82 @@ impl<'a> Stream<'a> {
         Stream::with_buffers(dev, buf_type, 4)
     }
 
+    /// Returns a stream for frame capturing with a custom number of buffers
+    ///
+    /// Some drivers refuse to allocate buffers before a format has been negotiated. In that case,
+    /// an error of kind [`io::ErrorKind::InvalidInput`] is returned and you should call
+    /// `set_format` on the device before trying again.
+    ///
+    /// # Arguments
+    ///
+    /// * `dev` - Device ref to get its file descriptor
+    /// * `buf_type` - Type of the buffers
+    /// * `buf_count` - Number of buffers to request, at most 32 (`VIDEO_MAX_FRAME`)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io;
+    ///
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    /// use v4l::video::Capture;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     let stream = match Stream::with_buffers(&dev, Type::VideoCapture, 4) {
+    ///         Err(e) if e.kind() == io::ErrorKind::InvalidInput => {
+    ///             // negotiate a format first, then retry
+    ///             let fmt = dev.format().unwrap();
+    ///             dev.set_format(&fmt).unwrap();
+    ///             Stream::with_buffers(&dev, Type::VideoCapture, 4)
+    ///         }
+    ///         res => res,
+    ///     };
+    ///
+    ///     // the kernel never allocates more than 32 buffers per queue
+    ///     let err = Stream::with_buffers(&dev, Type::VideoCapture, 33).unwrap_err();
+    ///     assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    /// }
+    /// ```
     pub fn with_buffers(dev: &Device, buf_type: Type, buf_count: u32) -> io::Result<Self> {
+        Stream::with_flags(dev, buf_type, buf_count, MemoryFlags::empty())
+    }
+
+    /// Returns a stream for frame capturing with custom memory flags
+    ///
+    /// The flags are passed to the driver when the buffers are requested. Drivers which do not
+    /// support them (older kernels, queues without cache hint support) silently ignore them.
+    ///
+    /// # Arguments
+    ///
+    /// * `dev` - Device ref to get its file descriptor
+    /// * `buf_type` - Type of the buffers
+    /// * `buf_count` - Number of buffers to request, at most 32 (`VIDEO_MAX_FRAME`)
+    /// * `flags` - Memory flags, e.g. [`MemoryFlags::NON_COHERENT`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    /// use v4l::memory::Flags;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     let stream = Stream::with_flags(&dev, Type::VideoCapture, 4, Flags::NON_COHERENT);
+    /// }
+    /// ```
+    pub fn with_flags(
+        dev: &Device,
+        buf_type: Type,
+        buf_count: u32,
+        flags: MemoryFlags,
+    ) -> io::Result<Self> {
         let mut arena = Arena::new(dev.handle(), buf_type);
+        arena.set_flags(flags);
         let count = arena.allocate(buf_count)?;
         let mut buf_meta = Vec::new();
         buf_meta.resize(count as usize, Metadata::default());
@@ -57,11 +146,17 @@ impl<'a> Stream<'a> {
         Ok(Stream {
             handle: dev.handle(),
             arena,
-            arena_index: 0,
             buf_type,
             buf_meta,
-            active: false,
+            plane_bytesused: vec![Vec::new(); count as usize],
+            plane_data_offset: vec![Vec::new(); count as usize],
             timeout: None,
+            stats: StreamStats::default(),
+            last_sequence: None,
+            request_fd: None,
+            frame_interval: None,
+            underruns: 0,
+            state: QueueState::default(),
         })
     }
 
@@ -70,9 +165,487 @@ impl<'a> Stream<'a> {
         self.handle.clone()
     }
 
+    /// Returns the file descriptor of the device
+    ///
+    /// This allows embedding a stream into an external event loop (epoll, mio, ..). The file
+    /// descriptor is non-blocking. It becomes readable (`POLLIN`) once a capture buffer can be
+    /// dequeued, writable (`POLLOUT`) once an output buffer was released by the driver and
+    /// signals events (`POLLPRI`) such as source changes, if subscribed to. Streaming has to be
+    /// started before waiting for readiness, i.e. call `next` (which starts the stream on its
+    /// first invocation) or [`crate::io::traits::Stream::start`] after queueing the buffers.
+    /// Once the file descriptor is ready, [`CaptureStream::try_next`] returns a frame without
+    /// blocking. Do not read from or close the file descriptor, the stream owns it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         // register with your reactor of choice
+    ///         let fd = stream.fd();
+    ///     }
+    /// }
+    /// ```
+    pub fn fd(&self) -> RawFd {
+        self.handle.fd()
+    }
+
+    /// Returns a buffer or `None` if the index is out of bounds
+    ///
+    /// Together with [`CaptureStream::dequeue`], this allows processing a frame after dequeueing
+    /// it, e.g. to hold on to several frames at once. The contents are only stable while the
+    /// buffer is not queued.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer, as returned by [`CaptureStream::dequeue`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    /// use v4l::io::traits::{CaptureStream, Stream as _};
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         CaptureStream::queue(&mut stream, 0).unwrap();
+    ///         stream.start().unwrap();
+    ///         let index = CaptureStream::dequeue(&mut stream).unwrap();
+    ///         let buf = stream.get(index).unwrap();
+    ///         let meta = stream.get_meta(index).unwrap();
+    ///         println!("frame {}: {} bytes", meta.sequence, buf.len());
+    ///     }
+    /// }
+    /// ```
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        self.arena.get(index)
+    }
+
+    /// Returns the counters of dequeued, errored and skipped frames
+    ///
+    /// Frames which the driver dropped because no buffer was queued show up as sequence gaps.
+    /// Buffers flagged as errored are still returned by `next`, check
+    /// [`crate::buffer::Flags::ERROR`] in their metadata to skip them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    /// use v4l::io::traits::CaptureStream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         assert_eq!(stream.stats().dequeued, 0);
+    ///         for _ in 0..10 {
+    ///             stream.next().unwrap();
+    ///         }
+    ///         println!("{}", stream.stats());
+    ///     }
+    /// }
+    /// ```
+    pub fn stats(&self) -> StreamStats {
+        self.stats
+    }
+
+    /// Returns the metadata of a buffer or `None` if the index is out of bounds
+    ///
+    /// The metadata is updated whenever the buffer is dequeued.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer, as returned by [`CaptureStream::dequeue`]
+    pub fn get_meta(&self, index: usize) -> Option<&Metadata> {
+        self.buf_meta.get(index)
+    }
+
+    /// Returns a mutable buffer or `None` if the index is out of bounds
+    pub(crate) fn get_mut(&mut self, index: usize) -> Option<&mut [u8]> {
+        self.arena.get_mut(index)
+    }
+
+    /// Returns the mutable metadata of a buffer or `None` if the index is out of bounds
+    pub(crate) fn get_meta_mut(&mut self, index: usize) -> Option<&mut Metadata> {
+        self.buf_meta.get_mut(index)
+    }
+
+    /// Returns the number of buffers granted by the driver
+    ///
+    /// This may differ from the count passed to [`Stream::with_buffers`], since drivers are free
+    /// to allocate fewer or more buffers. Creating the stream fails if no buffer was granted at
+    /// all, check this to enforce a higher minimum.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io;
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(stream) = Stream::with_buffers(&dev, Type::VideoCapture, 4) {
+    ///         if stream.allocated_buffers() < 3 {
+    ///             println!("only {} buffers, expect dropped frames", stream.allocated_buffers());
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn allocated_buffers(&self) -> usize {
+        self.arena.len()
+    }
+
+    /// Returns the number of bytes used in each plane of a buffer or `None` if the index is out
+    /// of bounds
+    ///
+    /// [`Metadata::bytesused`] only covers the slice returned by [`Stream::get`], use this to
+    /// find the payload of each plane returned by [`Stream::planes`]. The counts include the
+    /// data offset of each plane, see [`Stream::get_planes`] for the payload itself. The slice
+    /// is empty until the buffer was dequeued once.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer, as returned by [`CaptureStream::dequeue`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    /// use v4l::io::traits::{CaptureStream, Stream as _};
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCaptureMplane) {
+    ///         CaptureStream::queue(&mut stream, 0).unwrap();
+    ///         stream.start().unwrap();
+    ///         let index = CaptureStream::dequeue(&mut stream).unwrap();
+    ///         let planes = stream.planes(index).unwrap();
+    ///         let used = stream.plane_bytesused(index).unwrap();
+    ///         for (plane, &used) in planes.iter().zip(used) {
+    ///             let payload = &plane[..used as usize];
+    ///             println!("plane: {} of {} bytes used", payload.len(), plane.len());
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn plane_bytesused(&self, index: usize) -> Option<&[u32]> {
+        self.plane_bytesused.get(index).map(|used| used.as_slice())
+    }
+
+    /// Stops the stream and returns all buffers to the application
+    ///
+    /// Buffers can be exchanged with the driver in two ways: [`CaptureStream::next`] (or
+    /// [`OutputStream::next`]) manages the queue on its own, while the `queue` and `dequeue`
+    /// functions of the same traits leave that to the caller. `next` remembers the buffer it
+    /// handed out last and queues it again on the following call, so it must not be mixed with
+    /// manual queueing without a reset in between. Supported patterns are:
+    ///
+    /// * `next` only: no reset needed.
+    /// * manual only: queue buffers, call [`crate::io::traits::Stream::start`], then dequeue and
+    ///   queue them as needed, accessing them through [`Stream::get`].
+    /// * switching between the two: call this function first. Afterwards the stream behaves
+    ///   as if it had just been created: `next` queues all buffers and starts streaming again
+    ///   (including the warmup), whereas manual use starts by queueing buffers.
+    ///
+    /// Buffers which were queued manually without starting the stream are released as well.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    /// use v4l::io::traits::{CaptureStream, Stream as _};
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         // manual mode
+    ///         CaptureStream::queue(&mut stream, 0).unwrap();
+    ///         stream.start().unwrap();
+    ///         let index = CaptureStream::dequeue(&mut stream).unwrap();
+    ///
+    ///         // switch to next()
+    ///         stream.reset().unwrap();
+    ///         let (buf, meta) = CaptureStream::next(&mut stream).unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub fn reset(&mut self) -> io::Result<()> {
+        // VIDIOC_STREAMOFF also releases buffers which were queued without streaming
+        self.stop()?;
+        self.state.index = 0;
+        Ok(())
+    }
+
+    /// Returns the planes of a buffer or `None` if the index is out of bounds
+    ///
+    /// Streams of multi-planar buffer types (e.g. [`Type::VideoCaptureMplane`]) hand out the
+    /// first mapping of a buffer: all planes if the driver allocated them contiguously, only the
+    /// first plane otherwise. This returns each plane on its own in either case. Note that some
+    /// formats such as NV12 (as opposed to NV12M) store all components in one plane.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer, as returned by [`CaptureStream::dequeue`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    /// use v4l::io::traits::{CaptureStream, Stream as _};
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCaptureMplane) {
+    ///         CaptureStream::queue(&mut stream, 0).unwrap();
+    ///         stream.start().unwrap();
+    ///         let index = CaptureStream::dequeue(&mut stream).unwrap();
+    ///         for (i, plane) in stream.planes(index).unwrap().iter().enumerate() {
+    ///             println!("plane {}: {} bytes", i, plane.len());
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn planes(&self, index: usize) -> Option<Vec<&[u8]>> {
+        self.arena.planes(index)
+    }
+
+    /// Returns the payload of each plane of a dequeued buffer or `None` if the index is out of
+    /// bounds
+    ///
+    /// Unlike [`Stream::planes`], each slice starts at the data offset reported by the driver
+    /// and ends after the bytes used. This is the way to access all planes of multi-planar
+    /// buffers whose planes are mapped separately, since [`CaptureStream::next`] only hands out
+    /// the first one. The slices are empty until the buffer was dequeued once.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer, as returned by [`CaptureStream::dequeue`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    /// use v4l::io::traits::{CaptureStream, Stream as _};
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCaptureMplane) {
+    ///         CaptureStream::queue(&mut stream, 0).unwrap();
+    ///         stream.start().unwrap();
+    ///         let index = CaptureStream::dequeue(&mut stream).unwrap();
+    ///         for (i, payload) in stream.get_planes(index).unwrap().iter().enumerate() {
+    ///             println!("plane {}: {} bytes", i, payload.len());
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn get_planes(&self, index: usize) -> Option<Vec<&[u8]>> {
+        let planes = self.arena.planes(index)?;
+        let used = &self.plane_bytesused[index];
+        let offsets = &self.plane_data_offset[index];
+        Some(
+            planes
+                .into_iter()
+                .enumerate()
+                .map(|(i, plane)| {
+                    let (start, end) = payload(
+                        plane.len(),
+                        used.get(i).copied().unwrap_or(0),
+                        offsets.get(i).copied().unwrap_or(0),
+                    );
+                    &plane[start..end]
+                })
+                .collect(),
+        )
+    }
+
+    /// Exports a plane of a buffer as a dma-buf file descriptor
+    ///
+    /// The dma-buf refers to the same memory as the mapped buffer, so it can be shared with
+    /// another process or imported by a GPU without copying. The memory stays valid as long as
+    /// the descriptor is open, even after the stream was dropped. The descriptor is closed when
+    /// the returned [`OwnedFd`] is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer
+    /// * `plane` - Index of the plane, zero for single-planar buffer types
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::os::unix::io::AsRawFd;
+    ///
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         if let Ok(fd) = stream.export_buffer(0, 0) {
+    ///             println!("buffer 0 exported as fd {}", fd.as_raw_fd());
+    ///         }
+    ///         assert!(stream.export_buffer(0, 1).is_err());
+    ///     }
+    /// }
+    /// ```
+    pub fn export_buffer(&self, index: usize, plane: u32) -> io::Result<OwnedFd> {
+        if index >= self.arena.len() || plane as usize >= self.arena.num_planes() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid buffer index {} or plane {}", index, plane),
+            ));
+        }
+
+        let mut v4l2_expbuf = v4l2_exportbuffer {
+            type_: self.buf_type as u32,
+            index: index as u32,
+            plane,
+            flags: (libc::O_CLOEXEC | libc::O_RDWR) as u32,
+            ..unsafe { mem::zeroed() }
+        };
+        unsafe {
+            v4l2::ioctl(
+                self.handle.fd(),
+                v4l2::vidioc::VIDIOC_EXPBUF,
+                &mut v4l2_expbuf as *mut _ as *mut std::os::raw::c_void,
+            )?;
+
+            Ok(OwnedFd::from_raw_fd(v4l2_expbuf.fd))
+        }
+    }
+
+    /// Returns a device which shares the handle of this stream
+    ///
+    /// Many controls such as exposure or gain may be changed while streaming. The returned device
+    /// can be used to do that, even from another thread, without having to keep the original
+    /// device around. Note that drivers may reject changes which affect the buffer layout (e.g.
+    /// rotation or format) with `EBUSY` while the stream is active.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::control::{Control, Value};
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         let ctrl = Control {
+    ///             id: v4l::v4l_sys::V4L2_CID_BRIGHTNESS,
+    ///             value: Value::Integer(128),
+    ///         };
+    ///         let res = stream.device().set_control(ctrl);
+    ///     }
+    /// }
+    /// ```
+    pub fn device(&self) -> Device {
+        Device::from(self.handle.clone())
+    }
+
+    /// Returns whether streaming is turned on
+    pub(crate) fn is_active(&self) -> bool {
+        self.state.active
+    }
+
+    /// Captures frames and writes each of them to a file in the given directory
+    ///
+    /// The files contain the raw frame data (the first `bytesused` bytes of each buffer) without
+    /// any header. Their layout is thus defined by the active format of the device: the
+    /// pixelformat (fourcc), width, height and stride (bytes per line). Compressed formats such
+    /// as MJPG yield files which can be opened directly.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory to write the files to, must exist
+    /// * `count` - Number of frames to capture
+    /// * `namer` - Returns the file name for a frame, e.g. based on its sequence number
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         let res = stream.save_frames_to_dir(Path::new("/tmp"), 10, |meta| {
+    ///             format!("frame-{:06}.raw", meta.sequence)
+    ///         });
+    ///     }
+    /// }
+    /// ```
+    pub fn save_frames_to_dir(
+        &mut self,
+        dir: &Path,
+        count: usize,
+        namer: impl Fn(&Metadata) -> String,
+    ) -> io::Result<()> {
+        save_frames_to_dir(self, dir, count, namer)
+    }
+
     /// Sets a timeout of the v4l file handle.
+    ///
+    /// If no buffer becomes ready within the given duration, dequeueing fails with an error of
+    /// kind [`io::ErrorKind::TimedOut`].
+    ///
+    /// `poll` has a granularity of milliseconds, so the duration is rounded up to the next full
+    /// millisecond (a non-zero duration never turns into a non-blocking poll). Durations beyond
+    /// `i32::MAX` milliseconds (about 24.8 days) are clamped to that value, which is effectively
+    /// an infinite wait. Use [`Stream::clear_timeout`] to wait forever.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io;
+    /// use std::time::Duration;
+    ///
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::traits::CaptureStream;
+    /// use v4l::io::mmap::Stream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         // clamped to ~24.8 days instead of overflowing
+    ///         stream.set_timeout(Duration::MAX);
+    ///         stream.set_timeout(Duration::from_millis(500));
+    ///         match stream.next() {
+    ///             Err(e) if e.kind() == io::ErrorKind::TimedOut => println!("no frame in time"),
+    ///             _ => {}
+    ///         }
+    ///     }
+    /// }
+    /// ```
     pub fn set_timeout(&mut self, duration: Duration) {
-        self.timeout = Some(duration.as_millis().try_into().unwrap());
+        self.timeout = Some(poll_timeout(duration));
     }
 
     /// Clears the timeout of the v4l file handle.
@@ -80,12 +653,309 @@ impl<'a> Stream<'a> {
         self.timeout = None;
     }
 
+    /// Sets the number of frames to drop when the stream is started.
+    ///
+    /// Many devices deliver a couple of dark or otherwise garbled frames right after streaming
+    /// was turned on. These are discarded on the first call to `next`.
+    pub fn set_warmup(&mut self, frames: u32) {
+        self.state.warmup = frames;
+    }
+
+    /// Sets the time to wait for the device to settle when the stream is started.
+    ///
+    /// Auto-exposure and auto-focus algorithms usually need a while to converge. On the first call
+    /// to `next`, frames are captured and dropped until the given duration has passed. This is
+    /// more robust than a fixed frame count across different sensors and frame rates.
+    ///
+    /// If a warmup frame count is set as well (see [`Stream::set_warmup`]), frames are dropped
+    /// until both conditions are satisfied.
+    pub fn set_settle(&mut self, duration: Duration) {
+        self.state.settle = duration;
+    }
+
+    /// Sets how often `start` retries to turn on streaming if the device is busy.
+    ///
+    /// Some drivers report `EBUSY` for a short while after a previous stream was stopped because
+    /// the teardown is still in progress. By default, streaming is retried up to 5 times with a
+    /// 20ms pause in between, i.e. for about 100ms. Pass zero to disable retries.
+    pub fn set_start_retries(&mut self, retries: u32) {
+        self.state.start_retries = retries;
+    }
+
+    /// Sets when buffers returned by `next` are handed back to the driver
+    ///
+    /// By default ([`RequeuePolicy::Immediate`]), the previous buffer is queued again right
+    /// before waiting for the next frame. With [`RequeuePolicy::Deferred`], it is held until the
+    /// next frame has been dequeued, so the driver cannot overwrite it while it may still be
+    /// read. This does not affect the order in which the driver fills the queued buffers.
+    ///
+    /// Deferring reduces the number of buffers the driver can capture into by one: with `n`
+    /// buffers, at most `n - 1` frames can be queued up before the driver has to drop frames.
+    /// Allocate at least three buffers when deferring, fewer than two are rejected with an
+    /// error of kind [`io::ErrorKind::InvalidInput`] since the driver would have no buffer left.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    /// use v4l::io::traits::CaptureStream;
+    /// use v4l::io::RequeuePolicy;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::with_buffers(&dev, Type::VideoCapture, 4) {
+    ///         stream.set_requeue_policy(RequeuePolicy::Deferred).unwrap();
+    ///         let (buf, meta) = stream.next().unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub fn set_requeue_policy(&mut self, policy: RequeuePolicy) -> io::Result<()> {
+        if policy == RequeuePolicy::Deferred && self.arena.len() < 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "deferred requeueing requires at least two buffers",
+            ));
+        }
+
+        self.state.requeue_policy = policy;
+        Ok(())
+    }
+
+    /// Makes `next` skip buffers which the driver flagged as errored
+    ///
+    /// Drivers still return buffers whose data may be corrupt (e.g. because of USB transfer
+    /// errors), they are only marked with [`Flags::ERROR`]. Once enabled, such buffers are
+    /// queued again right away and the next frame is fetched instead. To bound the latency, an
+    /// error of kind [`io::ErrorKind::InvalidData`] is returned if more buffers than allowed by
+    /// [`Stream::set_error_retries`] are errored in a row. Skipped buffers are still counted in
+    /// [`Stream::stats`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    /// use v4l::io::traits::CaptureStream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         stream.set_skip_errored(true);
+    ///         stream.set_error_retries(10);
+    ///         match stream.next() {
+    ///             Ok((buf, meta)) => println!("frame {}: {} bytes", meta.sequence, buf.len()),
+    ///             Err(e) => println!("error: {}", e),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn set_skip_errored(&mut self, skip: bool) {
+        self.state.skip_errored = skip;
+    }
+
+    /// Sets how many errored buffers in a row are skipped before giving up, 3 by default
+    ///
+    /// Only has an effect if skipping is enabled, see [`Stream::set_skip_errored`].
+    pub fn set_error_retries(&mut self, retries: u32) {
+        self.state.error_retries = retries;
+    }
+
+    /// Makes the stream report source changes (e.g. HDMI resolution switches)
+    ///
+    /// Once enabled, dequeueing a buffer fails with a [`crate::io::SourceChanged`] error (see
+    /// [`crate::io::is_source_change`]) when the source changed its resolution. The frames
+    /// captured after that are garbage, so the stream has to be reconfigured:
+    ///
+    /// 1. Drop the stream, which stops streaming and releases the buffers
+    /// 2. Query and apply the new timings (see `Device::auto_configure_hdmi`)
+    /// 3. Create a new stream, which allocates buffers matching the new format
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::is_source_change;
+    /// use v4l::io::traits::CaptureStream;
+    /// use v4l::io::mmap::Stream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         if stream.watch_source_change().is_ok() {
+    ///             match stream.next() {
+    ///                 Err(e) if is_source_change(&e) => println!("reconfigure"),
+    ///                 _ => {}
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn watch_source_change(&mut self) -> io::Result<()> {
+        self.handle.subscribe_event(V4L2_EVENT_SOURCE_CHANGE, 0)?;
+        self.state.watch_source_change = true;
+        Ok(())
+    }
+
+    /// Queues a buffer as part of a media request
+    ///
+    /// This is needed for drivers which implement the request API, such as stateless codecs. The
+    /// buffer is only processed once the request is queued (`MEDIA_REQUEST_IOC_QUEUE`). Output
+    /// buffers are queued with the metadata set through `OutputStream::next`, just like
+    /// `OutputStream::queue` does.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer
+    /// * `request_fd` - File descriptor of the media request
+    pub fn queue_with_request(&mut self, index: usize, request_fd: RawFd) -> io::Result<()> {
+        self.request_fd = Some(request_fd);
+        let res = if self.buf_type.is_output() {
+            OutputStream::queue(self, index)
+        } else {
+            CaptureStream::queue(self, index)
+        };
+        self.request_fd = None;
+        res
+    }
+
     fn buffer_desc(&self) -> v4l2_buffer {
-        v4l2_buffer {
+        let mut v4l2_buf = v4l2_buffer {
             type_: self.buf_type as u32,
             memory: Memory::Mmap as u32,
             ..unsafe { mem::zeroed() }
+        };
+        if let Some(fd) = self.request_fd {
+            v4l2_buf.flags |= u32::from(Flags::REQUEST_FD);
+            v4l2_buf.__bindgen_anon_1.request_fd = fd;
         }
+
+        v4l2_buf
+    }
+
+    /// Runs a buffer ioctl, passing a plane array along for multi-planar buffers
+    fn buffer_ioctl(
+        &self,
+        request: v4l2::vidioc::_IOC_TYPE,
+        v4l2_buf: &mut v4l2_buffer,
+        v4l2_planes: &mut Planes,
+    ) -> io::Result<()> {
+        if self.buf_type.is_mplane() {
+            v4l2_buf.length = self.arena.num_planes() as u32;
+            v4l2_buf.m.planes = v4l2_planes.as_mut_ptr();
+        }
+
+        unsafe {
+            v4l2::ioctl(
+                self.handle.fd(),
+                request,
+                v4l2_buf as *mut _ as *mut std::os::raw::c_void,
+            )
+        }
+    }
+
+    /// Records the payload of each plane of a dequeued buffer
+    ///
+    /// Returns the number of bytes used in the first mapping, i.e. the slice handed out by
+    /// [`Stream::get`].
+    fn update_bytesused(&mut self, v4l2_buf: &v4l2_buffer, v4l2_planes: &Planes) -> u32 {
+        let index = v4l2_buf.index as usize;
+        let used = &mut self.plane_bytesused[index];
+        let offsets = &mut self.plane_data_offset[index];
+        used.clear();
+        offsets.clear();
+        if self.buf_type.is_mplane() {
+            for plane in &v4l2_planes[..self.arena.num_planes()] {
+                used.push(plane.bytesused);
+                offsets.push(plane.data_offset);
+            }
+        } else {
+            used.push(v4l2_buf.bytesused);
+            offsets.push(0);
+        }
+
+        mapped_bytesused(self.arena.plane_layout(index).unwrap_or(&[]), used)
+    }
+
+    /// Queues an output buffer without waiting for the driver to release one first
+    ///
+    /// Mem2mem devices only signal `POLLOUT` once they are done with a buffer, so callers which
+    /// track the free buffers themselves have to skip the wait done by [`OutputStream::queue`].
+    pub(crate) fn submit(&mut self, index: usize) -> io::Result<()> {
+        let mut v4l2_buf = v4l2_buffer {
+            index: index as u32,
+            ..self.buffer_desc()
+        };
+        let mut v4l2_planes: Planes = unsafe { mem::zeroed() };
+
+        // output settings
+        //
+        // MetaData.bytesused is initialized to 0. For an output device, when bytesused is
+        // set to 0 v4l2 will set it to the size of the plane:
+        // https://www.kernel.org/doc/html/v4.15/media/uapi/v4l/buffer.html#struct-v4l2-plane
+        v4l2_buf.bytesused = self.buf_meta[index].bytesused;
+        v4l2_buf.field = self.buf_meta[index].field;
+
+        if self.buf_type.is_mplane() {
+            // Multi-planar buffers carry the number of bytes per plane, fill them in order.
+            let mut remaining = self.buf_meta[index].bytesused;
+            if let Some(planes) = self.arena.planes(index) {
+                for (v4l2_plane, plane) in v4l2_planes.iter_mut().zip(planes) {
+                    v4l2_plane.bytesused = remaining.min(plane.len() as u32);
+                    remaining -= v4l2_plane.bytesused;
+                }
+            }
+        }
+
+        self.buffer_ioctl(v4l2::vidioc::VIDIOC_QBUF, &mut v4l2_buf, &mut v4l2_planes)
+    }
+}
+
+/// Returns the number of bytes used in the first mapping of a buffer
+///
+/// Contiguous planes share the first mapping, so the payload ends after the last used byte of
+/// the last plane. Separately mapped planes only contribute the first plane.
+///
+/// # Arguments
+///
+/// * `layout` - Planes as (map index, offset, length)
+/// * `used` - Bytes used in each plane
+fn mapped_bytesused(layout: &[(usize, usize, usize)], used: &[u32]) -> u32 {
+    layout
+        .iter()
+        .zip(used)
+        .filter(|((map, _, _), _)| *map == 0)
+        .map(|(&(_, offset, len), &used)| (offset + (used as usize).min(len)) as u32)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Returns the payload of a plane as range (start, end)
+///
+/// The bytes used include the data offset, both are clamped to the plane.
+///
+/// # Arguments
+///
+/// * `len` - Length of the plane
+/// * `bytesused` - Bytes used in the plane
+/// * `data_offset` - Offset of the data in the plane
+fn payload(len: usize, bytesused: u32, data_offset: u32) -> (usize, usize) {
+    let end = (bytesused as usize).min(len);
+    let start = (data_offset as usize).min(end);
+    (start, end)
+}
+
+impl<'a> fmt::Debug for Stream<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stream")
+            .field("buf_type", &self.buf_type)
+            .field("memory", &format_args!("{}", Memory::Mmap))
+            .field("buf_count", &self.arena.len())
+            .field("active", &self.state.active)
+            .finish()
     }
 }
 
@@ -107,20 +977,25 @@ impl<'a> Drop for Stream<'a> {
     }
 }
 
+impl<'a> AsRawFd for Stream<'a> {
+    /// Returns the file descriptor of the device, see [`Stream::fd`]
+    fn as_raw_fd(&self) -> RawFd {
+        self.handle.fd()
+    }
+}
+
 impl<'a> StreamTrait for Stream<'a> {
     type Item = [u8];
 
     fn start(&mut self) -> io::Result<()> {
-        unsafe {
-            let mut typ = self.buf_type as u32;
-            v4l2::ioctl(
-                self.handle.fd(),
-                v4l2::vidioc::VIDIOC_STREAMON,
-                &mut typ as *mut _ as *mut std::os::raw::c_void,
-            )?;
+        stream_on(&self.handle, self.buf_type, self.state.start_retries)?;
+
+        if self.buf_type.is_output() {
+            // Needed for underrun detection, see OutputStream::underruns()
+            self.frame_interval = output_interval(&self.device());
         }
 
-        self.active = true;
+        self.state.active = true;
         Ok(())
     }
 
@@ -134,141 +1009,166 @@ impl<'a> StreamTrait for Stream<'a> {
             )?;
         }
 
-        self.active = false;
+        self.state.active = false;
+        self.state.held = false;
+        self.last_sequence = None;
         Ok(())
     }
 }
 
-impl<'a, 'b> CaptureStream<'b> for Stream<'a> {
-    fn queue(&mut self, index: usize) -> io::Result<()> {
+impl<'a> Queue for Stream<'a> {
+    fn state(&self) -> &QueueState {
+        &self.state
+    }
+
+    fn state_mut(&mut self) -> &mut QueueState {
+        &mut self.state
+    }
+
+    fn handle_ref(&self) -> &Handle {
+        &self.handle
+    }
+
+    fn buffer_count(&self) -> usize {
+        self.arena.len()
+    }
+
+    fn meta(&self, index: usize) -> &Metadata {
+        &self.buf_meta[index]
+    }
+
+    fn query_flags(&self, index: usize) -> io::Result<Flags> {
         let mut v4l2_buf = v4l2_buffer {
             index: index as u32,
-            ..self.buffer_desc()
+            type_: self.buf_type as u32,
+            memory: Memory::Mmap as u32,
+            ..unsafe { mem::zeroed() }
         };
+        let mut v4l2_planes: Planes = unsafe { mem::zeroed() };
+        self.buffer_ioctl(
+            v4l2::vidioc::VIDIOC_QUERYBUF,
+            &mut v4l2_buf,
+            &mut v4l2_planes,
+        )?;
 
-        unsafe {
-            v4l2::ioctl(
-                self.handle.fd(),
-                v4l2::vidioc::VIDIOC_QBUF,
-                &mut v4l2_buf as *mut _ as *mut std::os::raw::c_void,
-            )?;
-        }
+        Ok(Flags::from(v4l2_buf.flags))
+    }
 
-        Ok(())
+    fn queue_buffer(&mut self, index: usize) -> io::Result<()> {
+        let mut v4l2_buf = v4l2_buffer {
+            index: index as u32,
+            ..self.buffer_desc()
+        };
+        let mut v4l2_planes: Planes = unsafe { mem::zeroed() };
+        self.buffer_ioctl(v4l2::vidioc::VIDIOC_QBUF, &mut v4l2_buf, &mut v4l2_planes)
     }
 
-    fn dequeue(&mut self) -> io::Result<usize> {
+    fn dequeue_ready(&mut self) -> io::Result<usize> {
         let mut v4l2_buf = self.buffer_desc();
 
-        if self.handle.poll(libc::POLLIN, self.timeout.unwrap_or(-1))? == 0 {
-            // This condition can only happen if there was a timeout.
-            // A timeout is only possible if the `timeout` value is non-zero, meaning we should
-            // propagate it to the caller.
-            return Err(io::Error::new(io::ErrorKind::TimedOut, "VIDIOC_DQBUF"));
-        }
+        let mut v4l2_planes: Planes = unsafe { mem::zeroed() };
+        self.buffer_ioctl(v4l2::vidioc::VIDIOC_DQBUF, &mut v4l2_buf, &mut v4l2_planes)?;
+        let index = v4l2_buf.index as usize;
 
-        unsafe {
-            v4l2::ioctl(
-                self.handle.fd(),
-                v4l2::vidioc::VIDIOC_DQBUF,
-                &mut v4l2_buf as *mut _ as *mut std::os::raw::c_void,
-            )?;
-        }
-        self.arena_index = v4l2_buf.index as usize;
-
-        self.buf_meta[self.arena_index] = Metadata {
-            bytesused: v4l2_buf.bytesused,
+        let bytesused = self.update_bytesused(&v4l2_buf, &v4l2_planes);
+        self.buf_meta[index] = Metadata {
+            bytesused,
             flags: v4l2_buf.flags.into(),
             field: v4l2_buf.field,
             timestamp: v4l2_buf.timestamp.into(),
             sequence: v4l2_buf.sequence,
         };
+        self.stats
+            .record(&self.buf_meta[index], &mut self.last_sequence);
 
-        Ok(self.arena_index)
+        Ok(index)
     }
+}
 
-    fn next(&'b mut self) -> io::Result<(&Self::Item, &Metadata)> {
-        if !self.active {
-            // Enqueue all buffers once on stream start
-            for index in 0..self.arena.bufs.len() {
-                CaptureStream::queue(self, index)?;
-            }
+impl<'a, 'b> CaptureStream<'b> for Stream<'a> {
+    fn queue(&mut self, index: usize) -> io::Result<()> {
+        self.queue_buffer(index)
+    }
 
-            self.start()?;
-        } else {
-            CaptureStream::queue(self, self.arena_index)?;
+    fn dequeue(&mut self) -> io::Result<usize> {
+        self.dequeue_within(self.timeout.unwrap_or(-1))
+    }
+
+    fn try_next(&'b mut self) -> io::Result<Option<(&Self::Item, &Metadata)>> {
+        if !self.try_fetch()? {
+            // Nothing ready yet, the next call continues from here
+            return Ok(None);
+        }
+
+        // The index used to access the buffer elements is given to us by v4l2, so we assume it
+        // will always be valid.
+        let bytes = self.arena.get(self.state.index).unwrap();
+        let meta = &self.buf_meta[self.state.index];
+        Ok(Some((bytes, meta)))
+    }
+
+    fn next_latest(&'b mut self) -> io::Result<(&Self::Item, &Metadata)> {
+        self.fetch_latest(self.timeout.unwrap_or(-1))?;
+
+        // The index used to access the buffer elements is given to us by v4l2, so we assume it
+        // will always be valid.
+        let bytes = self.arena.get(self.state.index).unwrap();
+        let meta = &self.buf_meta[self.state.index];
+        Ok((bytes, meta))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.state.active {
+            return Ok(());
         }
 
-        self.arena_index = CaptureStream::dequeue(self)?;
+        self.requeue()
+    }
+
+    fn restart(&mut self) -> io::Result<()> {
+        self.requeue()
+    }
+
+    fn next(&'b mut self) -> io::Result<(&Self::Item, &Metadata)> {
+        self.fetch(self.timeout.unwrap_or(-1))?;
 
         // The index used to access the buffer elements is given to us by v4l2, so we assume it
         // will always be valid.
-        let bytes = &self.arena.bufs[self.arena_index];
-        let meta = &self.buf_meta[self.arena_index];
+        let bytes = self.arena.get(self.state.index).unwrap();
+        let meta = &self.buf_meta[self.state.index];
         Ok((bytes, meta))
     }
 }
 
 impl<'a, 'b> OutputStream<'b> for Stream<'a> {
     fn queue(&mut self, index: usize) -> io::Result<()> {
-        let mut v4l2_buf = v4l2_buffer {
-            index: index as u32,
-            ..self.buffer_desc()
-        };
-        unsafe {
-            // output settings
-            //
-            // MetaData.bytesused is initialized to 0. For an output device, when bytesused is
-            // set to 0 v4l2 will set it to the size of the plane:
-            // https://www.kernel.org/doc/html/v4.15/media/uapi/v4l/buffer.html#struct-v4l2-plane
-            v4l2_buf.bytesused = self.buf_meta[index].bytesused;
-            v4l2_buf.field = self.buf_meta[index].field;
-
-            if self
-                .handle
-                .poll(libc::POLLOUT, self.timeout.unwrap_or(-1))?
-                == 0
-            {
-                // This condition can only happen if there was a timeout.
-                // A timeout is only possible if the `timeout` value is non-zero, meaning we should
-                // propagate it to the caller.
-                return Err(io::Error::new(io::ErrorKind::TimedOut, "VIDIOC_QBUF"));
+        let wait = Instant::now();
+        if self
+            .handle
+            .poll(libc::POLLOUT, self.timeout.unwrap_or(-1))?
+            == 0
+        {
+            // This condition can only happen if there was a timeout.
+            // A timeout is only possible if the `timeout` value is non-zero, meaning we should
+            // propagate it to the caller.
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "VIDIOC_QBUF"));
+        }
+        if let Some(interval) = self.frame_interval {
+            if wait.elapsed() > interval {
+                self.underruns += 1;
             }
-
-            v4l2::ioctl(
-                self.handle.fd(),
-                v4l2::vidioc::VIDIOC_QBUF,
-                &mut v4l2_buf as *mut _ as *mut std::os::raw::c_void,
-            )
         }
+
+        self.submit(index)
     }
 
     fn dequeue(&mut self) -> io::Result<usize> {
-        let mut v4l2_buf = self.buffer_desc();
-
-        unsafe {
-            v4l2::ioctl(
-                self.handle.fd(),
-                v4l2::vidioc::VIDIOC_DQBUF,
-                &mut v4l2_buf as *mut _ as *mut std::os::raw::c_void,
-            )?;
-        }
-        self.arena_index = v4l2_buf.index as usize;
-
-        self.buf_meta[self.arena_index] = Metadata {
-            bytesused: v4l2_buf.bytesused,
-            flags: v4l2_buf.flags.into(),
-            field: v4l2_buf.field,
-            timestamp: v4l2_buf.timestamp.into(),
-            sequence: v4l2_buf.sequence,
-        };
-
-        Ok(self.arena_index)
+        self.dequeue_ready()
     }
 
     fn next(&'b mut self) -> io::Result<(&mut Self::Item, &mut Metadata)> {
-        let init = !self.active;
-        if !self.active {
+        let init = !self.state.active;
+        if !self.state.active {
             self.start()?;
         }
 
@@ -276,14 +1176,90 @@ impl<'a, 'b> OutputStream<'b> for Stream<'a> {
         // call to this function from the call site will happen just after the buffers have been
         // allocated, meaning we need to return the empty buffer initially so it can be filled.
         if !init {
-            OutputStream::queue(self, self.arena_index)?;
-            self.arena_index = OutputStream::dequeue(self)?;
+            OutputStream::queue(self, self.state.index)?;
+            self.state.index = OutputStream::dequeue(self)?;
         }
 
         // The index used to access the buffer elements is given to us by v4l2, so we assume it
         // will always be valid.
-        let bytes = &mut self.arena.bufs[self.arena_index];
-        let meta = &mut self.buf_meta[self.arena_index];
+        let bytes = self.arena.get_mut(self.state.index).unwrap();
+        let meta = &mut self.buf_meta[self.state.index];
         Ok((bytes, meta))
     }
+
+    fn drain(&mut self) -> io::Result<()> {
+        self.drain_output(self.timeout.unwrap_or(-1))
+    }
+
+    fn underruns(&self) -> u64 {
+        self.underruns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytesused_single_planar() {
+        assert_eq!(mapped_bytesused(&[(0, 0, 4096)], &[1000]), 1000);
+        // clamped to the buffer
+        assert_eq!(mapped_bytesused(&[(0, 0, 4096)], &[5000]), 4096);
+    }
+
+    #[test]
+    fn bytesused_contiguous_planes() {
+        // NV12 with both planes in one mapping, the chroma plane is partially used
+        let layout = [(0, 0, 640 * 480), (0, 640 * 480, 640 * 240)];
+        assert_eq!(
+            mapped_bytesused(&layout, &[640 * 480, 1000]),
+            640 * 480 + 1000
+        );
+    }
+
+    #[test]
+    fn bytesused_separate_planes() {
+        // only the first plane is part of the first mapping
+        let layout = [(0, 0, 640 * 480), (1, 0, 640 * 240)];
+        assert_eq!(
+            mapped_bytesused(&layout, &[640 * 480, 640 * 240]),
+            640 * 480
+        );
+    }
+
+    #[test]
+    fn plane_payload() {
+        assert_eq!(payload(4096, 1000, 0), (0, 1000));
+        assert_eq!(payload(4096, 1000, 64), (64, 1000));
+        // clamped to the plane
+        assert_eq!(payload(4096, 5000, 64), (64, 4096));
+        assert_eq!(payload(4096, 32, 64), (32, 32));
+    }
+
+    // Needs a v4l2loopback device which is fed by a producer, e.g.:
+    //
+    //   sudo modprobe v4l2loopback devices=1 exclusive_caps=1
+    //   gst-launch-1.0 videotestsrc is-live=true ! v4l2sink device=/dev/video0 &
+    //   cargo test -- --ignored restart_after_stop
+    //
+    // Set V4L2LOOPBACK_DEVICE to use another device node than /dev/video0.
+    #[test]
+    #[ignore = "needs a v4l2loopback device, see the comment above"]
+    fn restart_after_stop() {
+        let path =
+            std::env::var("V4L2LOOPBACK_DEVICE").unwrap_or_else(|_| "/dev/video0".to_string());
+        let dev = Device::with_path(path).unwrap();
+        let mut stream = Stream::new(&dev, Type::VideoCapture).unwrap();
+        stream.set_timeout(Duration::from_secs(5));
+
+        let (buf, _) = CaptureStream::next(&mut stream).unwrap();
+        assert!(!buf.is_empty());
+
+        StreamTrait::stop(&mut stream).unwrap();
+        CaptureStream::restart(&mut stream).unwrap();
+
+        let (buf, meta) = CaptureStream::next(&mut stream).unwrap();
+        assert!(!buf.is_empty());
+        assert!(meta.bytesused > 0);
+    }
 }
@@ -1,4 +1,757 @@
+use std::convert::TryInto;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use std::{error, fmt, fs, io, mem, thread};
+
+use crate::buffer::{Flags, Metadata, Type};
+use crate::device::{Device, Handle};
+use crate::io::traits::{CaptureStream, OutputStream, Stream};
+use crate::v4l2;
+use crate::video::Output;
+
 pub mod traits;
 
+pub mod dmabuf;
+pub mod duplex;
 pub mod mmap;
+pub mod pipeline;
+#[cfg(feature = "tokio")]
+pub mod tokio;
 pub mod userptr;
+
+/// When capture streams hand a buffer back to the driver after it was returned by `next`
+///
+/// See [`mmap::Stream::set_requeue_policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RequeuePolicy {
+    /// Queue the previous buffer right before waiting for the next frame
+    ///
+    /// The driver can fill all buffers, which minimizes the risk of dropping frames.
+    #[default]
+    Immediate,
+    /// Queue the previous buffer only once the next frame has been dequeued
+    ///
+    /// The previous buffer stays untouched until a new frame is available, so the driver never
+    /// writes to a buffer which may still be in use (e.g. by a zero-copy consumer which reads it
+    /// asynchronously). In exchange, the driver has one buffer less to capture into.
+    Deferred,
+}
+
+/// Counters of a stream, e.g. to diagnose flaky cameras
+///
+/// The counters accumulate over the lifetime of the stream, including restarts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StreamStats {
+    /// Number of buffers dequeued, including the frames dropped during warmup
+    pub dequeued: u64,
+    /// Number of dequeued buffers flagged with [`Flags::ERROR`], their data may be corrupt
+    pub errored: u64,
+    /// Number of frames the driver skipped, derived from gaps in [`Metadata::sequence`]
+    pub sequence_gaps: u64,
+}
+
+impl fmt::Display for StreamStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "dequeued      : {}", self.dequeued)?;
+        writeln!(f, "errored       : {}", self.errored)?;
+        writeln!(f, "sequence gaps : {}", self.sequence_gaps)?;
+        Ok(())
+    }
+}
+
+impl StreamStats {
+    /// Accounts for a dequeued buffer
+    ///
+    /// # Arguments
+    ///
+    /// * `meta` - Metadata of the buffer
+    /// * `last_sequence` - Sequence number of the previously dequeued buffer, reset this to
+    ///   `None` when streaming is stopped since drivers restart counting at zero
+    pub(crate) fn record(&mut self, meta: &Metadata, last_sequence: &mut Option<u32>) {
+        self.dequeued += 1;
+        if meta.flags.contains(Flags::ERROR) {
+            self.errored += 1;
+        }
+        if let Some(last) = *last_sequence {
+            // the counter may wrap around, sequence numbers which go backwards are ignored
+            let delta = meta.sequence.wrapping_sub(last);
+            if delta > 1 && delta < u32::MAX / 2 {
+                self.sequence_gaps += u64::from(delta - 1);
+            }
+        }
+        *last_sequence = Some(meta.sequence);
+    }
+}
+
+/// Error returned by streams when the source changed its resolution
+///
+/// Streams only report this if they were asked to watch for source changes. It is wrapped in an
+/// [`io::Error`] of kind [`io::ErrorKind::Other`], use [`is_source_change`] to detect it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceChanged;
+
+impl fmt::Display for SourceChanged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "source resolution changed")
+    }
+}
+
+impl error::Error for SourceChanged {}
+
+impl From<SourceChanged> for io::Error {
+    fn from(err: SourceChanged) -> Self {
+        io::Error::new(io::ErrorKind::Other, err)
+    }
+}
+
+/// Returns whether an error signals a source change (see [`SourceChanged`])
+///
+/// # Arguments
+///
+/// * `err` - Error returned by a stream
+///
+/// # Example
+///
+/// ```
+/// use std::io;
+/// use v4l::io::{is_source_change, SourceChanged};
+///
+/// assert!(is_source_change(&io::Error::from(SourceChanged)));
+/// assert!(!is_source_change(&io::Error::new(io::ErrorKind::Other, "foo")));
+/// ```
+pub fn is_source_change(err: &io::Error) -> bool {
+    matches!(err.get_ref(), Some(inner) if inner.is::<SourceChanged>())
+}
+
+/// Converts a stream timeout to the milliseconds expected by `poll`
+///
+/// Partial milliseconds are rounded up so short timeouts do not turn into a non-blocking poll,
+/// durations beyond the range of `poll` are clamped to `i32::MAX` (~24.8 days).
+///
+/// # Arguments
+///
+/// * `duration` - Timeout
+pub(crate) fn poll_timeout(duration: Duration) -> i32 {
+    let millis = duration.as_nanos().div_ceil(1_000_000);
+    millis.try_into().unwrap_or(i32::MAX)
+}
+
+/// Captures frames and writes the first `bytesused` bytes of each of them to a file
+///
+/// # Arguments
+///
+/// * `stream` - Stream to capture from
+/// * `dir` - Directory to write the files to
+/// * `count` - Number of frames to capture
+/// * `namer` - Returns the file name for a frame
+pub(crate) fn save_frames_to_dir<S>(
+    stream: &mut S,
+    dir: &Path,
+    count: usize,
+    namer: impl Fn(&Metadata) -> String,
+) -> io::Result<()>
+where
+    S: for<'a> CaptureStream<'a> + Stream<Item = [u8]>,
+{
+    for _ in 0..count {
+        let (buf, meta) = CaptureStream::next(&mut *stream)?;
+        let len = (meta.bytesused as usize).min(buf.len());
+        fs::write(dir.join(namer(meta)), &buf[..len])?;
+    }
+
+    Ok(())
+}
+
+/// Buffer bookkeeping and fetch settings of a stream, see [`Queue`]
+#[derive(Debug)]
+pub(crate) struct QueueState {
+    /// Buffer handed out by the last call to `next`
+    pub(crate) index: usize,
+    /// Whether the capture buffer at `index` is handed out, i.e. not queued
+    pub(crate) held: bool,
+    /// Whether streaming is turned on
+    pub(crate) active: bool,
+    pub(crate) warmup: u32,
+    pub(crate) settle: Duration,
+    /// Start time and number of dropped frames while the stream is settling after its start
+    pub(crate) settling: Option<(Instant, u32)>,
+    pub(crate) start_retries: u32,
+    pub(crate) watch_source_change: bool,
+    pub(crate) requeue_policy: RequeuePolicy,
+    pub(crate) skip_errored: bool,
+    pub(crate) error_retries: u32,
+}
+
+impl Default for QueueState {
+    fn default() -> Self {
+        QueueState {
+            index: 0,
+            held: false,
+            active: false,
+            warmup: 0,
+            settle: Duration::ZERO,
+            settling: None,
+            start_retries: 5,
+            watch_source_change: false,
+            requeue_policy: RequeuePolicy::Immediate,
+            skip_errored: false,
+            error_retries: 3,
+        }
+    }
+}
+
+/// Buffer queue of a stream
+///
+/// The provided methods implement fetching frames ([`CaptureStream::next`] and its variants) and
+/// draining output once for all memory types, on top of the queue primitives of each stream. The
+/// settings they honor are documented on [`mmap::Stream`].
+pub(crate) trait Queue: Stream {
+    /// Returns the bookkeeping of the queue
+    fn state(&self) -> &QueueState;
+
+    /// Returns the bookkeeping of the queue mutably
+    fn state_mut(&mut self) -> &mut QueueState;
+
+    /// Returns the handle of the device
+    fn handle_ref(&self) -> &Handle;
+
+    /// Returns the number of allocated buffers
+    fn buffer_count(&self) -> usize;
+
+    /// Returns the metadata of a buffer
+    fn meta(&self, index: usize) -> &Metadata;
+
+    /// Returns the flags the driver reports for a buffer (`VIDIOC_QUERYBUF`)
+    fn query_flags(&self, index: usize) -> io::Result<Flags>;
+
+    /// Inserts a capture buffer into the incoming queue
+    fn queue_buffer(&mut self, index: usize) -> io::Result<()>;
+
+    /// Dequeues a buffer without waiting for the driver to finish one first and records its
+    /// metadata
+    ///
+    /// The device is opened in non-blocking mode, so this fails with
+    /// [`io::ErrorKind::WouldBlock`] if no buffer is ready.
+    fn dequeue_ready(&mut self) -> io::Result<usize>;
+
+    /// Waits for a capture buffer to be done and dequeues it
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Timeout in milliseconds, negative values block
+    fn dequeue_within(&mut self, timeout: i32) -> io::Result<usize> {
+        let watch_source_change = self.state().watch_source_change;
+        if !self
+            .handle_ref()
+            .wait_dequeue(watch_source_change, timeout)?
+        {
+            // This condition can only happen if there was a timeout.
+            // A timeout is only possible if the `timeout` value is non-zero, meaning we should
+            // propagate it to the caller.
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "VIDIOC_DQBUF"));
+        }
+
+        self.dequeue_ready()
+    }
+
+    /// Dequeues a buffer, queueing errored ones again if requested (see
+    /// [`mmap::Stream::set_skip_errored`])
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Timeout in milliseconds for each wait, negative values block
+    fn dequeue_valid(&mut self, timeout: i32) -> io::Result<usize> {
+        let error_retries = self.state().error_retries;
+        let mut retries = error_retries;
+        loop {
+            let index = self.dequeue_within(timeout)?;
+            if !self.state().skip_errored || !self.meta(index).flags.contains(Flags::ERROR) {
+                return Ok(index);
+            }
+
+            self.queue_buffer(index)?;
+            if retries == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{} consecutive buffers were flagged as errored",
+                        error_retries + 1
+                    ),
+                ));
+            }
+            retries -= 1;
+        }
+    }
+
+    /// Drops frames until the device had a chance to settle, see [`mmap::Stream::set_warmup`]
+    /// and [`mmap::Stream::set_settle`]
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Timeout in milliseconds for each wait, negative values block
+    fn settle(&mut self, timeout: i32) -> io::Result<()> {
+        while let Some((start, frames)) = self.state().settling {
+            let state = self.state();
+            if frames >= state.warmup && start.elapsed() >= state.settle {
+                self.state_mut().settling = None;
+                break;
+            }
+
+            let index = self.dequeue_within(timeout)?;
+            self.queue_buffer(index)?;
+            self.state_mut().settling = Some((start, frames + 1));
+        }
+
+        Ok(())
+    }
+
+    /// Fetches a new frame into the `index` of the state, see [`CaptureStream::next`]
+    ///
+    /// Streaming is turned on and all buffers are queued on the first call. Errors such as
+    /// timeouts leave the queue in a consistent state, the buffer handed out before is queued
+    /// exactly once, so a later call picks up where this one stopped.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Timeout in milliseconds for each wait, negative values block
+    fn fetch(&mut self, timeout: i32) -> io::Result<()> {
+        if !self.state().active {
+            // Enqueue all buffers once on stream start
+            for index in 0..self.buffer_count() {
+                self.queue_buffer(index)?;
+            }
+
+            self.start()?;
+            self.state_mut().settling = Some((Instant::now(), 0));
+        }
+        self.settle(timeout)?;
+
+        let state = self.state();
+        if state.held && state.requeue_policy == RequeuePolicy::Immediate {
+            let index = state.index;
+            self.queue_buffer(index)?;
+            self.state_mut().held = false;
+        }
+
+        // A buffer which is still held at this point is requeued deferred
+        let index = self.dequeue_valid(timeout)?;
+        let state = self.state_mut();
+        let prev = mem::replace(&mut state.index, index);
+        if mem::replace(&mut state.held, true) {
+            self.queue_buffer(prev)?;
+        }
+
+        Ok(())
+    }
+
+    /// Fetches a new frame if one is ready, see [`CaptureStream::try_next`]
+    ///
+    /// Returns `false` if no frame is ready yet, the next call continues from there.
+    fn try_fetch(&mut self) -> io::Result<bool> {
+        match self.fetch(0) {
+            Ok(()) => Ok(true),
+            Err(e)
+                if e.kind() == io::ErrorKind::TimedOut || e.kind() == io::ErrorKind::WouldBlock =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Fetches the most recent frame, see [`CaptureStream::next_latest`]
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Timeout in milliseconds for each wait, negative values block
+    fn fetch_latest(&mut self, timeout: i32) -> io::Result<()> {
+        if !self.state().active {
+            return self.fetch(timeout);
+        }
+
+        // Hand the previous buffer back and wait for at least one frame
+        self.fetch(timeout)?;
+
+        // Drop frames as long as newer ones are ready
+        loop {
+            match self.dequeue_valid(0) {
+                Ok(index) => {
+                    let prev = mem::replace(&mut self.state_mut().index, index);
+                    self.queue_buffer(prev)?;
+                }
+                // Drained, possibly after the driver reported readiness prematurely
+                Err(e)
+                    if e.kind() == io::ErrorKind::TimedOut
+                        || e.kind() == io::ErrorKind::WouldBlock =>
+                {
+                    return Ok(())
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Turns streaming off and on again, see [`CaptureStream::restart`]
+    fn requeue(&mut self) -> io::Result<()> {
+        // VIDIOC_STREAMOFF returns all buffers, including the ones holding captured frames.
+        // This is fine if the stream is already stopped.
+        let held = self.state().held;
+        self.stop()?;
+
+        // The buffer returned by the last call to next() is queued by the following call
+        let handed_out = self.state().index;
+        for index in 0..self.buffer_count() {
+            if !held || index != handed_out {
+                self.queue_buffer(index)?;
+            }
+        }
+
+        self.start()?;
+        self.state_mut().held = held;
+        Ok(())
+    }
+
+    /// Queues the buffer handed out by `next`, waits for the driver to process all queued
+    /// buffers and stops the stream, see [`OutputStream::drain`]
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Timeout in milliseconds for each wait, negative values block
+    fn drain_output(&mut self, timeout: i32) -> io::Result<()>
+    where
+        Self: for<'a> OutputStream<'a>,
+    {
+        if !self.state().active {
+            return Ok(());
+        }
+
+        // The buffer handed out by the last call to next() has been filled, but not queued yet.
+        let index = self.state().index;
+        OutputStream::queue(self, index)?;
+
+        let mut pending = 0;
+        for index in 0..self.buffer_count() {
+            if self
+                .query_flags(index)?
+                .intersects(Flags::QUEUED | Flags::DONE)
+            {
+                pending += 1;
+            }
+        }
+
+        for _ in 0..pending {
+            if self.handle_ref().poll(libc::POLLOUT, timeout)? == 0 {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "VIDIOC_DQBUF"));
+            }
+            OutputStream::dequeue(self)?;
+        }
+
+        self.stop()
+    }
+}
+
+/// Turns streaming on, retrying while the device is busy (see
+/// [`mmap::Stream::set_start_retries`])
+///
+/// # Arguments
+///
+/// * `handle` - Device handle
+/// * `buf_type` - Type of the buffers
+/// * `retries` - Number of retries
+pub(crate) fn stream_on(handle: &Handle, buf_type: Type, mut retries: u32) -> io::Result<()> {
+    loop {
+        let res = unsafe {
+            let mut typ = buf_type as u32;
+            v4l2::ioctl(
+                handle.fd(),
+                v4l2::vidioc::VIDIOC_STREAMON,
+                &mut typ as *mut _ as *mut std::os::raw::c_void,
+            )
+        };
+
+        match res {
+            // EBUSY may be transient if the previous stream is still being torn down
+            Err(e) if e.raw_os_error() == Some(libc::EBUSY) && retries > 0 => {
+                retries -= 1;
+                thread::sleep(Duration::from_millis(20));
+            }
+            res => return res,
+        }
+    }
+}
+
+/// Returns the frame interval of an output device, needed for underrun detection (see
+/// [`OutputStream::underruns`])
+///
+/// # Arguments
+///
+/// * `dev` - Output device
+pub(crate) fn output_interval(dev: &Device) -> Option<Duration> {
+    let frac = Output::params(dev)
+        .map(|params| params.interval)
+        .ok()
+        .filter(|frac| frac.numerator > 0 && frac.denominator > 0)?;
+    Some(Duration::from_nanos(
+        u64::from(frac.numerator) * 1_000_000_000 / u64::from(frac.denominator),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::fs::File;
+    use std::io::Write;
+    use std::os::unix::io::FromRawFd;
+
+    use super::*;
+
+    #[test]
+    fn poll_timeout_rounds_up() {
+        assert_eq!(poll_timeout(Duration::ZERO), 0);
+        assert_eq!(poll_timeout(Duration::from_nanos(1)), 1);
+        assert_eq!(poll_timeout(Duration::from_micros(1500)), 2);
+        assert_eq!(poll_timeout(Duration::from_millis(500)), 500);
+    }
+
+    #[test]
+    fn poll_timeout_clamps() {
+        assert_eq!(poll_timeout(Duration::from_secs(30 * 60)), 30 * 60 * 1000);
+        assert_eq!(
+            poll_timeout(Duration::from_secs(30 * 24 * 60 * 60)),
+            i32::MAX
+        );
+        assert_eq!(poll_timeout(Duration::MAX), i32::MAX);
+    }
+
+    /// Stream which hands out a counter as frame, with a payload shorter than the buffer
+    struct CountingStream {
+        buf: [u8; 4],
+        meta: Metadata,
+    }
+
+    impl Stream for CountingStream {
+        type Item = [u8];
+
+        fn start(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn stop(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> CaptureStream<'a> for CountingStream {
+        fn queue(&mut self, _index: usize) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn dequeue(&mut self) -> io::Result<usize> {
+            Ok(0)
+        }
+
+        fn next(&'a mut self) -> io::Result<(&Self::Item, &Metadata)> {
+            self.meta.sequence += 1;
+            self.buf[0] = self.meta.sequence as u8;
+            Ok((&self.buf, &self.meta))
+        }
+
+        fn next_latest(&'a mut self) -> io::Result<(&Self::Item, &Metadata)> {
+            self.next()
+        }
+    }
+
+    #[test]
+    fn save_frames_writes_payload() {
+        let dir = std::env::temp_dir().join(format!("v4l-save-frames-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut stream = CountingStream {
+            buf: [0; 4],
+            meta: Metadata {
+                bytesused: 2,
+                ..Metadata::default()
+            },
+        };
+        let res = save_frames_to_dir(&mut stream, &dir, 3, |meta| {
+            format!("frame-{}.raw", meta.sequence)
+        });
+        let frames: Vec<_> = (1..=3)
+            .map(|i| fs::read(dir.join(format!("frame-{}.raw", i))))
+            .collect();
+        fs::remove_dir_all(&dir).unwrap();
+
+        res.unwrap();
+        for (i, frame) in frames.into_iter().enumerate() {
+            assert_eq!(frame.unwrap(), vec![i as u8 + 1, 0]);
+        }
+    }
+
+    /// Queue which rejects buffers that are queued twice, like drivers do with `EINVAL`
+    struct FakeQueue {
+        // The read end of a pipe stands in for the device, it turns readable once a buffer is done
+        handle: Handle,
+        writer: File,
+        state: QueueState,
+        meta: Vec<Metadata>,
+        queued: Vec<bool>,
+        done: VecDeque<usize>,
+    }
+
+    impl FakeQueue {
+        fn new(count: usize) -> Self {
+            let mut fds = [0; 2];
+            assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+            FakeQueue {
+                handle: Handle::new(fds[0]),
+                writer: unsafe { File::from_raw_fd(fds[1]) },
+                state: QueueState::default(),
+                meta: vec![Metadata::default(); count],
+                queued: vec![false; count],
+                done: VecDeque::new(),
+            }
+        }
+
+        /// Lets the driver finish a queued buffer
+        fn complete(&mut self, index: usize) {
+            assert!(self.queued[index]);
+            self.done.push_back(index);
+            self.writer.write_all(&[0]).unwrap();
+        }
+    }
+
+    impl Stream for FakeQueue {
+        type Item = [u8];
+
+        fn start(&mut self) -> io::Result<()> {
+            self.state.active = true;
+            Ok(())
+        }
+
+        fn stop(&mut self) -> io::Result<()> {
+            self.queued.iter_mut().for_each(|queued| *queued = false);
+            self.done.clear();
+            self.state.active = false;
+            self.state.held = false;
+            Ok(())
+        }
+    }
+
+    impl Queue for FakeQueue {
+        fn state(&self) -> &QueueState {
+            &self.state
+        }
+
+        fn state_mut(&mut self) -> &mut QueueState {
+            &mut self.state
+        }
+
+        fn handle_ref(&self) -> &Handle {
+            &self.handle
+        }
+
+        fn buffer_count(&self) -> usize {
+            self.queued.len()
+        }
+
+        fn meta(&self, index: usize) -> &Metadata {
+            &self.meta[index]
+        }
+
+        fn query_flags(&self, index: usize) -> io::Result<Flags> {
+            Ok(if self.queued[index] {
+                Flags::QUEUED
+            } else {
+                Flags::empty()
+            })
+        }
+
+        fn queue_buffer(&mut self, index: usize) -> io::Result<()> {
+            if self.queued[index] {
+                return Err(io::Error::from_raw_os_error(libc::EINVAL));
+            }
+
+            self.queued[index] = true;
+            Ok(())
+        }
+
+        fn dequeue_ready(&mut self) -> io::Result<usize> {
+            let index = self.done.pop_front().ok_or(io::ErrorKind::WouldBlock)?;
+            self.queued[index] = false;
+            Ok(index)
+        }
+    }
+
+    #[test]
+    fn fetch_resumes_after_timeout() {
+        let mut queue = FakeQueue::new(3);
+        let err = queue.fetch(0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(queue.state.active);
+
+        queue.complete(0);
+        queue.fetch(0).unwrap();
+        assert_eq!(queue.state.index, 0);
+        assert!(queue.state.held);
+
+        // the buffer handed out before is queued once, no matter how often fetching fails
+        assert!(queue.fetch(0).is_err());
+        assert!(queue.fetch(0).is_err());
+        assert!(queue.queued[0]);
+        assert!(!queue.state.held);
+
+        queue.complete(1);
+        queue.fetch(0).unwrap();
+        assert_eq!(queue.state.index, 1);
+        assert!(queue.state.held);
+    }
+
+    #[test]
+    fn deferred_fetch_resumes_after_timeout() {
+        let mut queue = FakeQueue::new(3);
+        queue.state.requeue_policy = RequeuePolicy::Deferred;
+        queue.fetch(0).unwrap_err();
+        queue.complete(0);
+        queue.fetch(0).unwrap();
+
+        // the held buffer stays untouched until the next frame arrives
+        assert!(queue.fetch(0).is_err());
+        assert!(!queue.queued[0]);
+        assert!(queue.state.held);
+
+        queue.complete(1);
+        queue.fetch(0).unwrap();
+        assert_eq!(queue.state.index, 1);
+        assert!(queue.queued[0]);
+    }
+
+    #[test]
+    fn fetch_latest_drops_older_frames() {
+        let mut queue = FakeQueue::new(3);
+        queue.fetch(0).unwrap_err();
+        queue.complete(0);
+        queue.complete(1);
+        queue.complete(2);
+
+        queue.fetch_latest(0).unwrap();
+        assert_eq!(queue.state.index, 2);
+        assert_eq!(queue.queued, vec![true, true, false]);
+    }
+
+    #[test]
+    fn requeue_skips_held_buffer() {
+        let mut queue = FakeQueue::new(3);
+        queue.fetch(0).unwrap_err();
+        queue.complete(1);
+        queue.fetch(0).unwrap();
+
+        queue.requeue().unwrap();
+        assert_eq!(queue.queued, vec![true, false, true]);
+        assert!(queue.state.held);
+
+        // the next fetch hands the held buffer back
+        queue.complete(2);
+        queue.fetch(0).unwrap();
+        assert_eq!(queue.queued, vec![true, true, false]);
+    }
+}
@@ -0,0 +1,203 @@
+use std::io;
+use std::sync::Arc;
+
+use crate::buffer::{Metadata, Type};
+use crate::device::{Device, Handle};
+use crate::format::FourCC;
+use crate::io::mmap::Stream as MmapStream;
+use crate::io::traits::{CaptureStream, OutputStream, Stream as StreamTrait};
+use crate::video::{Capture, Output};
+
+/// Pipeline which feeds the frames of a camera into a mem2mem encoder
+///
+/// Raw frames are captured from the camera and copied into the output queue (raw side) of the
+/// encoder, the encoded frames are then dequeued from its capture queue (coded side). The
+/// encoder runs asynchronously: frames are fed as long as it has free raw buffers, and each call
+/// to [`CaptureEncode::next_encoded`] returns as soon as an encoded frame is ready.
+///
+/// Only single-planar buffer types are supported, which rules out encoders that are exclusively
+/// multi-planar.
+pub struct CaptureEncode<'a> {
+    // Fields are dropped in declaration order, so the coded queue of the encoder is stopped
+    // before its raw queue.
+    camera: MmapStream<'a>,
+    coded: MmapStream<'a>,
+    raw: MmapStream<'a>,
+    encoder: Arc<Handle>,
+    /// Raw buffers which are not queued, i.e. can be filled with the next camera frame
+    free: Vec<usize>,
+    /// Coded buffer returned by the last call to next_encoded(), queued again on the next call
+    pending: Option<usize>,
+    active: bool,
+}
+
+impl<'a> CaptureEncode<'a> {
+    /// Returns a pipeline from a camera to an encoder
+    ///
+    /// The current capture format of the camera is applied to the raw side of the encoder and the
+    /// coded side is set to the given format at the same resolution. Encoder parameters such as
+    /// the bitrate are controls, set them on the encoder device beforehand.
+    ///
+    /// # Arguments
+    ///
+    /// * `camera` - Capture device
+    /// * `encoder` - Mem2mem encoder device
+    /// * `fourcc` - Coded format, e.g. H264
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    /// use v4l::format::FourCC;
+    /// use v4l::io::pipeline::CaptureEncode;
+    ///
+    /// let camera = Device::new(0);
+    /// let encoder = Device::with_path("/dev/video11");
+    /// if let (Ok(camera), Ok(encoder)) = (camera, encoder) {
+    ///     if let Ok(mut pipeline) = CaptureEncode::new(&camera, &encoder, FourCC::new(b"H264")) {
+    ///         if let Ok((buf, meta)) = pipeline.next_encoded() {
+    ///             println!("encoded frame {}: {} bytes", meta.sequence, buf.len());
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn new(camera: &Device, encoder: &Device, fourcc: FourCC) -> io::Result<Self> {
+        CaptureEncode::with_buffers(camera, encoder, fourcc, 4)
+    }
+
+    /// Returns a pipeline from a camera to an encoder with a custom number of buffers
+    ///
+    /// # Arguments
+    ///
+    /// * `camera` - Capture device
+    /// * `encoder` - Mem2mem encoder device
+    /// * `fourcc` - Coded format, e.g. H264
+    /// * `buf_count` - Number of buffers to request for the camera and each encoder queue
+    pub fn with_buffers(
+        camera: &Device,
+        encoder: &Device,
+        fourcc: FourCC,
+        buf_count: u32,
+    ) -> io::Result<Self> {
+        let raw_fmt = Capture::format(camera)?;
+
+        // Stateful encoders expect the coded format to be set before the raw one
+        let mut coded_fmt = Capture::format(encoder)?;
+        coded_fmt.width = raw_fmt.width;
+        coded_fmt.height = raw_fmt.height;
+        coded_fmt.fourcc = fourcc;
+        if Capture::set_format(encoder, &coded_fmt)?.fourcc != fourcc {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("encoder does not support coded format {}", fourcc),
+            ));
+        }
+
+        let fmt = Output::set_format(encoder, &raw_fmt)?;
+        if fmt.width != raw_fmt.width
+            || fmt.height != raw_fmt.height
+            || fmt.fourcc != raw_fmt.fourcc
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("encoder does not accept camera format {}", raw_fmt),
+            ));
+        }
+
+        Ok(CaptureEncode {
+            camera: MmapStream::with_buffers(camera, Type::VideoCapture, buf_count)?,
+            coded: MmapStream::with_buffers(encoder, Type::VideoCapture, buf_count)?,
+            raw: MmapStream::with_buffers(encoder, Type::VideoOutput, buf_count)?,
+            encoder: encoder.handle(),
+            free: Vec::new(),
+            pending: None,
+            active: false,
+        })
+    }
+
+    /// Returns the next encoded frame
+    ///
+    /// Camera frames are fed into the encoder until it produced a frame. The returned slice only
+    /// covers the encoded bytes. Streaming is turned on for the camera and both encoder queues
+    /// on the first call.
+    pub fn next_encoded(&mut self) -> io::Result<(&[u8], &Metadata)> {
+        if !self.active {
+            self.start()?;
+        } else if let Some(index) = self.pending.take() {
+            CaptureStream::queue(&mut self.coded, index)?;
+        }
+
+        loop {
+            if self.encoder.poll(libc::POLLIN, 0)? > 0 {
+                break;
+            }
+
+            // Reclaim the raw buffers the encoder is done with
+            while self.free.len() < self.raw.allocated_buffers()
+                && self.encoder.poll(libc::POLLOUT, 0)? > 0
+            {
+                let index = OutputStream::dequeue(&mut self.raw)?;
+                self.free.push(index);
+            }
+
+            match self.free.pop() {
+                Some(index) => self.feed(index)?,
+                None => {
+                    // Wait until the encoder produced a frame or released a raw buffer
+                    self.encoder.poll(libc::POLLIN | libc::POLLOUT, -1)?;
+                }
+            }
+        }
+
+        let index = CaptureStream::dequeue(&mut self.coded)?;
+        self.pending = Some(index);
+
+        // The index used to access the buffer elements is given to us by v4l2, so we assume it
+        // will always be valid.
+        let meta = self.coded.get_meta(index).unwrap();
+        let buf = self.coded.get(index).unwrap();
+        let len = (meta.bytesused as usize).min(buf.len());
+        Ok((&buf[..len], meta))
+    }
+
+    /// Turns off streaming for the encoder, coded side first, and the camera
+    pub fn stop(&mut self) -> io::Result<()> {
+        self.coded.stop()?;
+        self.raw.stop()?;
+        self.camera.stop()?;
+
+        self.free.clear();
+        self.pending = None;
+        self.active = false;
+        Ok(())
+    }
+
+    fn start(&mut self) -> io::Result<()> {
+        // The raw queue of a mem2mem device has to be streaming before the coded one
+        self.raw.start()?;
+        for index in 0..self.coded.allocated_buffers() {
+            CaptureStream::queue(&mut self.coded, index)?;
+        }
+        self.coded.start()?;
+
+        self.free = (0..self.raw.allocated_buffers()).collect();
+        self.active = true;
+        Ok(())
+    }
+
+    /// Copies the next camera frame into a raw buffer and hands it to the encoder
+    fn feed(&mut self, index: usize) -> io::Result<()> {
+        let (frame, frame_meta) = CaptureStream::next(&mut self.camera)?;
+        let used = (frame_meta.bytesused as usize).min(frame.len());
+
+        let buf = self.raw.get_mut(index).unwrap();
+        let len = used.min(buf.len());
+        buf[..len].copy_from_slice(&frame[..len]);
+
+        let meta = self.raw.get_meta_mut(index).unwrap();
+        meta.bytesused = len as u32;
+        meta.field = frame_meta.field;
+
+        self.raw.submit(index)
+    }
+}
@@ -0,0 +1,128 @@
+use std::io;
+use std::os::unix::io::RawFd;
+
+use ::tokio::io::unix::AsyncFd;
+use ::tokio::io::Interest;
+
+use crate::buffer::{Metadata, Type};
+use crate::device::Device;
+use crate::io::mmap::Stream as MmapStream;
+use crate::io::Queue;
+
+/// Stream of mapped buffers for the tokio runtime
+///
+/// The buffers are handled by a memory mapped stream, but instead of blocking in `poll` until a
+/// frame is ready, the file descriptor is registered with the tokio reactor so the task yields
+/// while waiting. This allows serving many devices from a few threads.
+pub struct AsyncMmapStream<'a> {
+    // Deregistered from the reactor before the stream (and thus the device) goes away
+    fd: AsyncFd<RawFd>,
+    stream: MmapStream<'a>,
+}
+
+impl<'a> AsyncMmapStream<'a> {
+    /// Returns a stream for frame capturing
+    ///
+    /// Must be called from within a tokio runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `dev` - Capture device ref to get its file descriptor
+    /// * `buf_type` - Type of the buffers
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::tokio::AsyncMmapStream;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = AsyncMmapStream::new(&dev, Type::VideoCapture) {
+    ///         if let Ok((buf, meta)) = stream.next().await {
+    ///             println!("frame {}: {} bytes", meta.sequence, buf.len());
+    ///         }
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn new(dev: &Device, buf_type: Type) -> io::Result<Self> {
+        AsyncMmapStream::with_buffers(dev, buf_type, 4)
+    }
+
+    /// Returns a stream for frame capturing with a custom number of buffers
+    ///
+    /// Must be called from within a tokio runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `dev` - Capture device ref to get its file descriptor
+    /// * `buf_type` - Type of the buffers
+    /// * `buf_count` - Number of buffers to allocate
+    pub fn with_buffers(dev: &Device, buf_type: Type, buf_count: u32) -> io::Result<Self> {
+        let stream = MmapStream::with_buffers(dev, buf_type, buf_count)?;
+        Ok(AsyncMmapStream {
+            fd: AsyncFd::with_interest(stream.fd(), interest())?,
+            stream,
+        })
+    }
+
+    /// Returns the underlying stream, e.g. to access its buffers by index
+    pub fn stream(&self) -> &MmapStream<'a> {
+        &self.stream
+    }
+
+    /// Returns the underlying stream mutably, e.g. to skip errored buffers
+    /// ([`MmapStream::set_skip_errored`]), to drop warmup frames ([`MmapStream::set_warmup`]) or
+    /// to watch for source changes ([`MmapStream::watch_source_change`])
+    pub fn stream_mut(&mut self) -> &mut MmapStream<'a> {
+        &mut self.stream
+    }
+
+    /// Fetch a new frame by first queueing and then dequeueing
+    ///
+    /// Streaming is turned on and all buffers are queued on the first call. Afterwards, the
+    /// buffer returned by the previous call is handed back to the driver. Warmup, the requeue
+    /// policy, source changes and errored buffers are handled like
+    /// [`crate::io::traits::CaptureStream::next`] does, according to the settings of the
+    /// underlying stream (see [`AsyncMmapStream::stream_mut`]).
+    pub async fn next(&mut self) -> io::Result<(&[u8], &Metadata)> {
+        let stream = &mut self.stream;
+        // The first attempt turns streaming on, later ones wait for the driver to finish a buffer
+        if !stream.try_fetch()? {
+            loop {
+                let mut guard = self.fd.ready(interest()).await?;
+                // Readiness may be stale, in which case nothing is dequeued and the readiness is
+                // cleared before waiting again. The stream picks up where it stopped.
+                let res = guard.try_io(|_| match stream.try_fetch() {
+                    Ok(true) => Ok(()),
+                    Ok(false) => Err(io::ErrorKind::WouldBlock.into()),
+                    Err(e) => Err(e),
+                });
+                match res {
+                    Ok(res) => break res?,
+                    Err(_would_block) => continue,
+                }
+            }
+        }
+
+        // The index used to access the buffer elements is given to us by v4l2, so we assume it
+        // will always be valid.
+        let index = self.stream.state().index;
+        let bytes = self.stream.get(index).unwrap();
+        let meta = self.stream.get_meta(index).unwrap();
+        Ok((bytes, meta))
+    }
+}
+
+/// Returns the readiness to wait for: frames signal `POLLIN`, events such as source changes
+/// signal `POLLPRI`
+fn interest() -> Interest {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    return Interest::READABLE | Interest::PRIORITY;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    return Interest::READABLE;
+}
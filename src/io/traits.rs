@@ -1,4 +1,5 @@
 use std::io;
+use std::os::unix::io::AsRawFd;
 
 use crate::buffer::Metadata;
 
@@ -23,6 +24,267 @@ pub trait CaptureStream<'a>: Stream {
     /// Fetch a new frame by first queueing and then dequeueing.
     /// First time initialization is performed if necessary.
     fn next(&'a mut self) -> io::Result<(&Self::Item, &Metadata)>;
+
+    /// Fetch a new frame if one is ready, without waiting for it.
+    ///
+    /// Returns `Ok(None)` if the driver has not finished any buffer yet. The default
+    /// implementation polls the file descriptor once and then calls [`CaptureStream::next`],
+    /// which may still wait, e.g. to start the stream on the first call. The streams of this
+    /// crate never wait: warmup frames, skipped errored buffers and the stream start are
+    /// handled across several calls instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    /// use v4l::io::traits::CaptureStream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         match stream.try_next() {
+    ///             Ok(Some((buf, meta))) => println!("frame {}: {} bytes", meta.sequence, buf.len()),
+    ///             Ok(None) => println!("no frame ready"),
+    ///             Err(e) => println!("error: {}", e),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn try_next(&'a mut self) -> io::Result<Option<(&Self::Item, &Metadata)>>
+    where
+        Self: AsRawFd,
+    {
+        let mut fds = [libc::pollfd {
+            fd: self.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        }];
+        if unsafe { libc::poll(fds.as_mut_ptr(), 1, 0) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        // Streams which are not started yet report POLLERR, next() takes care of them
+        if fds[0].revents == 0 {
+            return Ok(None);
+        }
+
+        self.next().map(Some)
+    }
+
+    /// Fetch the most recent frame, dropping all frames which were captured before it.
+    ///
+    /// All buffers the driver has finished are dequeued without waiting and the stale ones are
+    /// queued again right away, so only the newest frame is returned. If no frame is ready, this
+    /// waits for the next one like [`CaptureStream::next`] does. This is useful for slow
+    /// consumers (e.g. analysis) which only care about the current state of the scene rather
+    /// than every single frame; the dropped frames can be recognized by gaps in
+    /// [`Metadata::sequence`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    /// use v4l::io::traits::CaptureStream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         for _ in 0..10 {
+    ///             if let Ok((buf, meta)) = stream.next_latest() {
+    ///                 // slow processing, frames queue up in the meantime
+    ///                 println!("frame {}: {} bytes", meta.sequence, buf.len());
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn next_latest(&'a mut self) -> io::Result<(&Self::Item, &Metadata)>;
+
+    /// Discard all frames in flight without tearing down the stream.
+    ///
+    /// Streaming is turned off, which makes the driver drop all frames it captured so far, and
+    /// turned on again with the buffers queued. The buffers stay allocated, which makes this
+    /// much cheaper than creating a new stream, e.g. after seeking or reconfiguring a pipeline.
+    /// The buffer returned by the last call to [`CaptureStream::next`] remains valid until the
+    /// next call, which hands it back as usual. Does nothing if the stream is not active.
+    ///
+    /// Streams which cannot flush return an error of kind [`io::ErrorKind::Unsupported`] and
+    /// are left untouched.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    /// use v4l::io::traits::CaptureStream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         if stream.next().is_ok() {
+    ///             // e.g. the scene changed, frames captured up to now are stale
+    ///             stream.flush().unwrap();
+    ///             let (buf, meta) = stream.next().unwrap();
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn flush(&mut self) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "flushing is not supported by this stream",
+        ))
+    }
+
+    /// Turn streaming off and on again, with all buffers queued.
+    ///
+    /// Unlike [`CaptureStream::flush`], this also resumes a stream which was stopped through
+    /// [`Stream::stop`], so a long running service can pause capturing (e.g. while no clients
+    /// are connected) without freeing the buffers. Frames captured before are discarded. The
+    /// buffer returned by the last call to [`CaptureStream::next`] remains valid until the
+    /// next call, which hands it back as usual. Warmup frames are not dropped again.
+    ///
+    /// Streams which cannot restart return an error of kind [`io::ErrorKind::Unsupported`] and
+    /// are left untouched.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    /// use v4l::io::traits::{CaptureStream, Stream as _};
+    ///
+    /// // e.g. a v4l2loopback device fed by another process
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         if let Ok((_, meta)) = stream.next() {
+    ///             let before = meta.sequence;
+    ///
+    ///             // pause
+    ///             stream.stop().unwrap();
+    ///
+    ///             // resume
+    ///             stream.restart().unwrap();
+    ///             let (buf, meta) = stream.next().unwrap();
+    ///             assert!(!buf.is_empty());
+    ///             println!("frame {} after {}", meta.sequence, before);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn restart(&mut self) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "restarting is not supported by this stream",
+        ))
+    }
+
+    /// Fetch a new frame and copy it into a caller provided buffer.
+    ///
+    /// Returns the number of bytes copied along with the metadata of the frame. If `dst` cannot
+    /// hold all bytes of the frame, an error of kind [`io::ErrorKind::InvalidInput`] is returned
+    /// unless `truncate` is set, in which case only the first `dst.len()` bytes are copied.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    /// use v4l::io::traits::CaptureStream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         let mut frame = vec![0u8; 1920 * 1080 * 2];
+    ///         if let Ok((len, meta)) = stream.read_into(&mut frame, false) {
+    ///             println!("Copied {} bytes of frame {}", len, meta.sequence);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn read_into(&'a mut self, dst: &mut [u8], truncate: bool) -> io::Result<(usize, Metadata)>
+    where
+        Self: Stream<Item = [u8]>,
+    {
+        let (buf, meta) = self.next()?;
+        let used = (meta.bytesused as usize).min(buf.len());
+        if used > dst.len() && !truncate {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "destination of {} bytes cannot hold frame of {} bytes",
+                    dst.len(),
+                    used
+                ),
+            ));
+        }
+
+        let len = used.min(dst.len());
+        dst[..len].copy_from_slice(&buf[..len]);
+        Ok((len, *meta))
+    }
+
+    /// Returns an iterator which yields a copy of each frame.
+    ///
+    /// Every iteration fetches a frame like [`CaptureStream::next`] does and copies its
+    /// `bytesused` bytes into a newly allocated vector. This is convenient, but costs an
+    /// allocation and a copy of the whole image per frame; use [`CaptureStream::next`] to access
+    /// the buffers in place instead. The iterator never ends on its own, errors are yielded as
+    /// they occur.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::mmap::Stream;
+    /// use v4l::io::traits::CaptureStream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         for frame in stream.frames().take(10) {
+    ///             match frame {
+    ///                 Ok((buf, meta)) => println!("frame {}: {} bytes", meta.sequence, buf.len()),
+    ///                 Err(e) => println!("error: {}", e),
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn frames(&mut self) -> Frames<'_, Self>
+    where
+        Self: Sized,
+    {
+        Frames { stream: self }
+    }
+}
+
+/// Iterator over copies of the frames of a capture stream, see [`CaptureStream::frames`]
+pub struct Frames<'s, S> {
+    stream: &'s mut S,
+}
+
+impl<'s, S> Iterator for Frames<'s, S>
+where
+    S: for<'b> CaptureStream<'b> + Stream<Item = [u8]>,
+{
+    type Item = io::Result<(Vec<u8>, Metadata)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let frame = CaptureStream::next(&mut *self.stream).map(|(buf, meta)| {
+            let used = (meta.bytesused as usize).min(buf.len());
+            (buf[..used].to_vec(), *meta)
+        });
+        Some(frame)
+    }
 }
 
 pub trait OutputStream<'a>: Stream {
@@ -35,4 +297,33 @@ pub trait OutputStream<'a>: Stream {
     /// Dump a new frame by first queueing and then dequeueing.
     /// First time initialization is performed if necessary.
     fn next(&'a mut self) -> io::Result<(&mut Self::Item, &mut Metadata)>;
+
+    /// Hand the last frame to the driver, wait until all frames were processed and stop streaming.
+    ///
+    /// The buffer returned by the most recent call to [`OutputStream::next`] is queued first,
+    /// since it is assumed to have been filled at the call site. [`Stream::stop`] on the other
+    /// hand issues `VIDIOC_STREAMOFF` right away, which makes the driver discard all buffers
+    /// that are queued but not processed yet, so the sink may never see the last frames.
+    /// Does nothing if the stream is not active.
+    ///
+    /// Streams which cannot drain return an error of kind [`io::ErrorKind::Unsupported`] and
+    /// are left untouched.
+    fn drain(&mut self) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "draining is not supported by this stream",
+        ))
+    }
+
+    /// Returns the number of buffer underruns since the stream was created.
+    ///
+    /// An underrun is counted whenever queueing a buffer had to wait longer than one frame
+    /// interval (as configured on the device) for the driver to release a buffer (`POLLOUT`).
+    /// The pipeline did not keep up with the frame rate in that case, so the sink most likely
+    /// repeated a frame. Nothing is counted if the device does not report a frame interval.
+    ///
+    /// Streams which do not track underruns always return zero.
+    fn underruns(&self) -> u64 {
+        0
+    }
 }
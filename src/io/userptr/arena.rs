@@ -1,18 +1,26 @@
+use std::convert::TryInto;
 use std::{io, mem, sync::Arc};
 
 use crate::buffer;
 use crate::device::Handle;
-use crate::memory::Memory;
+use crate::memory::{self, Memory};
 use crate::v4l2;
 use crate::v4l_sys::*;
 
 /// Manage user allocated buffers
 ///
+/// Multi-planar buffers consist of one allocation per plane, each sized to hold the plane as
+/// described by the active format.
+///
 /// All buffers are released in the Drop impl.
 pub struct Arena {
     handle: Arc<Handle>,
-    pub bufs: Vec<Vec<u8>>,
+    /// Allocations of each buffer, one per plane
+    bufs: Vec<Vec<Vec<u8>>>,
     pub buf_type: buffer::Type,
+    /// Size of each plane
+    plane_sizes: Vec<usize>,
+    buf_align: usize,
 }
 
 impl Arena {
@@ -30,9 +38,114 @@ impl Arena {
             handle,
             bufs: Vec::new(),
             buf_type,
+            plane_sizes: Vec::new(),
+            // Some drivers refuse user pointers which are not page aligned.
+            buf_align: page_size(),
         }
     }
 
+    /// Returns the number of buffers
+    pub fn len(&self) -> usize {
+        self.bufs.len()
+    }
+
+    /// Returns whether there are no buffers
+    pub fn is_empty(&self) -> bool {
+        self.bufs.is_empty()
+    }
+
+    /// Returns the (aligned) memory region of a buffer or `None` if the index is out of bounds
+    ///
+    /// For multi-planar buffers, this is the first plane. Use [`Arena::planes`] to access all
+    /// of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        self.bufs
+            .get(index)
+            .map(|buf| Self::region(&buf[0], self.plane_sizes[0], self.buf_align))
+    }
+
+    /// Returns the (aligned) memory region of a buffer or `None` if the index is out of bounds
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut [u8]> {
+        let (sizes, align) = (&self.plane_sizes, self.buf_align);
+        self.bufs
+            .get_mut(index)
+            .map(|buf| Self::region_mut(&mut buf[0], sizes[0], align))
+    }
+
+    /// Returns the (aligned) memory region of a buffer without bounds checking
+    ///
+    /// # Safety
+    ///
+    /// The index must be less than [`Arena::len`].
+    pub unsafe fn get_unchecked(&self, index: usize) -> &[u8] {
+        let buf = self.bufs.get_unchecked(index);
+        Self::region(&buf[0], self.plane_sizes[0], self.buf_align)
+    }
+
+    /// Returns the (aligned) memory region of a buffer without bounds checking
+    ///
+    /// # Safety
+    ///
+    /// The index must be less than [`Arena::len`].
+    pub unsafe fn get_unchecked_mut(&mut self, index: usize) -> &mut [u8] {
+        let (size, align) = (self.plane_sizes[0], self.buf_align);
+        Self::region_mut(&mut self.bufs.get_unchecked_mut(index)[0], size, align)
+    }
+
+    /// Returns the (aligned) planes of a buffer or `None` if the index is out of bounds
+    ///
+    /// Single-planar buffers consist of exactly one plane.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer
+    pub fn planes(&self, index: usize) -> Option<Vec<&[u8]>> {
+        self.bufs.get(index).map(|buf| {
+            buf.iter()
+                .zip(&self.plane_sizes)
+                .map(|(plane, &size)| Self::region(plane, size, self.buf_align))
+                .collect()
+        })
+    }
+
+    /// Returns the (aligned) planes of a buffer or `None` if the index is out of bounds
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer
+    pub fn planes_mut(&mut self, index: usize) -> Option<Vec<&mut [u8]>> {
+        let (sizes, align) = (&self.plane_sizes, self.buf_align);
+        self.bufs.get_mut(index).map(|buf| {
+            buf.iter_mut()
+                .zip(sizes)
+                .map(|(plane, &size)| Self::region_mut(plane, size, align))
+                .collect()
+        })
+    }
+
+    /// Returns the number of planes per buffer
+    pub fn num_planes(&self) -> usize {
+        self.plane_sizes.len().max(1)
+    }
+
+    fn region(buf: &[u8], size: usize, align: usize) -> &[u8] {
+        let offset = buf.as_ptr().align_offset(align);
+        &buf[offset..offset + size]
+    }
+
+    fn region_mut(buf: &mut [u8], size: usize, align: usize) -> &mut [u8] {
+        let offset = buf.as_ptr().align_offset(align);
+        &mut buf[offset..offset + size]
+    }
+
     fn requestbuffers_desc(&self) -> v4l2_requestbuffers {
         v4l2_requestbuffers {
             type_: self.buf_type as u32,
@@ -41,8 +154,8 @@ impl Arena {
         }
     }
 
-    pub fn allocate(&mut self, count: u32) -> io::Result<u32> {
-        // we need to get the maximum buffer size from the format first
+    /// Returns the plane sizes required by the active format
+    fn format_sizes(&self) -> io::Result<Vec<usize>> {
         let mut v4l2_fmt = v4l2_format {
             type_: self.buf_type as u32,
             ..unsafe { mem::zeroed() }
@@ -52,9 +165,29 @@ impl Arena {
                 self.handle.fd(),
                 v4l2::vidioc::VIDIOC_G_FMT,
                 &mut v4l2_fmt as *mut _ as *mut std::os::raw::c_void,
-            )?;
+            )
+            .map_err(|e| buffer::UnsupportedBufferType::map_format_error(self.buf_type, e))?;
+        }
+
+        let sizes: Vec<usize> = if self.buf_type.is_mplane() {
+            plane_sizes(unsafe { &v4l2_fmt.fmt.pix_mp })
+        } else {
+            vec![unsafe { v4l2_fmt.fmt.pix.sizeimage } as usize]
+        };
+
+        // Without a negotiated format, there is no way to know how large the buffers must be.
+        if sizes.is_empty() || sizes.contains(&0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "no format set (set a format before creating a stream)",
+            ));
         }
 
+        Ok(sizes)
+    }
+
+    /// Requests buffer slots from the driver, returns the number of slots it granted
+    fn request(&self, count: u32) -> io::Result<u32> {
         #[cfg(feature = "v4l-sys")]
         eprintln!(
             "\n### WARNING ###\n\
@@ -71,19 +204,96 @@ impl Arena {
                 self.handle.fd(),
                 v4l2::vidioc::VIDIOC_REQBUFS,
                 &mut v4l2_reqbufs as *mut _ as *mut std::os::raw::c_void,
-            )?;
+            )
+            // Some drivers reject buffer allocation until a format has been negotiated.
+            .map_err(memory::map_request_error)?;
         }
+        memory::verify_request(
+            Memory::UserPtr,
+            v4l2_reqbufs.memory,
+            v4l2_reqbufs.capabilities.into(),
+        )?;
 
-        // allocate the new user buffers
-        self.bufs.resize(v4l2_reqbufs.count as usize, Vec::new());
-        for i in 0..v4l2_reqbufs.count {
-            let buf = &mut self.bufs[i as usize];
-            unsafe {
-                buf.resize(v4l2_fmt.fmt.pix.sizeimage as usize, 0);
-            }
+        Ok(v4l2_reqbufs.count)
+    }
+
+    pub fn allocate(&mut self, count: u32) -> io::Result<u32> {
+        memory::verify_count(count)?;
+        // we need to get the maximum plane sizes from the format first
+        let sizes = self.format_sizes()?;
+        let requested = count;
+        let count = self.request(count)?;
+        memory::verify_granted(requested, count)?;
+
+        // allocate the new user buffers, with some slack so they can be aligned
+        self.bufs = (0..count)
+            .map(|_| {
+                sizes
+                    .iter()
+                    .map(|&size| allocate_plane(size, self.buf_align))
+                    .collect()
+            })
+            .collect();
+        self.plane_sizes = sizes;
+
+        Ok(count)
+    }
+
+    /// Uses caller provided buffers instead of allocating new ones
+    ///
+    /// The buffers are used as they are, i.e. they are not aligned by the arena. Surplus buffers
+    /// are dropped if the driver grants fewer slots than buffers were provided. Only formats
+    /// with a single plane are supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `bufs` - Buffers, each must be able to hold a frame of the active format
+    pub fn allocate_from(&mut self, mut bufs: Vec<Vec<u8>>) -> io::Result<u32> {
+        memory::verify_count(bufs.len().try_into().unwrap_or(u32::MAX))?;
+        let sizes = self.format_sizes()?;
+        if sizes.len() > 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "format has {} planes, but caller provided buffers are single-planar",
+                    sizes.len()
+                ),
+            ));
         }
 
-        Ok(v4l2_reqbufs.count)
+        let size = sizes[0];
+        if let Some(buf) = bufs.iter().find(|buf| buf.len() < size) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "buffer of {} bytes cannot hold a frame of {} bytes",
+                    buf.len(),
+                    size
+                ),
+            ));
+        }
+
+        let count = self.request(bufs.len() as u32)?;
+        memory::verify_granted(bufs.len() as u32, count)?;
+        if count as usize > bufs.len() {
+            // the driver needs more buffers than we have, give the slots back
+            let provided = bufs.len();
+            self.release()?;
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "driver requires {} buffers, but only {} were provided",
+                    count, provided
+                ),
+            ));
+        }
+
+        bufs.truncate(count as usize);
+        self.plane_sizes = sizes;
+        self.buf_align = 1;
+        self.bufs = bufs.into_iter().map(|buf| vec![buf]).collect();
+
+        Ok(count)
     }
 
     pub fn release(&mut self) -> io::Result<()> {
@@ -104,7 +314,7 @@ impl Arena {
 
 impl Drop for Arena {
     fn drop(&mut self) {
-        if self.bufs.is_empty() {
+        if self.is_empty() {
             // nothing to do
             return;
         }
@@ -124,3 +334,80 @@ impl Drop for Arena {
         }
     }
 }
+
+/// Returns the size of each plane of a multi-planar format
+///
+/// # Arguments
+///
+/// * `pix_mp` - Multi-planar format as reported by the driver
+fn plane_sizes(pix_mp: &v4l2_pix_format_mplane) -> Vec<usize> {
+    let num_planes = (pix_mp.num_planes as usize).min(pix_mp.plane_fmt.len());
+    pix_mp.plane_fmt[..num_planes]
+        .iter()
+        .map(|plane| plane.sizeimage as usize)
+        .collect()
+}
+
+/// Returns the page size of the system, or one if it cannot be determined
+fn page_size() -> usize {
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if page_size > 0 {
+        page_size as usize
+    } else {
+        1
+    }
+}
+
+/// Allocates a plane with enough slack to align its memory region, see [`Arena::get`]
+///
+/// # Arguments
+///
+/// * `size` - Size of the plane
+/// * `align` - Alignment of the plane
+fn allocate_plane(size: usize, align: usize) -> Vec<u8> {
+    vec![0; size + align - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_aligned_planes() {
+        let align = page_size();
+        assert!(align.is_power_of_two());
+
+        for &size in &[1, 1000, align, 640 * 480 * 2] {
+            let mut buf = allocate_plane(size, align);
+
+            let region = Arena::region(&buf, size, align);
+            assert_eq!(region.as_ptr() as usize % align, 0);
+            assert_eq!(region.len(), size);
+
+            let region = Arena::region_mut(&mut buf, size, align);
+            assert_eq!(region.as_ptr() as usize % align, 0);
+            assert_eq!(region.len(), size);
+        }
+    }
+
+    #[test]
+    fn mplane_sizes() {
+        // NV12M, luma and chroma in separate planes
+        let mut pix_mp: v4l2_pix_format_mplane = unsafe { mem::zeroed() };
+        pix_mp.num_planes = 2;
+        pix_mp.plane_fmt[0].sizeimage = 640 * 480;
+        pix_mp.plane_fmt[1].sizeimage = 640 * 240;
+        assert_eq!(plane_sizes(&pix_mp), vec![640 * 480, 640 * 240]);
+
+        // bogus plane counts are clamped
+        pix_mp.num_planes = 255;
+        assert_eq!(plane_sizes(&pix_mp).len(), VIDEO_MAX_PLANES as usize);
+    }
+
+    #[test]
+    fn unaligned_planes() {
+        // caller provided buffers are used as they are
+        let buf = vec![0; 1000];
+        assert_eq!(Arena::region(&buf, 1000, 1).as_ptr(), buf.as_ptr());
+    }
+}
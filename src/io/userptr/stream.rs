@@ -1,27 +1,39 @@
-use std::convert::TryInto;
-use std::time::Duration;
-use std::{io, mem, sync::Arc};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use std::{fmt, io, mem, sync::Arc};
 
-use crate::buffer::{Metadata, Type};
+use crate::buffer::{Flags, Metadata, Type};
 use crate::device::{Device, Handle};
-use crate::io::traits::{CaptureStream, Stream as StreamTrait};
+use crate::io::traits::{CaptureStream, OutputStream, Stream as StreamTrait};
 use crate::io::userptr::arena::Arena;
+use crate::io::{
+    output_interval, poll_timeout, save_frames_to_dir, stream_on, Queue, QueueState, RequeuePolicy,
+    StreamStats,
+};
 use crate::memory::Memory;
 use crate::v4l2;
 use crate::v4l_sys::*;
 
+type Planes = [v4l2_plane; VIDEO_MAX_PLANES as usize];
+
 /// Stream of user buffers
 ///
 /// An arena instance is used internally for buffer handling.
 pub struct Stream {
     handle: Arc<Handle>,
     arena: Arena,
-    arena_index: usize,
     buf_type: Type,
     buf_meta: Vec<Metadata>,
+    /// Number of bytes used in each plane of the buffers, see [`Stream::plane_bytesused`]
+    plane_bytesused: Vec<Vec<u32>>,
     timeout: Option<i32>,
+    stats: StreamStats,
+    last_sequence: Option<u32>,
+    frame_interval: Option<Duration>,
+    underruns: u64,
 
-    active: bool,
+    state: QueueState,
 }
 
 impl Stream {
@@ -48,6 +60,53 @@ impl Stream {
         Stream::with_buffers(dev, buf_type, 4)
     }
 
+    /// Returns a stream for frame capturing with a custom number of buffers
+    ///
+    /// The buffers are page aligned because some drivers refuse user pointers which are not.
+    /// For multi-planar buffer types (e.g. [`Type::VideoOutputMplane`]), each plane gets its own
+    /// allocation, sized as reported by the active format.
+    ///
+    /// Some drivers refuse to allocate buffers before a format has been negotiated. In that case,
+    /// an error of kind [`io::ErrorKind::InvalidInput`] is returned and you should call
+    /// `set_format` on the device before trying again.
+    ///
+    /// # Arguments
+    ///
+    /// * `dev` - Device ref to get its file descriptor
+    /// * `buf_type` - Type of the buffers
+    /// * `buf_count` - Number of buffers to request, at most 32 (`VIDEO_MAX_FRAME`)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io;
+    ///
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::traits::CaptureStream;
+    /// use v4l::io::userptr::Stream;
+    /// use v4l::video::Capture;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     let stream = match Stream::with_buffers(&dev, Type::VideoCapture, 4) {
+    ///         Err(e) if e.kind() == io::ErrorKind::InvalidInput => {
+    ///             // negotiate a format first, then retry
+    ///             let fmt = dev.format().unwrap();
+    ///             dev.set_format(&fmt).unwrap();
+    ///             Stream::with_buffers(&dev, Type::VideoCapture, 4)
+    ///         }
+    ///         res => res,
+    ///     };
+    ///
+    ///     if let Ok(mut stream) = stream {
+    ///         if let Ok((buf, _)) = stream.next() {
+    ///             let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    ///             assert_eq!(buf.as_ptr() as usize % page_size, 0);
+    ///         }
+    ///     }
+    /// }
+    /// ```
     pub fn with_buffers(dev: &Device, buf_type: Type, buf_count: u32) -> io::Result<Self> {
         let mut arena = Arena::new(dev.handle(), buf_type);
         let count = arena.allocate(buf_count)?;
@@ -57,11 +116,66 @@ impl Stream {
         Ok(Stream {
             handle: dev.handle(),
             arena,
-            arena_index: 0,
             buf_type,
             buf_meta,
-            active: false,
+            plane_bytesused: vec![Vec::new(); count as usize],
             timeout: None,
+            stats: StreamStats::default(),
+            last_sequence: None,
+            frame_interval: None,
+            underruns: 0,
+            state: QueueState::default(),
+        })
+    }
+
+    /// Returns a stream for frame capturing which uses the given buffers
+    ///
+    /// This is useful to integrate the stream with existing memory management (e.g. a pool of
+    /// buffers which is reused across streams). Each buffer must be able to hold a frame of the
+    /// active format, otherwise an error of kind [`io::ErrorKind::InvalidInput`] is returned.
+    /// The buffers are used as they are, so keep in mind that some drivers require them to be
+    /// page aligned. Formats with more than one plane are not supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `dev` - Device ref to get its file descriptor
+    /// * `buf_type` - Type of the buffers
+    /// * `bufs` - Buffers to capture into
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::userptr::Stream;
+    /// use v4l::video::Capture;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(fmt) = dev.format() {
+    ///         let bufs = vec![vec![0u8; fmt.size as usize]; 4];
+    ///         let stream = Stream::with_buffers_from(&dev, Type::VideoCapture, bufs);
+    ///     }
+    /// }
+    /// ```
+    pub fn with_buffers_from(dev: &Device, buf_type: Type, bufs: Vec<Vec<u8>>) -> io::Result<Self> {
+        let mut arena = Arena::new(dev.handle(), buf_type);
+        let count = arena.allocate_from(bufs)?;
+        let mut buf_meta = Vec::new();
+        buf_meta.resize(count as usize, Metadata::default());
+
+        Ok(Stream {
+            handle: dev.handle(),
+            arena,
+            buf_type,
+            buf_meta,
+            plane_bytesused: vec![Vec::new(); count as usize],
+            timeout: None,
+            stats: StreamStats::default(),
+            last_sequence: None,
+            frame_interval: None,
+            underruns: 0,
+            state: QueueState::default(),
         })
     }
 
@@ -70,9 +184,248 @@ impl Stream {
         self.handle.clone()
     }
 
+    /// Returns the number of buffers granted by the driver
+    ///
+    /// This may differ from the count passed to [`Stream::with_buffers`], since drivers are free
+    /// to allocate fewer or more buffers.
+    pub fn allocated_buffers(&self) -> usize {
+        self.arena.len()
+    }
+
+    /// Returns the file descriptor of the device
+    ///
+    /// See [`crate::io::mmap::Stream::fd`] for how to use it with an external event loop.
+    pub fn fd(&self) -> RawFd {
+        self.handle.fd()
+    }
+
+    /// Returns a buffer or `None` if the index is out of bounds
+    ///
+    /// Together with [`CaptureStream::dequeue`], this allows processing a frame after dequeueing
+    /// it, e.g. to hold on to several frames at once. The contents are only stable while the
+    /// buffer is not queued.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer, as returned by [`CaptureStream::dequeue`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::userptr::Stream;
+    /// use v4l::io::traits::{CaptureStream, Stream as _};
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         CaptureStream::queue(&mut stream, 0).unwrap();
+    ///         stream.start().unwrap();
+    ///         let index = CaptureStream::dequeue(&mut stream).unwrap();
+    ///         let buf = stream.get(index).unwrap();
+    ///         let meta = stream.get_meta(index).unwrap();
+    ///         println!("frame {}: {} bytes", meta.sequence, buf.len());
+    ///     }
+    /// }
+    /// ```
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        self.arena.get(index)
+    }
+
+    /// Returns the planes of a buffer or `None` if the index is out of bounds
+    ///
+    /// Streams of multi-planar buffer types hand out the first plane of a buffer, this returns
+    /// all of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer, as returned by [`CaptureStream::dequeue`]
+    pub fn planes(&self, index: usize) -> Option<Vec<&[u8]>> {
+        self.arena.planes(index)
+    }
+
+    /// Returns the number of bytes used in each plane of a buffer or `None` if the index is out
+    /// of bounds
+    ///
+    /// [`Metadata::bytesused`] only covers the first plane, i.e. the slice returned by
+    /// [`Stream::get`]. See [`crate::io::mmap::Stream::plane_bytesused`].
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer, as returned by [`CaptureStream::dequeue`]
+    pub fn plane_bytesused(&self, index: usize) -> Option<&[u32]> {
+        self.plane_bytesused.get(index).map(|used| used.as_slice())
+    }
+
+    /// Returns the planes of a buffer for writing or `None` if the index is out of bounds
+    ///
+    /// This is how frames are handed to multi-planar output devices such as hardware encoders:
+    /// fill each plane, then queue the buffer. The contents must not be modified while the
+    /// buffer is queued.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::traits::OutputStream;
+    /// use v4l::io::userptr::Stream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoOutputMplane) {
+    ///         // one allocation per plane, e.g. two for NV12M
+    ///         let planes = stream.planes_mut(0).unwrap();
+    ///         assert!(!planes.is_empty());
+    ///         for plane in planes {
+    ///             assert!(!plane.is_empty());
+    ///             plane.fill(0x80);
+    ///         }
+    ///         OutputStream::queue(&mut stream, 0).unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub fn planes_mut(&mut self, index: usize) -> Option<Vec<&mut [u8]>> {
+        self.arena.planes_mut(index)
+    }
+
+    /// Returns the counters of dequeued, errored and skipped frames
+    ///
+    /// See [`crate::io::mmap::Stream::stats`].
+    pub fn stats(&self) -> StreamStats {
+        self.stats
+    }
+
+    /// Returns the metadata of a buffer or `None` if the index is out of bounds
+    ///
+    /// The metadata is updated whenever the buffer is dequeued.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the buffer, as returned by [`CaptureStream::dequeue`]
+    pub fn get_meta(&self, index: usize) -> Option<&Metadata> {
+        self.buf_meta.get(index)
+    }
+
+    /// Stops the stream and returns all buffers to the application
+    ///
+    /// Call this when switching between [`CaptureStream::next`] and manual queueing. See
+    /// [`crate::io::mmap::Stream::reset`] for the supported usage patterns.
+    pub fn reset(&mut self) -> io::Result<()> {
+        // VIDIOC_STREAMOFF also releases buffers which were queued without streaming
+        self.stop()?;
+        self.state.index = 0;
+        Ok(())
+    }
+
+    /// Returns a device which shares the handle of this stream
+    ///
+    /// Many controls such as exposure or gain may be changed while streaming. The returned device
+    /// can be used to do that, even from another thread, without having to keep the original
+    /// device around. Note that drivers may reject changes which affect the buffer layout (e.g.
+    /// rotation or format) with `EBUSY` while the stream is active.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::control::{Control, Value};
+    /// use v4l::device::Device;
+    /// use v4l::io::userptr::Stream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         let ctrl = Control {
+    ///             id: v4l::v4l_sys::V4L2_CID_BRIGHTNESS,
+    ///             value: Value::Integer(128),
+    ///         };
+    ///         let res = stream.device().set_control(ctrl);
+    ///     }
+    /// }
+    /// ```
+    pub fn device(&self) -> Device {
+        Device::from(self.handle.clone())
+    }
+
+    /// Captures frames and writes each of them to a file in the given directory
+    ///
+    /// See [`crate::io::mmap::Stream::save_frames_to_dir`] for the layout of the files.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory to write the files to, must exist
+    /// * `count` - Number of frames to capture
+    /// * `namer` - Returns the file name for a frame, e.g. based on its sequence number
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::userptr::Stream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         let res = stream.save_frames_to_dir(Path::new("/tmp"), 10, |meta| {
+    ///             format!("frame-{:06}.raw", meta.sequence)
+    ///         });
+    ///     }
+    /// }
+    /// ```
+    pub fn save_frames_to_dir(
+        &mut self,
+        dir: &Path,
+        count: usize,
+        namer: impl Fn(&Metadata) -> String,
+    ) -> io::Result<()> {
+        save_frames_to_dir(self, dir, count, namer)
+    }
+
     /// Sets a timeout of the v4l file handle.
+    ///
+    /// If no buffer becomes ready within the given duration, dequeueing fails with an error of
+    /// kind [`io::ErrorKind::TimedOut`].
+    ///
+    /// `poll` has a granularity of milliseconds, so the duration is rounded up to the next full
+    /// millisecond (a non-zero duration never turns into a non-blocking poll). Durations beyond
+    /// `i32::MAX` milliseconds (about 24.8 days) are clamped to that value, which is effectively
+    /// an infinite wait. Use [`Stream::clear_timeout`] to wait forever.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io;
+    /// use std::time::Duration;
+    ///
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::traits::CaptureStream;
+    /// use v4l::io::userptr::Stream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         // clamped to ~24.8 days instead of overflowing
+    ///         stream.set_timeout(Duration::MAX);
+    ///         stream.set_timeout(Duration::from_millis(500));
+    ///         match stream.next() {
+    ///             Err(e) if e.kind() == io::ErrorKind::TimedOut => println!("no frame in time"),
+    ///             _ => {}
+    ///         }
+    ///     }
+    /// }
+    /// ```
     pub fn set_timeout(&mut self, duration: Duration) {
-        self.timeout = Some(duration.as_millis().try_into().unwrap());
+        self.timeout = Some(poll_timeout(duration));
     }
 
     /// Clears the timeout of the v4l file handle.
@@ -80,6 +433,93 @@ impl Stream {
         self.timeout = None;
     }
 
+    /// Sets the number of frames to drop when the stream is started.
+    ///
+    /// See [`crate::io::mmap::Stream::set_warmup`] for details.
+    pub fn set_warmup(&mut self, frames: u32) {
+        self.state.warmup = frames;
+    }
+
+    /// Sets the time to wait for the device to settle when the stream is started.
+    ///
+    /// See [`crate::io::mmap::Stream::set_settle`] for details.
+    pub fn set_settle(&mut self, duration: Duration) {
+        self.state.settle = duration;
+    }
+
+    /// Sets how often `start` retries to turn on streaming if the device is busy.
+    ///
+    /// See [`crate::io::mmap::Stream::set_start_retries`] for details.
+    pub fn set_start_retries(&mut self, retries: u32) {
+        self.state.start_retries = retries;
+    }
+
+    /// Sets when buffers returned by `next` are handed back to the driver
+    ///
+    /// See [`crate::io::mmap::Stream::set_requeue_policy`] for the trade-offs.
+    pub fn set_requeue_policy(&mut self, policy: RequeuePolicy) -> io::Result<()> {
+        if policy == RequeuePolicy::Deferred && self.arena.len() < 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "deferred requeueing requires at least two buffers",
+            ));
+        }
+
+        self.state.requeue_policy = policy;
+        Ok(())
+    }
+
+    /// Makes `next` skip buffers which the driver flagged as errored
+    ///
+    /// See [`crate::io::mmap::Stream::set_skip_errored`] for details.
+    pub fn set_skip_errored(&mut self, skip: bool) {
+        self.state.skip_errored = skip;
+    }
+
+    /// Sets how many errored buffers in a row are skipped before giving up, 3 by default
+    ///
+    /// Only has an effect if skipping is enabled, see [`Stream::set_skip_errored`].
+    pub fn set_error_retries(&mut self, retries: u32) {
+        self.state.error_retries = retries;
+    }
+
+    /// Makes the stream report source changes (e.g. HDMI resolution switches)
+    ///
+    /// Once enabled, dequeueing a buffer fails with a [`crate::io::SourceChanged`] error (see
+    /// [`crate::io::is_source_change`]) when the source changed its resolution. The frames
+    /// captured after that are garbage, so the stream has to be reconfigured:
+    ///
+    /// 1. Drop the stream, which stops streaming and releases the buffers
+    /// 2. Query and apply the new timings (see `Device::auto_configure_hdmi`)
+    /// 3. Create a new stream, which allocates buffers matching the new format
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::buffer::Type;
+    /// use v4l::device::Device;
+    /// use v4l::io::is_source_change;
+    /// use v4l::io::traits::CaptureStream;
+    /// use v4l::io::userptr::Stream;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(mut stream) = Stream::new(&dev, Type::VideoCapture) {
+    ///         if stream.watch_source_change().is_ok() {
+    ///             match stream.next() {
+    ///                 Err(e) if is_source_change(&e) => println!("reconfigure"),
+    ///                 _ => {}
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn watch_source_change(&mut self) -> io::Result<()> {
+        self.handle.subscribe_event(V4L2_EVENT_SOURCE_CHANGE, 0)?;
+        self.state.watch_source_change = true;
+        Ok(())
+    }
+
     fn buffer_desc(&self) -> v4l2_buffer {
         v4l2_buffer {
             type_: self.buf_type as u32,
@@ -87,6 +527,72 @@ impl Stream {
             ..unsafe { mem::zeroed() }
         }
     }
+
+    /// Runs a buffer ioctl, passing a plane array along for multi-planar buffers
+    fn buffer_ioctl(
+        &self,
+        request: v4l2::vidioc::_IOC_TYPE,
+        v4l2_buf: &mut v4l2_buffer,
+        v4l2_planes: &mut Planes,
+    ) -> io::Result<()> {
+        if self.buf_type.is_mplane() {
+            v4l2_buf.length = self.arena.num_planes() as u32;
+            v4l2_buf.m.planes = v4l2_planes.as_mut_ptr();
+        }
+
+        unsafe {
+            v4l2::ioctl(
+                self.handle.fd(),
+                request,
+                v4l2_buf as *mut _ as *mut std::os::raw::c_void,
+            )
+        }
+    }
+
+    /// Inserts a buffer into the incoming queue, passing the user pointer of each plane
+    fn submit(&self, index: usize) -> io::Result<()> {
+        let planes = self.arena.planes(index).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid buffer index {}", index),
+            )
+        })?;
+        let mut v4l2_buf = v4l2_buffer {
+            index: index as u32,
+            ..self.buffer_desc()
+        };
+        let mut v4l2_planes: Planes = unsafe { mem::zeroed() };
+
+        // MetaData.bytesused is initialized to 0. For an output device, when bytesused is
+        // set to 0 v4l2 will set it to the size of the plane.
+        let bytesused = if self.buf_type.is_output() {
+            v4l2_buf.field = self.buf_meta[index].field;
+            self.buf_meta[index].bytesused
+        } else {
+            0
+        };
+
+        if self.buf_type.is_mplane() {
+            fill_planes(&mut v4l2_planes, &planes, bytesused);
+        } else {
+            v4l2_buf.m.userptr = planes[0].as_ptr() as std::os::raw::c_ulong;
+            v4l2_buf.length = planes[0].len() as u32;
+            v4l2_buf.bytesused = bytesused;
+        }
+
+        self.buffer_ioctl(v4l2::vidioc::VIDIOC_QBUF, &mut v4l2_buf, &mut v4l2_planes)
+    }
+}
+
+impl fmt::Debug for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stream")
+            .field("buf_type", &self.buf_type)
+            .field("memory", &format_args!("{}", Memory::UserPtr))
+            .field("buf_count", &self.arena.len())
+            .field("active", &self.state.active)
+            .finish()
+    }
 }
 
 impl Drop for Stream {
@@ -107,20 +613,25 @@ impl Drop for Stream {
     }
 }
 
+impl AsRawFd for Stream {
+    /// Returns the file descriptor of the device, see [`Stream::fd`]
+    fn as_raw_fd(&self) -> RawFd {
+        self.handle.fd()
+    }
+}
+
 impl StreamTrait for Stream {
     type Item = [u8];
 
     fn start(&mut self) -> io::Result<()> {
-        unsafe {
-            let mut typ = self.buf_type as u32;
-            v4l2::ioctl(
-                self.handle.fd(),
-                v4l2::vidioc::VIDIOC_STREAMON,
-                &mut typ as *mut _ as *mut std::os::raw::c_void,
-            )?;
+        stream_on(&self.handle, self.buf_type, self.state.start_retries)?;
+
+        if self.buf_type.is_output() {
+            // Needed for underrun detection, see OutputStream::underruns()
+            self.frame_interval = output_interval(&self.device());
         }
 
-        self.active = true;
+        self.state.active = true;
         Ok(())
     }
 
@@ -134,81 +645,269 @@ impl StreamTrait for Stream {
             )?;
         }
 
-        self.active = false;
+        self.state.active = false;
+        self.state.held = false;
+        self.last_sequence = None;
         Ok(())
     }
 }
 
-impl<'a> CaptureStream<'a> for Stream {
-    fn queue(&mut self, index: usize) -> io::Result<()> {
-        let buf = &mut self.arena.bufs[index];
+impl Queue for Stream {
+    fn state(&self) -> &QueueState {
+        &self.state
+    }
+
+    fn state_mut(&mut self) -> &mut QueueState {
+        &mut self.state
+    }
+
+    fn handle_ref(&self) -> &Handle {
+        &self.handle
+    }
+
+    fn buffer_count(&self) -> usize {
+        self.arena.len()
+    }
+
+    fn meta(&self, index: usize) -> &Metadata {
+        &self.buf_meta[index]
+    }
+
+    fn query_flags(&self, index: usize) -> io::Result<Flags> {
         let mut v4l2_buf = v4l2_buffer {
             index: index as u32,
-            m: v4l2_buffer__bindgen_ty_1 {
-                userptr: buf.as_ptr() as std::os::raw::c_ulong,
-            },
-            length: buf.len() as u32,
             ..self.buffer_desc()
         };
-        unsafe {
-            v4l2::ioctl(
-                self.handle.fd(),
-                v4l2::vidioc::VIDIOC_QBUF,
-                &mut v4l2_buf as *mut _ as *mut std::os::raw::c_void,
-            )?;
-        }
+        let mut v4l2_planes: Planes = unsafe { mem::zeroed() };
+        self.buffer_ioctl(
+            v4l2::vidioc::VIDIOC_QUERYBUF,
+            &mut v4l2_buf,
+            &mut v4l2_planes,
+        )?;
 
-        Ok(())
+        Ok(Flags::from(v4l2_buf.flags))
     }
 
-    fn dequeue(&mut self) -> io::Result<usize> {
-        let mut v4l2_buf = self.buffer_desc();
+    fn queue_buffer(&mut self, index: usize) -> io::Result<()> {
+        self.submit(index)
+    }
 
-        if self.handle.poll(libc::POLLIN, self.timeout.unwrap_or(-1))? == 0 {
-            // This condition can only happen if there was a timeout.
-            // A timeout is only possible if the `timeout` value is non-zero, meaning we should
-            // propagate it to the caller.
-            return Err(io::Error::new(io::ErrorKind::TimedOut, "VIDIOC_DQBUF"));
-        }
+    fn dequeue_ready(&mut self) -> io::Result<usize> {
+        let mut v4l2_buf = self.buffer_desc();
+        let mut v4l2_planes: Planes = unsafe { mem::zeroed() };
+        self.buffer_ioctl(v4l2::vidioc::VIDIOC_DQBUF, &mut v4l2_buf, &mut v4l2_planes)?;
+        let index = v4l2_buf.index as usize;
 
-        unsafe {
-            v4l2::ioctl(
-                self.handle.fd(),
-                v4l2::vidioc::VIDIOC_DQBUF,
-                &mut v4l2_buf as *mut _ as *mut std::os::raw::c_void,
-            )?;
-        }
-        self.arena_index = v4l2_buf.index as usize;
+        let v4l2_planes = if self.buf_type.is_mplane() {
+            &v4l2_planes[..self.arena.num_planes()]
+        } else {
+            &[]
+        };
+        let used = dequeued_bytesused(&v4l2_buf, v4l2_planes);
+        // The metadata describes the slice handed out by next(), i.e. the first plane
+        let bytesused = used.first().copied().unwrap_or(0);
+        self.plane_bytesused[index] = used;
 
-        self.buf_meta[self.arena_index] = Metadata {
-            bytesused: v4l2_buf.bytesused,
+        self.buf_meta[index] = Metadata {
+            bytesused,
             flags: v4l2_buf.flags.into(),
             field: v4l2_buf.field,
             timestamp: v4l2_buf.timestamp.into(),
             sequence: v4l2_buf.sequence,
         };
+        self.stats
+            .record(&self.buf_meta[index], &mut self.last_sequence);
 
-        Ok(self.arena_index)
+        Ok(index)
+    }
+}
+
+impl<'a> CaptureStream<'a> for Stream {
+    fn queue(&mut self, index: usize) -> io::Result<()> {
+        self.submit(index)
+    }
+
+    fn dequeue(&mut self) -> io::Result<usize> {
+        self.dequeue_within(self.timeout.unwrap_or(-1))
+    }
+
+    fn try_next(&'a mut self) -> io::Result<Option<(&Self::Item, &Metadata)>> {
+        if !self.try_fetch()? {
+            // Nothing ready yet, the next call continues from here
+            return Ok(None);
+        }
+
+        // The index used to access the buffer elements is given to us by v4l2, so we assume it
+        // will always be valid.
+        let bytes = self.arena.get(self.state.index).unwrap();
+        let meta = &self.buf_meta[self.state.index];
+        Ok(Some((bytes, meta)))
+    }
+
+    fn next_latest(&'a mut self) -> io::Result<(&Self::Item, &Metadata)> {
+        self.fetch_latest(self.timeout.unwrap_or(-1))?;
+
+        // The index used to access the buffer elements is given to us by v4l2, so we assume it
+        // will always be valid.
+        let bytes = self.arena.get(self.state.index).unwrap();
+        let meta = &self.buf_meta[self.state.index];
+        Ok((bytes, meta))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.state.active {
+            return Ok(());
+        }
+
+        self.requeue()
+    }
+
+    fn restart(&mut self) -> io::Result<()> {
+        self.requeue()
     }
 
     fn next(&'a mut self) -> io::Result<(&Self::Item, &Metadata)> {
-        if !self.active {
-            // Enqueue all buffers once on stream start
-            for index in 0..self.arena.bufs.len() {
-                self.queue(index)?;
+        self.fetch(self.timeout.unwrap_or(-1))?;
+
+        // The index used to access the buffer elements is given to us by v4l2, so we assume it
+        // will always be valid.
+        let bytes = self.arena.get(self.state.index).unwrap();
+        let meta = &self.buf_meta[self.state.index];
+        Ok((bytes, meta))
+    }
+}
+
+impl<'a> OutputStream<'a> for Stream {
+    fn queue(&mut self, index: usize) -> io::Result<()> {
+        let wait = Instant::now();
+        if self
+            .handle
+            .poll(libc::POLLOUT, self.timeout.unwrap_or(-1))?
+            == 0
+        {
+            // This condition can only happen if there was a timeout.
+            // A timeout is only possible if the `timeout` value is non-zero, meaning we should
+            // propagate it to the caller.
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "VIDIOC_QBUF"));
+        }
+        if let Some(interval) = self.frame_interval {
+            if wait.elapsed() > interval {
+                self.underruns += 1;
             }
+        }
+
+        self.submit(index)
+    }
 
+    fn dequeue(&mut self) -> io::Result<usize> {
+        self.dequeue_ready()
+    }
+
+    fn next(&'a mut self) -> io::Result<(&mut Self::Item, &mut Metadata)> {
+        let init = !self.state.active;
+        if !self.state.active {
             self.start()?;
-        } else {
-            self.queue(self.arena_index)?;
         }
 
-        self.arena_index = self.dequeue()?;
+        // Only queue and dequeue once the buffer has been filled at the call site. The initial
+        // call to this function from the call site will happen just after the buffers have been
+        // allocated, meaning we need to return the empty buffer initially so it can be filled.
+        if !init {
+            OutputStream::queue(self, self.state.index)?;
+            self.state.index = OutputStream::dequeue(self)?;
+        }
 
         // The index used to access the buffer elements is given to us by v4l2, so we assume it
         // will always be valid.
-        let bytes = &mut self.arena.bufs[self.arena_index];
-        let meta = &self.buf_meta[self.arena_index];
+        let bytes = self.arena.get_mut(self.state.index).unwrap();
+        let meta = &mut self.buf_meta[self.state.index];
         Ok((bytes, meta))
     }
+
+    fn drain(&mut self) -> io::Result<()> {
+        self.drain_output(self.timeout.unwrap_or(-1))
+    }
+
+    fn underruns(&self) -> u64 {
+        self.underruns
+    }
+}
+
+/// Returns the number of bytes used in each plane of a dequeued buffer
+///
+/// # Arguments
+///
+/// * `v4l2_buf` - Buffer descriptor returned by the driver
+/// * `v4l2_planes` - Plane descriptors of multi-planar buffers, empty for single-planar ones
+fn dequeued_bytesused(v4l2_buf: &v4l2_buffer, v4l2_planes: &[v4l2_plane]) -> Vec<u32> {
+    if v4l2_planes.is_empty() {
+        vec![v4l2_buf.bytesused]
+    } else {
+        v4l2_planes.iter().map(|plane| plane.bytesused).collect()
+    }
+}
+
+/// Points the plane descriptors to the memory of the planes
+///
+/// Multi-planar buffers carry the number of bytes per plane, so `bytesused` is distributed over
+/// the planes in order.
+///
+/// # Arguments
+///
+/// * `v4l2_planes` - Plane descriptors passed to the driver
+/// * `planes` - Memory of each plane
+/// * `bytesused` - Number of bytes used in all planes
+fn fill_planes(v4l2_planes: &mut Planes, planes: &[&[u8]], bytesused: u32) {
+    let mut remaining = bytesused;
+    for (v4l2_plane, plane) in v4l2_planes.iter_mut().zip(planes) {
+        v4l2_plane.m.userptr = plane.as_ptr() as std::os::raw::c_ulong;
+        v4l2_plane.length = plane.len() as u32;
+        v4l2_plane.bytesused = remaining.min(plane.len() as u32);
+        remaining -= v4l2_plane.bytesused;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mplane_descriptors() {
+        let (luma, chroma) = (vec![0u8; 640 * 480], vec![0u8; 640 * 240]);
+        let planes = [luma.as_slice(), chroma.as_slice()];
+
+        let mut v4l2_planes: Planes = unsafe { mem::zeroed() };
+        fill_planes(&mut v4l2_planes, &planes, 640 * 480 + 1000);
+        for (v4l2_plane, plane) in v4l2_planes.iter().zip(&planes) {
+            assert_eq!(unsafe { v4l2_plane.m.userptr }, plane.as_ptr() as _);
+            assert_eq!(v4l2_plane.length as usize, plane.len());
+        }
+        assert_eq!(v4l2_planes[0].bytesused, 640 * 480);
+        assert_eq!(v4l2_planes[1].bytesused, 1000);
+        // unused descriptors are left alone
+        assert_eq!(v4l2_planes[2].length, 0);
+
+        // capture buffers are queued empty
+        fill_planes(&mut v4l2_planes, &planes, 0);
+        assert_eq!(v4l2_planes[0].bytesused, 0);
+        assert_eq!(v4l2_planes[1].bytesused, 0);
+    }
+
+    #[test]
+    fn dequeued_plane_payloads() {
+        let v4l2_buf = v4l2_buffer {
+            bytesused: 1234,
+            ..unsafe { mem::zeroed() }
+        };
+        assert_eq!(dequeued_bytesused(&v4l2_buf, &[]), vec![1234]);
+
+        // the buffer descriptor itself does not count for multi-planar buffers
+        let mut v4l2_planes: Planes = unsafe { mem::zeroed() };
+        v4l2_planes[0].bytesused = 640 * 480;
+        v4l2_planes[1].bytesused = 640 * 240;
+        assert_eq!(
+            dequeued_bytesused(&v4l2_buf, &v4l2_planes[..2]),
+            vec![640 * 480, 640 * 240]
+        );
+    }
 }
@@ -0,0 +1,65 @@
+use std::fmt;
+
+use crate::v4l_sys::*;
+
+bitflags::bitflags! {
+    /// Markers included in the JPEG stream
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct Markers : u32 {
+        /// Huffman tables
+        const DHT   = 0x00000008;
+        /// Quantization tables
+        const DQT   = 0x00000010;
+        /// Restart interval
+        const DRI   = 0x00000020;
+        /// Comment segment
+        const COM   = 0x00000040;
+        /// Application segment
+        const APP   = 0x00000080;
+    }
+}
+
+impl From<u32> for Markers {
+    fn from(flags: u32) -> Self {
+        Self::from_bits_retain(flags)
+    }
+}
+
+impl From<Markers> for u32 {
+    fn from(flags: Markers) -> Self {
+        flags.bits()
+    }
+}
+
+impl fmt::Display for Markers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// JPEG compression parameters as returned by [`crate::v4l2::vidioc::VIDIOC_G_JPEGCOMP`]
+pub struct JpegCompression {
+    /// Compression quality, the range is driver specific (usually 0 to 100)
+    pub quality: i32,
+    /// Markers included in the stream. Devices omitting the Huffman tables (DHT) produce
+    /// streams which many decoders cannot handle.
+    pub markers: Markers,
+}
+
+impl fmt::Display for JpegCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "quality : {}", self.quality)?;
+        writeln!(f, "markers : {}", self.markers)?;
+        Ok(())
+    }
+}
+
+impl From<v4l2_jpegcompression> for JpegCompression {
+    fn from(comp: v4l2_jpegcompression) -> Self {
+        Self {
+            quality: comp.quality,
+            markers: Markers::from(comp.jpeg_markers),
+        }
+    }
+}
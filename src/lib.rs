@@ -41,6 +41,9 @@
 //! do not incur any copy overhead either. Most (if not all) devices reporting streaming I/O
 //! capabilities support MMAP buffer sharing, but not all support user pointer access.
 //!
+//! DMA buffers are exposed by the `dmabuf` stream, which hands out the dma-buf file descriptors
+//! of the captured frames instead of their bytes.
+//!
 //! The regular user of this crate will mainly be interested in frame capturing.
 //! Here is a very brief example of streaming I/O with memory mapped buffers:
 //!
@@ -80,30 +83,80 @@ pub mod capability;
 pub mod context;
 pub mod control;
 pub mod device;
+pub mod edid;
+pub mod error;
+pub mod event;
 pub mod format;
 pub mod fraction;
 pub mod frameinterval;
 pub mod framesize;
+pub mod input;
+pub mod jpeg;
 pub mod memory;
+pub mod mode;
+pub mod output;
 pub mod parameters;
+pub mod priority;
+pub mod rect;
+pub mod selection;
+pub mod standard;
 pub mod timestamp;
+pub mod timings;
+pub mod tuner;
+pub mod uvc;
 pub mod video;
 
 pub mod io;
 
+#[cfg(test)]
+mod tests;
+
 pub use {
     capability::Capabilities,
     control::Control,
     device::Device,
+    error::Error,
     format::{Format, FourCC},
     fraction::Fraction,
     frameinterval::FrameInterval,
     framesize::FrameSize,
     memory::Memory,
+    rect::Rect,
     timestamp::Timestamp,
 };
 
+/// Commonly needed types for capturing frames
+///
+/// The traits are not part of the prelude: the methods of the capture traits
+/// ([`video::Capture`], [`io::traits::CaptureStream`]) share names with the output ones (e.g.
+/// `format` or `next`), so exporting them here would make these calls ambiguous for code
+/// which imports the output traits next to the prelude. Import the traits you need
+/// explicitly and call them qualified where needed, e.g. `Output::format(&dev)`.
+///
+/// # Example
+///
+/// ```
+/// use v4l::io::traits::CaptureStream;
+/// use v4l::prelude::*;
+/// use v4l::video::Capture;
+///
+/// if let Ok(dev) = Device::new(0) {
+///     if let Ok(fmt) = dev.format() {
+///         println!("{}", fmt);
+///     }
+///     if let Ok(mut stream) = MmapStream::new(&dev, BufferType::VideoCapture) {
+///         if let Ok((buf, meta)) = stream.next() {
+///             println!("frame {}: {} bytes", meta.sequence, buf.len());
+///         }
+///     }
+/// }
+/// ```
 pub mod prelude {
+    pub use crate::buffer::Type as BufferType;
     pub use crate::device::Device;
-    pub use crate::io::{mmap::Stream as MmapStream, userptr::Stream as UserptrStream};
+    pub use crate::format::{Format, FourCC};
+    pub use crate::io::{
+        dmabuf::Stream as DmaBufStream, mmap::Stream as MmapStream,
+        userptr::Stream as UserptrStream,
+    };
 }
@@ -1,9 +1,13 @@
-use std::fmt;
+use std::convert::TryFrom;
+use std::{error, fmt, io};
+
+use crate::v4l_sys::VIDEO_MAX_FRAME;
 
 /// Memory used for buffer exchange
 #[allow(clippy::unreadable_literal)]
 #[rustfmt::skip]
 #[repr(u32)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Memory {
     Mmap        = 1,
     UserPtr     = 2,
@@ -11,6 +15,41 @@ pub enum Memory {
     DmaBuf      = 4,
 }
 
+/// Converts a raw memory type code
+impl TryFrom<u32> for Memory {
+    type Error = UnknownMemory;
+
+    fn try_from(code: u32) -> Result<Self, Self::Error> {
+        match code {
+            1 => Ok(Self::Mmap),
+            2 => Ok(Self::UserPtr),
+            3 => Ok(Self::Overlay),
+            4 => Ok(Self::DmaBuf),
+            _ => Err(UnknownMemory(code)),
+        }
+    }
+}
+
+/// Error returned when converting a raw code which does not name a [`Memory`] type
+///
+/// It converts into an [`io::Error`] of kind [`io::ErrorKind::InvalidData`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownMemory(pub u32);
+
+impl fmt::Display for UnknownMemory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown memory type {}", self.0)
+    }
+}
+
+impl error::Error for UnknownMemory {}
+
+impl From<UnknownMemory> for io::Error {
+    fn from(err: UnknownMemory) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
 impl fmt::Display for Memory {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
@@ -21,3 +60,264 @@ impl fmt::Display for Memory {
         }
     }
 }
+
+bitflags::bitflags! {
+    /// Memory flags which can be set when requesting buffers
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct Flags: u32 {
+        /// Buffers are allocated in non-coherent memory. This requires explicit cache
+        /// management by the kernel, but is often faster for CPU access (e.g. on ARM).
+        /// Only honored for memory-mapped buffers if the queue supports cache hints.
+        const NON_COHERENT  = 0x00000001;
+    }
+}
+
+impl From<u32> for Flags {
+    fn from(flags: u32) -> Self {
+        Self::from_bits_retain(flags)
+    }
+}
+
+impl From<Flags> for u32 {
+    fn from(flags: Flags) -> Self {
+        flags.bits()
+    }
+}
+
+impl fmt::Display for Flags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+bitflags::bitflags! {
+    /// Buffer capabilities reported by the driver when requesting buffers
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct Capabilities: u32 {
+        const SUPPORTS_MMAP                 = 0x00000001;
+        const SUPPORTS_USERPTR              = 0x00000002;
+        const SUPPORTS_DMABUF               = 0x00000004;
+        const SUPPORTS_REQUESTS             = 0x00000008;
+        const SUPPORTS_ORPHANED_BUFS        = 0x00000010;
+        const SUPPORTS_M2M_HOLD_CAPTURE_BUF = 0x00000020;
+        const SUPPORTS_MMAP_CACHE_HINTS     = 0x00000040;
+    }
+}
+
+impl Capabilities {
+    /// Returns whether buffers of the given memory type can be requested
+    ///
+    /// # Arguments
+    ///
+    /// * `memory` - Memory type
+    pub fn supports(&self, memory: Memory) -> bool {
+        match memory {
+            Memory::Mmap => self.contains(Capabilities::SUPPORTS_MMAP),
+            Memory::UserPtr => self.contains(Capabilities::SUPPORTS_USERPTR),
+            Memory::DmaBuf => self.contains(Capabilities::SUPPORTS_DMABUF),
+            Memory::Overlay => false,
+        }
+    }
+}
+
+impl From<u32> for Capabilities {
+    fn from(caps: u32) -> Self {
+        Self::from_bits_retain(caps)
+    }
+}
+
+impl From<Capabilities> for u32 {
+    fn from(caps: Capabilities) -> Self {
+        caps.bits()
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Verifies that a buffer request was granted with the requested memory type
+///
+/// Drivers report the memory type and the queue capabilities back in the VIDIOC_REQBUFS
+/// result. Checking them right away turns a misconfigured stream into a clear error instead
+/// of a confusing failure during the first QBUF.
+///
+/// Older kernels do not report any capabilities, in which case only the memory type is checked.
+///
+/// # Arguments
+///
+/// * `requested` - Memory type that was requested
+/// * `memory` - Memory type returned by the driver
+/// * `caps` - Buffer capabilities returned by the driver
+///
+/// # Example
+///
+/// ```
+/// use v4l::memory::{verify_request, Capabilities, Memory};
+///
+/// // a queue that only offers memory-mapped buffers
+/// let caps = Capabilities::SUPPORTS_MMAP;
+/// assert!(verify_request(Memory::Mmap, Memory::Mmap as u32, caps).is_ok());
+/// assert!(verify_request(Memory::UserPtr, Memory::UserPtr as u32, caps).is_err());
+///
+/// // the driver silently switched the memory type
+/// assert!(verify_request(Memory::DmaBuf, Memory::Mmap as u32, Capabilities::empty()).is_err());
+///
+/// // no capabilities reported at all
+/// assert!(verify_request(Memory::UserPtr, Memory::UserPtr as u32, Capabilities::empty()).is_ok());
+/// ```
+pub fn verify_request(requested: Memory, memory: u32, caps: Capabilities) -> io::Result<()> {
+    if memory != requested as u32 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "VIDIOC_REQBUFS: requested {} buffers, but driver returned memory type {}",
+                requested, memory
+            ),
+        ));
+    }
+
+    if !caps.is_empty() && !caps.supports(requested) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "VIDIOC_REQBUFS: queue does not support {} buffers (capabilities: {})",
+                requested, caps
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Verifies that a number of buffers can be requested at all
+///
+/// The kernel never allocates more than `VIDEO_MAX_FRAME` (32) buffers per queue. Drivers
+/// silently clamp larger requests or fail in driver-specific ways, so out-of-range counts are
+/// rejected with an error of kind [`io::ErrorKind::InvalidInput`] before the request is made.
+///
+/// # Arguments
+///
+/// * `count` - Number of buffers, between 1 and 32
+///
+/// # Example
+///
+/// ```
+/// use std::io;
+/// use v4l::memory::verify_count;
+///
+/// assert!(verify_count(4).is_ok());
+/// assert!(verify_count(32).is_ok());
+/// assert_eq!(verify_count(33).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+/// assert_eq!(verify_count(10000).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+/// assert_eq!(verify_count(0).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+/// ```
+pub fn verify_count(count: u32) -> io::Result<()> {
+    if count == 0 || count > VIDEO_MAX_FRAME {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "cannot request {} buffers, the count must be between 1 and {}",
+                count, VIDEO_MAX_FRAME
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Verifies that the driver granted at least one buffer
+///
+/// Drivers may allocate fewer buffers than requested, e.g. if memory is scarce. A stream without
+/// buffers cannot work though, so this case is reported as an error of kind
+/// [`io::ErrorKind::OutOfMemory`]. Use the `allocated_buffers` method of the streams to enforce
+/// a higher minimum.
+///
+/// # Arguments
+///
+/// * `requested` - Number of buffers passed to `VIDIOC_REQBUFS`
+/// * `granted` - Number of buffers returned by the driver
+///
+/// # Example
+///
+/// ```
+/// use std::io;
+/// use v4l::memory::verify_granted;
+///
+/// assert!(verify_granted(4, 4).is_ok());
+/// assert!(verify_granted(4, 2).is_ok());
+/// assert_eq!(verify_granted(4, 0).unwrap_err().kind(), io::ErrorKind::OutOfMemory);
+/// ```
+pub fn verify_granted(requested: u32, granted: u32) -> io::Result<()> {
+    if granted == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::OutOfMemory,
+            format!(
+                "VIDIOC_REQBUFS: requested {} buffers, but driver granted none",
+                requested
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Buffer request which the driver rejected with `EINVAL`
+///
+/// Besides unsupported buffer types, some drivers refuse to allocate buffers until a format has
+/// been negotiated. The error returned by the driver is kept as source.
+#[derive(Debug)]
+struct RequestRejected(io::Error);
+
+impl fmt::Display for RequestRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "VIDIOC_REQBUFS: buffer type not supported or no format set \
+            (set a format before creating a stream)"
+        )
+    }
+}
+
+impl error::Error for RequestRejected {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+/// Maps `EINVAL` of `VIDIOC_REQBUFS` to an error of kind [`io::ErrorKind::InvalidInput`] which
+/// hints at the missing format, other errors are passed on
+///
+/// # Arguments
+///
+/// * `err` - Error returned by `VIDIOC_REQBUFS`
+pub(crate) fn map_request_error(err: io::Error) -> io::Error {
+    match err.raw_os_error() {
+        Some(libc::EINVAL) => io::Error::new(io::ErrorKind::InvalidInput, RequestRejected(err)),
+        _ => err,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_error_keeps_os_error() {
+        let err = map_request_error(io::Error::from_raw_os_error(libc::EINVAL));
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("set a format"));
+
+        let source = err.get_ref().and_then(|inner| inner.source()).unwrap();
+        let source = source.downcast_ref::<io::Error>().unwrap();
+        assert_eq!(source.raw_os_error(), Some(libc::EINVAL));
+    }
+
+    #[test]
+    fn request_error_passes_others() {
+        let err = map_request_error(io::Error::from_raw_os_error(libc::ENOMEM));
+        assert_eq!(err.raw_os_error(), Some(libc::ENOMEM));
+    }
+}
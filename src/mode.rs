@@ -0,0 +1,34 @@
+use std::fmt;
+
+use crate::format::FourCC;
+use crate::fraction::Fraction;
+
+/// Maximum number of modes returned by [`crate::device::Device::supported_modes`]
+pub const MAX_MODES: usize = 4096;
+
+/// Maximum number of frame intervals a stepwise interval range contributes to
+/// [`crate::device::Device::supported_modes`] per frame size
+pub const MAX_MODE_INTERVALS: usize = 8;
+
+#[derive(Debug, Clone, Copy)]
+/// Combination of pixelformat, frame size and frame rate accepted by a device
+pub struct Mode {
+    /// pixelformat code
+    pub fourcc: FourCC,
+    /// width in pixels
+    pub width: u32,
+    /// height in pixels
+    pub height: u32,
+    /// frames per second, i.e. the inverse of the frame interval
+    pub fps: Fraction,
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}x{}@{}",
+            self.fourcc, self.width, self.height, self.fps
+        )
+    }
+}
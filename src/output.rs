@@ -0,0 +1,116 @@
+use std::{fmt, str};
+
+use crate::standard::Standard;
+use crate::v4l_sys::*;
+
+/// Output type
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Type {
+    Modulator,
+    Analog,
+    AnalogVgaOverlay,
+    Unknown(u32),
+}
+
+/// Converts a raw output type code, unknown codes are preserved in [`Type::Unknown`]
+impl From<u32> for Type {
+    fn from(repr: u32) -> Self {
+        match repr {
+            1 => Self::Modulator,
+            2 => Self::Analog,
+            3 => Self::AnalogVgaOverlay,
+            repr => Self::Unknown(repr),
+        }
+    }
+}
+
+impl From<Type> for u32 {
+    fn from(t: Type) -> Self {
+        match t {
+            Type::Modulator => 1,
+            Type::Analog => 2,
+            Type::AnalogVgaOverlay => 3,
+            Type::Unknown(t) => t,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct Capabilities : u32 {
+        const DV_TIMINGS    = 0x00000002;
+        const STD           = 0x00000004;
+        const NATIVE_SIZE   = 0x00000008;
+    }
+}
+
+impl From<u32> for Capabilities {
+    fn from(flags: u32) -> Self {
+        Self::from_bits_retain(flags)
+    }
+}
+
+impl From<Capabilities> for u32 {
+    fn from(flags: Capabilities) -> Self {
+        flags.bits()
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Debug)]
+/// Video output as returned by [`crate::v4l2::vidioc::VIDIOC_ENUMOUTPUT`]
+pub struct Output {
+    /// Index of the output, used to select it
+    pub index: u32,
+    /// Name of the output, e.g. "HDMI" or "Composite"
+    pub name: String,
+    /// Type of the output
+    pub typ: Type,
+    /// Bitmask of the audio outputs associated with this output
+    pub audioset: u32,
+    /// Index of the modulator, only valid for modulator outputs
+    pub modulator: u32,
+    /// Supported analog video standards
+    pub std: Standard,
+    /// Output capabilities
+    pub capabilities: Capabilities,
+}
+
+impl fmt::Display for Output {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "index        : {}", self.index)?;
+        writeln!(f, "name         : {}", self.name)?;
+        writeln!(f, "type         : {}", self.typ)?;
+        writeln!(f, "std          : {}", self.std)?;
+        writeln!(f, "capabilities : {}", self.capabilities)?;
+        Ok(())
+    }
+}
+
+impl From<v4l2_output> for Output {
+    fn from(output: v4l2_output) -> Self {
+        Self {
+            index: output.index,
+            name: str::from_utf8(&output.name)
+                .unwrap()
+                .trim_matches(char::from(0))
+                .to_string(),
+            typ: Type::from(output.type_),
+            audioset: output.audioset,
+            modulator: output.modulator,
+            std: Standard::from(output.std),
+            capabilities: Capabilities::from(output.capabilities),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::fmt;
+
+/// Access priority of a file handle
+///
+/// Only file handles with the highest priority among all open ones may change the device
+/// state (format, controls, inputs, ..), others are refused with `EBUSY`. The priority is
+/// released when the handle is closed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Priority {
+    /// Lowest priority, e.g. for monitoring tools that must not interfere with other clients
+    Background,
+    /// Default priority of newly opened file handles
+    Interactive,
+    /// Highest priority, claimed by applications which must not be interrupted such as a
+    /// recording daemon. While held, clients with a lower priority cannot alter the state.
+    Record,
+    Unknown(u32),
+}
+
+/// Converts a raw priority code, unknown codes are preserved in [`Priority::Unknown`]
+impl From<u32> for Priority {
+    fn from(repr: u32) -> Self {
+        match repr {
+            1 => Self::Background,
+            2 => Self::Interactive,
+            3 => Self::Record,
+            repr => Self::Unknown(repr),
+        }
+    }
+}
+
+impl From<Priority> for u32 {
+    fn from(prio: Priority) -> Self {
+        match prio {
+            Priority::Background => 1,
+            Priority::Interactive => 2,
+            Priority::Record => 3,
+            Priority::Unknown(prio) => prio,
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
@@ -0,0 +1,73 @@
+use crate::v4l_sys::*;
+use std::fmt;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// Rectangle used for cropping and selection settings
+pub struct Rect {
+    /// horizontal offset of the top left corner in pixels
+    pub left: i32,
+    /// vertical offset of the top left corner in pixels
+    pub top: i32,
+    /// width in pixels
+    pub width: u32,
+    /// height in pixels
+    pub height: u32,
+}
+
+impl Rect {
+    /// Returns a rectangle representation
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - Horizontal offset of the top left corner
+    /// * `top` - Vertical offset of the top left corner
+    /// * `width` - Width in pixels
+    /// * `height` - Height in pixels
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::rect::Rect;
+    /// let rect = Rect::new(0, 0, 640, 480);
+    /// ```
+    pub fn new(left: i32, top: i32, width: u32, height: u32) -> Self {
+        Rect {
+            left,
+            top,
+            width,
+            height,
+        }
+    }
+}
+
+impl fmt::Display for Rect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x{}@({}, {})",
+            self.width, self.height, self.left, self.top
+        )
+    }
+}
+
+impl From<v4l2_rect> for Rect {
+    fn from(rect: v4l2_rect) -> Self {
+        Self {
+            left: rect.left,
+            top: rect.top,
+            width: rect.width,
+            height: rect.height,
+        }
+    }
+}
+
+impl From<Rect> for v4l2_rect {
+    fn from(rect: Rect) -> Self {
+        Self {
+            left: rect.left,
+            top: rect.top,
+            width: rect.width,
+            height: rect.height,
+        }
+    }
+}
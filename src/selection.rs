@@ -0,0 +1,96 @@
+use std::fmt;
+
+use crate::fraction::Fraction;
+pub use crate::rect::Rect;
+use crate::v4l_sys::*;
+
+/// Selection target
+#[allow(clippy::unreadable_literal)]
+#[rustfmt::skip]
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// Current cropping area
+    Crop            = 0x0000,
+    /// Suggested cropping area, covering the whole picture without black borders
+    CropDefault     = 0x0001,
+    /// Limits for the cropping area
+    CropBounds      = 0x0002,
+    /// Native size of the sensor (or other source)
+    NativeSize      = 0x0003,
+    /// Current composing area
+    Compose         = 0x0100,
+    /// Suggested composing area
+    ComposeDefault  = 0x0101,
+    /// Limits for the composing area
+    ComposeBounds   = 0x0102,
+    /// Composing area including the padding modified by the hardware
+    ComposePadded   = 0x0103,
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+bitflags::bitflags! {
+    /// Constraints for adjusting a requested selection rectangle
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct Flags: u32 {
+        /// The adjusted rectangle must be at least as large as the requested one
+        const GE            = 0x00000001;
+        /// The adjusted rectangle must be at most as large as the requested one
+        const LE            = 0x00000002;
+        /// The configuration must not be propagated to other pipeline stages
+        const KEEP_CONFIG   = 0x00000004;
+    }
+}
+
+impl From<u32> for Flags {
+    fn from(flags: u32) -> Self {
+        Self::from_bits_retain(flags)
+    }
+}
+
+impl From<Flags> for u32 {
+    fn from(flags: Flags) -> Self {
+        flags.bits()
+    }
+}
+
+impl fmt::Display for Flags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+/// Cropping capabilities as returned by [`crate::v4l2::vidioc::VIDIOC_CROPCAP`]
+pub struct CropCapabilities {
+    /// Limits for the cropping area
+    pub bounds: Rect,
+    /// Default cropping area, covering the whole picture
+    pub default: Rect,
+    /// Pixel aspect ratio (y / x) when no scaling is applied
+    pub pixel_aspect: Fraction,
+}
+
+impl fmt::Display for CropCapabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "bounds       : {}", self.bounds)?;
+        writeln!(f, "default      : {}", self.default)?;
+        writeln!(f, "pixel aspect : {}", self.pixel_aspect)?;
+        Ok(())
+    }
+}
+
+impl From<v4l2_cropcap> for CropCapabilities {
+    fn from(cropcap: v4l2_cropcap) -> Self {
+        Self {
+            bounds: Rect::from(cropcap.bounds),
+            default: Rect::from(cropcap.defrect),
+            pixel_aspect: Fraction::from(cropcap.pixelaspect),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::fmt;
+
+use crate::fraction::Fraction;
+use crate::v4l_sys::*;
+
+bitflags::bitflags! {
+    /// Analog video standard (v4l2_std_id)
+    ///
+    /// The constants are not part of the generated bindings since the C header defines them
+    /// as 64-bit macros, so they are mirrored here.
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct Standard : u64 {
+        /* one bit for each */
+        const PAL_B         = 0x00000001;
+        const PAL_B1        = 0x00000002;
+        const PAL_G         = 0x00000004;
+        const PAL_H         = 0x00000008;
+        const PAL_I         = 0x00000010;
+        const PAL_D         = 0x00000020;
+        const PAL_D1        = 0x00000040;
+        const PAL_K         = 0x00000080;
+
+        const PAL_M         = 0x00000100;
+        const PAL_N         = 0x00000200;
+        const PAL_NC        = 0x00000400;
+        const PAL_60        = 0x00000800;
+
+        const NTSC_M        = 0x00001000;
+        const NTSC_M_JP     = 0x00002000;
+        const NTSC_443      = 0x00004000;
+        const NTSC_M_KR     = 0x00008000;
+
+        const SECAM_B       = 0x00010000;
+        const SECAM_D       = 0x00020000;
+        const SECAM_G       = 0x00040000;
+        const SECAM_H       = 0x00080000;
+        const SECAM_K       = 0x00100000;
+        const SECAM_K1      = 0x00200000;
+        const SECAM_L       = 0x00400000;
+        const SECAM_LC      = 0x00800000;
+
+        /* ATSC/HDTV */
+        const ATSC_8_VSB    = 0x01000000;
+        const ATSC_16_VSB   = 0x02000000;
+
+        /* common groups */
+        const NTSC          = Self::NTSC_M.bits() | Self::NTSC_M_JP.bits() | Self::NTSC_M_KR.bits();
+        const SECAM_DK      = Self::SECAM_D.bits() | Self::SECAM_K.bits() | Self::SECAM_K1.bits();
+        const SECAM         = Self::SECAM_B.bits()
+                            | Self::SECAM_G.bits()
+                            | Self::SECAM_H.bits()
+                            | Self::SECAM_DK.bits()
+                            | Self::SECAM_L.bits()
+                            | Self::SECAM_LC.bits();
+        const PAL_BG        = Self::PAL_B.bits() | Self::PAL_B1.bits() | Self::PAL_G.bits();
+        const PAL_DK        = Self::PAL_D.bits() | Self::PAL_D1.bits() | Self::PAL_K.bits();
+        const PAL           = Self::PAL_BG.bits()
+                            | Self::PAL_DK.bits()
+                            | Self::PAL_H.bits()
+                            | Self::PAL_I.bits();
+        const MN            = Self::PAL_M.bits()
+                            | Self::PAL_N.bits()
+                            | Self::PAL_NC.bits()
+                            | Self::NTSC.bits();
+        const ATSC          = Self::ATSC_8_VSB.bits() | Self::ATSC_16_VSB.bits();
+
+        /* line/field based groups */
+        const STD_525_60    = Self::PAL_M.bits()
+                            | Self::PAL_60.bits()
+                            | Self::NTSC.bits()
+                            | Self::NTSC_443.bits();
+        const STD_625_50    = Self::PAL.bits()
+                            | Self::PAL_N.bits()
+                            | Self::PAL_NC.bits()
+                            | Self::SECAM.bits();
+        const ALL           = Self::STD_525_60.bits() | Self::STD_625_50.bits();
+    }
+}
+
+impl From<u64> for Standard {
+    fn from(id: u64) -> Self {
+        Self::from_bits_retain(id)
+    }
+}
+
+impl From<Standard> for u64 {
+    fn from(std: Standard) -> Self {
+        std.bits()
+    }
+}
+
+impl fmt::Display for Standard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Debug)]
+/// Video standard as returned by [`crate::v4l2::vidioc::VIDIOC_ENUMSTD`]
+pub struct Description {
+    /// Index of the standard in the enumeration
+    pub index: u32,
+    /// Standard bits covered by this entry, may be more than one (e.g. PAL B/G)
+    pub id: Standard,
+    /// Human-readable name, e.g. "PAL-BG" or "NTSC-M"
+    pub name: String,
+    /// Frame period (not field period), e.g. 1/25 for PAL
+    pub frame_period: Fraction,
+    /// Total lines per frame including blanking, e.g. 625 for PAL
+    pub frame_lines: u32,
+}
+
+impl fmt::Display for Description {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "index        : {}", self.index)?;
+        writeln!(f, "id           : {}", self.id)?;
+        writeln!(f, "name         : {}", self.name)?;
+        writeln!(f, "frame period : {}", self.frame_period)?;
+        writeln!(f, "frame lines  : {}", self.frame_lines)?;
+        Ok(())
+    }
+}
+
+impl From<v4l2_standard> for Description {
+    fn from(std: v4l2_standard) -> Self {
+        Self {
+            index: std.index,
+            id: Standard::from(std.id),
+            name: String::from_utf8_lossy(&std.name)
+                .trim_matches(char::from(0))
+                .to_string(),
+            frame_period: Fraction::from(std.frameperiod),
+            frame_lines: std.framelines,
+        }
+    }
+}
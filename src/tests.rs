@@ -0,0 +1,193 @@
+//! Round trips of the conversions between the enums and their raw V4L2 codes
+//!
+//! Every enum is listed along with all of its variants. The lists are matched exhaustively, so
+//! adding a variant without extending its list fails to compile.
+
+use std::convert::TryFrom;
+
+use crate::buffer::Type as BufferType;
+use crate::control::Type as ControlType;
+use crate::event::Type as EventType;
+use crate::format::{Colorspace, FieldOrder, Quantization, TransferFunction};
+use crate::input::Type as InputType;
+use crate::memory::Memory;
+use crate::output::Type as OutputType;
+use crate::priority::Priority;
+use crate::tuner::{AudioMode, Type as TunerType};
+
+/// Checks an enum converted by `TryFrom<u32>` and `as u32`, followed by codes which are invalid
+macro_rules! try_from_round_trip {
+    ($test:ident, $typ:ident, [$($variant:ident),+ $(,)?], invalid: [$($code:expr),*]) => {
+        #[test]
+        fn $test() {
+            for value in [$($typ::$variant),+] {
+                match value {
+                    $($typ::$variant)|+ => {}
+                }
+                assert!($typ::try_from(value as u32) == Ok(value));
+            }
+            $(assert!($typ::try_from($code).is_err());)*
+        }
+    };
+}
+
+/// Checks an enum converted by `From<u32>` and back, which preserves unknown codes in its
+/// `Unknown` variant, followed by a range of raw codes
+macro_rules! from_round_trip {
+    ($test:ident, $typ:ident, [$($variant:ident),+ $(,)?], codes: $codes:expr) => {
+        #[test]
+        fn $test() {
+            for value in [$($typ::$variant),+] {
+                match value {
+                    $($typ::$variant)|+ | $typ::Unknown(_) => {}
+                }
+                assert_eq!($typ::from(u32::from(value)), value);
+            }
+            for code in $codes {
+                assert_eq!(u32::from($typ::from(code)), code);
+            }
+        }
+    };
+}
+
+try_from_round_trip!(
+    buffer_type,
+    BufferType,
+    [
+        VideoCapture,
+        VideoOutput,
+        VideoOverlay,
+        VbiCapture,
+        VbiOutput,
+        SlicedVbiCapture,
+        SlicedVbiOutput,
+        VideoOutputOverlay,
+        VideoCaptureMplane,
+        VideoOutputMplane,
+        SdrCapture,
+        SdrOutput,
+        MetaCapture,
+        MetaOutput,
+        Private,
+    ],
+    invalid: [0, 15]
+);
+
+try_from_round_trip!(
+    memory,
+    Memory,
+    [Mmap, UserPtr, Overlay, DmaBuf],
+    invalid: [0, 5]
+);
+
+try_from_round_trip!(
+    colorspace,
+    Colorspace,
+    [
+        Default,
+        SMPTE170M,
+        SMPTE240M,
+        Rec709,
+        NTSC,
+        EBUTech3212,
+        JPEG,
+        SRGB,
+        OPRGB,
+        Rec2020,
+        RAW,
+        DCIP3,
+    ],
+    // BT878 is deprecated
+    invalid: [4]
+);
+
+try_from_round_trip!(
+    field_order,
+    FieldOrder,
+    [
+        Any,
+        Progressive,
+        Top,
+        Bottom,
+        Interlaced,
+        SequentialTB,
+        SequentialBT,
+        Alternate,
+        InterlacedTB,
+        InterlacedBT,
+    ],
+    invalid: [10]
+);
+
+try_from_round_trip!(
+    quantization,
+    Quantization,
+    [Default, FullRange, LimitedRange],
+    invalid: [3]
+);
+
+try_from_round_trip!(
+    transfer_function,
+    TransferFunction,
+    [Default, Rec709, SRGB, OPRGB, SMPTE240M, None, DCIP3, SMPTE2084],
+    invalid: [8]
+);
+
+from_round_trip!(
+    control_type,
+    ControlType,
+    [
+        Integer,
+        Boolean,
+        Menu,
+        Button,
+        Integer64,
+        CtrlClass,
+        String,
+        Bitmask,
+        IntegerMenu,
+        U8,
+        U16,
+        U32,
+        Area,
+        Rect,
+    ],
+    codes: 0..0x1000
+);
+
+from_round_trip!(
+    event_type,
+    EventType,
+    [All, Vsync, Eos, Ctrl, FrameSync, SourceChange, MotionDet],
+    codes: (0..16).chain([0x08000000])
+);
+
+from_round_trip!(input_type, InputType, [Tuner, Camera, Touch], codes: 0..16);
+
+from_round_trip!(
+    output_type,
+    OutputType,
+    [Modulator, Analog, AnalogVgaOverlay],
+    codes: 0..16
+);
+
+from_round_trip!(
+    priority,
+    Priority,
+    [Background, Interactive, Record],
+    codes: 0..16
+);
+
+from_round_trip!(
+    tuner_type,
+    TunerType,
+    [Radio, AnalogTv, DigitalTv, Sdr, Rf],
+    codes: 0..16
+);
+
+from_round_trip!(
+    audio_mode,
+    AudioMode,
+    [Mono, Stereo, Lang2, Lang1, Lang1Lang2],
+    codes: 0..32
+);
@@ -1,4 +1,4 @@
-use std::{fmt, time};
+use std::{cmp, fmt, ops, time};
 
 use crate::v4l_sys::*;
 
@@ -26,6 +26,79 @@ impl Timestamp {
     pub fn new(sec: time_t, usec: time_t) -> Self {
         Timestamp { sec, usec }
     }
+
+    /// Returns the timestamp as duration since the epoch of its clock
+    ///
+    /// Microseconds beyond one second are carried over, negative timestamps saturate at zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use v4l::timestamp::Timestamp;
+    ///
+    /// assert_eq!(Timestamp::new(5, 250_000).as_duration(), Duration::from_millis(5250));
+    /// assert_eq!(Timestamp::new(1, 1_000_001).as_duration(), Duration::from_micros(2_000_001));
+    /// assert_eq!(Timestamp::new(-1, 0).as_duration(), Duration::ZERO);
+    /// ```
+    pub fn as_duration(&self) -> time::Duration {
+        time::Duration::from_micros(self.as_micros().max(0) as u64)
+    }
+
+    /// Returns the timestamp in microseconds
+    fn as_micros(&self) -> i128 {
+        self.sec as i128 * 1_000_000 + self.usec as i128
+    }
+}
+
+impl PartialEq for Timestamp {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_micros() == other.as_micros()
+    }
+}
+
+impl Eq for Timestamp {}
+
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Timestamps are ordered by the point in time they represent:
+///
+/// ```
+/// use v4l::timestamp::Timestamp;
+///
+/// assert!(Timestamp::new(1, 999_999) < Timestamp::new(2, 0));
+/// assert!(Timestamp::new(2, 0) > Timestamp::new(1, 999_999));
+/// assert_eq!(Timestamp::new(1, 1_000_000), Timestamp::new(2, 0));
+/// ```
+impl Ord for Timestamp {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.as_micros().cmp(&other.as_micros())
+    }
+}
+
+/// Returns the time elapsed between two timestamps
+///
+/// The result saturates at zero if the right hand side is later than the left hand side.
+///
+/// ```
+/// use std::time::Duration;
+/// use v4l::timestamp::Timestamp;
+///
+/// let delta = Timestamp::new(2, 0) - Timestamp::new(1, 999_000);
+/// assert_eq!(delta, Duration::from_millis(1));
+/// assert_eq!(Timestamp::new(1, 0) - Timestamp::new(2, 0), Duration::ZERO);
+/// ```
+impl ops::Sub for Timestamp {
+    type Output = time::Duration;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        let micros = (self.as_micros() - rhs.as_micros()).max(0);
+        time::Duration::from_micros(micros as u64)
+    }
 }
 
 impl fmt::Display for Timestamp {
@@ -0,0 +1,268 @@
+use std::fmt;
+
+use crate::fraction::Fraction;
+use crate::v4l_sys::*;
+
+bitflags::bitflags! {
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct Polarities : u32 {
+        const VSYNC_POS_POL         = 0x0001;
+        const HSYNC_POS_POL         = 0x0002;
+    }
+}
+
+impl From<u32> for Polarities {
+    fn from(flags: u32) -> Self {
+        Self::from_bits_retain(flags)
+    }
+}
+
+impl From<Polarities> for u32 {
+    fn from(flags: Polarities) -> Self {
+        flags.bits()
+    }
+}
+
+impl fmt::Display for Polarities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct Flags : u32 {
+        const REDUCED_BLANKING          = 0x0001;
+        const CAN_REDUCE_FPS            = 0x0002;
+        const REDUCED_FPS               = 0x0004;
+        const HALF_LINE                 = 0x0008;
+        const IS_CE_VIDEO               = 0x0010;
+        const FIRST_FIELD_EXTRA_LINE    = 0x0020;
+        const HAS_PICTURE_ASPECT        = 0x0040;
+        const HAS_CEA861_VIC            = 0x0080;
+        const HAS_HDMI_VIC              = 0x0100;
+        const CAN_DETECT_REDUCED_FPS    = 0x0200;
+    }
+}
+
+impl From<u32> for Flags {
+    fn from(flags: u32) -> Self {
+        Self::from_bits_retain(flags)
+    }
+}
+
+impl From<Flags> for u32 {
+    fn from(flags: Flags) -> Self {
+        flags.bits()
+    }
+}
+
+impl fmt::Display for Flags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// BT.656/BT.1120 timings as returned by [`crate::v4l2::vidioc::VIDIOC_QUERY_DV_TIMINGS`]
+pub struct BtTimings {
+    /// active width in pixels
+    pub width: u32,
+    /// active height in lines
+    pub height: u32,
+    /// whether the signal is interlaced
+    pub interlaced: bool,
+    /// positive sync polarities
+    pub polarities: Polarities,
+    /// pixel clock in Hz
+    pub pixelclock: u64,
+    /// horizontal front porch in pixels
+    pub hfrontporch: u32,
+    /// horizontal sync length in pixels
+    pub hsync: u32,
+    /// horizontal back porch in pixels
+    pub hbackporch: u32,
+    /// vertical front porch in lines
+    pub vfrontporch: u32,
+    /// vertical sync length in lines
+    pub vsync: u32,
+    /// vertical back porch in lines
+    pub vbackporch: u32,
+    /// vertical front porch of the bottom field in lines (interlaced only)
+    pub il_vfrontporch: u32,
+    /// vertical sync length of the bottom field in lines (interlaced only)
+    pub il_vsync: u32,
+    /// vertical back porch of the bottom field in lines (interlaced only)
+    pub il_vbackporch: u32,
+    /// video standards the timings belong to (V4L2_DV_BT_STD_*)
+    pub standards: u32,
+    /// timing flags
+    pub flags: Flags,
+    /// picture aspect ratio, valid if [`Flags::HAS_PICTURE_ASPECT`] is set
+    pub picture_aspect: Fraction,
+    /// CEA-861 video identification code, valid if [`Flags::HAS_CEA861_VIC`] is set
+    pub cea861_vic: u8,
+    /// HDMI video identification code, valid if [`Flags::HAS_HDMI_VIC`] is set
+    pub hdmi_vic: u8,
+}
+
+impl BtTimings {
+    /// Returns the total width (active pixels and horizontal blanking) of a line
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::timings::BtTimings;
+    /// use v4l::v4l_sys::v4l2_bt_timings;
+    ///
+    /// // 1080p60 as defined by CEA-861
+    /// let timings = BtTimings::from(v4l2_bt_timings {
+    ///     width: 1920,
+    ///     height: 1080,
+    ///     pixelclock: 148_500_000,
+    ///     hfrontporch: 88,
+    ///     hsync: 44,
+    ///     hbackporch: 148,
+    ///     vfrontporch: 4,
+    ///     vsync: 5,
+    ///     vbackporch: 36,
+    ///     ..unsafe { std::mem::zeroed() }
+    /// });
+    /// assert_eq!(timings.total_width(), 2200);
+    /// assert_eq!(timings.total_height(), 1125);
+    /// assert_eq!(timings.refresh_rate(), 60.0);
+    /// ```
+    pub fn total_width(&self) -> u32 {
+        self.width + self.hfrontporch + self.hsync + self.hbackporch
+    }
+
+    /// Returns the total height (active lines and vertical blanking) of a frame
+    ///
+    /// For interlaced signals, this includes the blanking of both fields.
+    pub fn total_height(&self) -> u32 {
+        let mut height = self.height + self.vfrontporch + self.vsync + self.vbackporch;
+        if self.interlaced {
+            height += self.il_vfrontporch + self.il_vsync + self.il_vbackporch;
+        }
+        height
+    }
+
+    /// Returns the number of frames per second
+    ///
+    /// Interlaced signals transmit twice as many fields per second. If the timings carry the
+    /// [`Flags::REDUCED_FPS`] flag, the rate is reduced by a factor of 1000 / 1001 (e.g. 59.94
+    /// instead of 60 frames per second).
+    pub fn refresh_rate(&self) -> f64 {
+        let total = u64::from(self.total_width()) * u64::from(self.total_height());
+        if total == 0 {
+            return 0.0;
+        }
+
+        let rate = self.pixelclock as f64 / total as f64;
+        if self.flags.contains(Flags::REDUCED_FPS) {
+            rate * 1000.0 / 1001.0
+        } else {
+            rate
+        }
+    }
+}
+
+impl fmt::Display for BtTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pol = |positive: bool| if positive { '+' } else { '-' };
+
+        writeln!(
+            f,
+            "active      : {}x{}{}{:.2}",
+            self.width,
+            self.height,
+            if self.interlaced { 'i' } else { 'p' },
+            self.refresh_rate()
+        )?;
+        writeln!(
+            f,
+            "total       : {}x{}",
+            self.total_width(),
+            self.total_height()
+        )?;
+        writeln!(f, "pixelclock  : {} Hz", self.pixelclock)?;
+        writeln!(
+            f,
+            "horizontal  : front porch {}, sync {} ({}), back porch {}",
+            self.hfrontporch,
+            self.hsync,
+            pol(self.polarities.contains(Polarities::HSYNC_POS_POL)),
+            self.hbackporch
+        )?;
+        writeln!(
+            f,
+            "vertical    : front porch {}, sync {} ({}), back porch {}",
+            self.vfrontporch,
+            self.vsync,
+            pol(self.polarities.contains(Polarities::VSYNC_POS_POL)),
+            self.vbackporch
+        )?;
+        if self.interlaced {
+            writeln!(
+                f,
+                "bottom field: front porch {}, sync {}, back porch {}",
+                self.il_vfrontporch, self.il_vsync, self.il_vbackporch
+            )?;
+        }
+        writeln!(f, "flags       : {}", self.flags)?;
+        Ok(())
+    }
+}
+
+impl From<v4l2_bt_timings> for BtTimings {
+    fn from(bt: v4l2_bt_timings) -> Self {
+        Self {
+            width: bt.width,
+            height: bt.height,
+            interlaced: bt.interlaced != 0,
+            polarities: Polarities::from(bt.polarities),
+            pixelclock: bt.pixelclock,
+            hfrontporch: bt.hfrontporch,
+            hsync: bt.hsync,
+            hbackporch: bt.hbackporch,
+            vfrontporch: bt.vfrontporch,
+            vsync: bt.vsync,
+            vbackporch: bt.vbackporch,
+            il_vfrontporch: bt.il_vfrontporch,
+            il_vsync: bt.il_vsync,
+            il_vbackporch: bt.il_vbackporch,
+            standards: bt.standards,
+            flags: Flags::from(bt.flags),
+            picture_aspect: Fraction::from(bt.picture_aspect),
+            cea861_vic: bt.cea861_vic,
+            hdmi_vic: bt.hdmi_vic,
+        }
+    }
+}
+
+impl From<BtTimings> for v4l2_bt_timings {
+    fn from(timings: BtTimings) -> Self {
+        Self {
+            width: timings.width,
+            height: timings.height,
+            interlaced: timings.interlaced as u32,
+            polarities: timings.polarities.into(),
+            pixelclock: timings.pixelclock,
+            hfrontporch: timings.hfrontporch,
+            hsync: timings.hsync,
+            hbackporch: timings.hbackporch,
+            vfrontporch: timings.vfrontporch,
+            vsync: timings.vsync,
+            vbackporch: timings.vbackporch,
+            il_vfrontporch: timings.il_vfrontporch,
+            il_vsync: timings.il_vsync,
+            il_vbackporch: timings.il_vbackporch,
+            standards: timings.standards,
+            flags: timings.flags.into(),
+            picture_aspect: timings.picture_aspect.into(),
+            cea861_vic: timings.cea861_vic,
+            hdmi_vic: timings.hdmi_vic,
+            ..unsafe { std::mem::zeroed() }
+        }
+    }
+}
@@ -0,0 +1,322 @@
+use std::{fmt, str};
+
+use crate::v4l_sys::*;
+
+/// Tuner type
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Type {
+    Radio,
+    AnalogTv,
+    DigitalTv,
+    Sdr,
+    Rf,
+    Unknown(u32),
+}
+
+/// Converts a raw tuner type code, unknown codes are preserved in [`Type::Unknown`]
+impl From<u32> for Type {
+    fn from(repr: u32) -> Self {
+        match repr {
+            1 => Self::Radio,
+            2 => Self::AnalogTv,
+            3 => Self::DigitalTv,
+            4 => Self::Sdr,
+            5 => Self::Rf,
+            repr => Self::Unknown(repr),
+        }
+    }
+}
+
+impl From<Type> for u32 {
+    fn from(t: Type) -> Self {
+        match t {
+            Type::Radio => 1,
+            Type::AnalogTv => 2,
+            Type::DigitalTv => 3,
+            Type::Sdr => 4,
+            Type::Rf => 5,
+            Type::Unknown(t) => t,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct Capabilities : u32 {
+        /// Frequencies are expressed in units of 62.5 Hz instead of 62.5 kHz
+        const LOW               = 0x00000001;
+        const NORM              = 0x00000002;
+        const HWSEEK_BOUNDED    = 0x00000004;
+        const HWSEEK_WRAP       = 0x00000008;
+        const STEREO            = 0x00000010;
+        const LANG2             = 0x00000020;
+        const SAP               = 0x00000020;
+        const LANG1             = 0x00000040;
+        const RDS               = 0x00000080;
+        const RDS_BLOCK_IO      = 0x00000100;
+        const RDS_CONTROLS      = 0x00000200;
+        const FREQ_BANDS        = 0x00000400;
+        const HWSEEK_PROG_LIM   = 0x00000800;
+        /// Frequencies are expressed in units of 1 Hz
+        const UNIT_1HZ          = 0x00001000;
+    }
+}
+
+impl From<u32> for Capabilities {
+    fn from(flags: u32) -> Self {
+        Self::from_bits_retain(flags)
+    }
+}
+
+impl From<Capabilities> for u32 {
+    fn from(flags: Capabilities) -> Self {
+        flags.bits()
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+bitflags::bitflags! {
+    /// Audio sub-channels present in the received signal
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct SubChannels : u32 {
+        const MONO      = 0x00000001;
+        const STEREO    = 0x00000002;
+        const LANG2     = 0x00000004;
+        const SAP       = 0x00000004;
+        const LANG1     = 0x00000008;
+        const RDS       = 0x00000010;
+    }
+}
+
+impl From<u32> for SubChannels {
+    fn from(flags: u32) -> Self {
+        Self::from_bits_retain(flags)
+    }
+}
+
+impl From<SubChannels> for u32 {
+    fn from(flags: SubChannels) -> Self {
+        flags.bits()
+    }
+}
+
+impl fmt::Display for SubChannels {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Audio mode, i.e. which sub-channels are decoded
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AudioMode {
+    Mono,
+    Stereo,
+    /// Second language, also used for the secondary audio program (SAP)
+    Lang2,
+    Lang1,
+    /// First language on the left, second language on the right channel
+    Lang1Lang2,
+    Unknown(u32),
+}
+
+/// Converts a raw audio mode code, unknown codes are preserved in [`AudioMode::Unknown`]
+impl From<u32> for AudioMode {
+    fn from(repr: u32) -> Self {
+        match repr {
+            0 => Self::Mono,
+            1 => Self::Stereo,
+            2 => Self::Lang2,
+            3 => Self::Lang1,
+            4 => Self::Lang1Lang2,
+            repr => Self::Unknown(repr),
+        }
+    }
+}
+
+impl From<AudioMode> for u32 {
+    fn from(mode: AudioMode) -> Self {
+        match mode {
+            AudioMode::Mono => 0,
+            AudioMode::Stereo => 1,
+            AudioMode::Lang2 => 2,
+            AudioMode::Lang1 => 3,
+            AudioMode::Lang1Lang2 => 4,
+            AudioMode::Unknown(mode) => mode,
+        }
+    }
+}
+
+impl fmt::Display for AudioMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Debug)]
+/// Tuner as returned by [`crate::v4l2::vidioc::VIDIOC_G_TUNER`]
+///
+/// Frequencies are given in tuner units, which depend on the capabilities: 62.5 kHz by default,
+/// 62.5 Hz with [`Capabilities::LOW`] (usually radio tuners) and 1 Hz with
+/// [`Capabilities::UNIT_1HZ`]. Use [`Tuner::to_hz`] and [`Tuner::from_hz`] to convert them.
+pub struct Tuner {
+    /// Index of the tuner, used to select it
+    pub index: u32,
+    /// Name of the tuner
+    pub name: String,
+    /// Type of the tuner
+    pub typ: Type,
+    /// Tuner capabilities
+    pub capabilities: Capabilities,
+    /// Lowest tunable frequency in tuner units
+    pub rangelow: u32,
+    /// Highest tunable frequency in tuner units
+    pub rangehigh: u32,
+    /// Audio sub-channels currently received
+    pub rxsubchans: SubChannels,
+    /// Current audio mode
+    pub audmode: AudioMode,
+    /// Signal strength, from 0 to 65535 if known
+    pub signal: i32,
+    /// Automatic frequency control, negative if the frequency is too low and positive if it
+    /// is too high
+    pub afc: i32,
+}
+
+impl Tuner {
+    /// Returns the tuner unit in Hz as (numerator, denominator)
+    fn unit(&self) -> (u64, u64) {
+        if self.capabilities.contains(Capabilities::UNIT_1HZ) {
+            (1, 1)
+        } else if self.capabilities.contains(Capabilities::LOW) {
+            (125, 2)
+        } else {
+            (62500, 1)
+        }
+    }
+
+    /// Returns a frequency in Hz
+    ///
+    /// # Arguments
+    ///
+    /// * `units` - Frequency in tuner units
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::device::Device;
+    ///
+    /// let dev = Device::new(0);
+    /// if let Ok(dev) = dev {
+    ///     if let Ok(tuners) = dev.enum_tuners() {
+    ///         for tuner in tuners {
+    ///             println!(
+    ///                 "{}: {} Hz - {} Hz",
+    ///                 tuner.name,
+    ///                 tuner.to_hz(tuner.rangelow),
+    ///                 tuner.to_hz(tuner.rangehigh)
+    ///             );
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn to_hz(&self, units: u32) -> u64 {
+        let (num, den) = self.unit();
+        units as u64 * num / den
+    }
+
+    /// Returns a frequency in tuner units, rounded to the nearest unit
+    ///
+    /// Frequencies which do not fit are clamped to the largest representable value. The result
+    /// is not checked against the range of the tuner.
+    ///
+    /// # Arguments
+    ///
+    /// * `hz` - Frequency in Hz
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::tuner::{AudioMode, Capabilities, SubChannels, Tuner, Type};
+    ///
+    /// let mut tuner = Tuner {
+    ///     index: 0,
+    ///     name: String::from("FM"),
+    ///     typ: Type::Radio,
+    ///     capabilities: Capabilities::LOW,
+    ///     rangelow: 1_400_000,
+    ///     rangehigh: 1_728_000,
+    ///     rxsubchans: SubChannels::STEREO,
+    ///     audmode: AudioMode::Stereo,
+    ///     signal: 0,
+    ///     afc: 0,
+    /// };
+    /// // 62.5 Hz units
+    /// assert_eq!(tuner.to_hz(tuner.rangelow), 87_500_000);
+    /// assert_eq!(tuner.from_hz(99_100_000), 1_585_600);
+    ///
+    /// // 62.5 kHz units
+    /// tuner.typ = Type::AnalogTv;
+    /// tuner.capabilities = Capabilities::NORM;
+    /// assert_eq!(tuner.to_hz(3_000), 187_500_000);
+    /// assert_eq!(tuner.from_hz(187_531_000), 3_000);
+    ///
+    /// // 1 Hz units
+    /// tuner.capabilities = Capabilities::UNIT_1HZ;
+    /// assert_eq!(tuner.from_hz(u64::MAX), u32::MAX);
+    /// ```
+    pub fn from_hz(&self, hz: u64) -> u32 {
+        let (num, den) = self.unit();
+        let units = (hz as u128 * den as u128 + num as u128 / 2) / num as u128;
+        units.min(u32::MAX as u128) as u32
+    }
+}
+
+impl fmt::Display for Tuner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "index        : {}", self.index)?;
+        writeln!(f, "name         : {}", self.name)?;
+        writeln!(f, "type         : {}", self.typ)?;
+        writeln!(f, "capabilities : {}", self.capabilities)?;
+        writeln!(
+            f,
+            "range        : {} Hz - {} Hz",
+            self.to_hz(self.rangelow),
+            self.to_hz(self.rangehigh)
+        )?;
+        writeln!(f, "subchannels  : {}", self.rxsubchans)?;
+        writeln!(f, "audio mode   : {}", self.audmode)?;
+        writeln!(f, "signal       : {}", self.signal)?;
+        Ok(())
+    }
+}
+
+impl From<v4l2_tuner> for Tuner {
+    fn from(tuner: v4l2_tuner) -> Self {
+        Self {
+            index: tuner.index,
+            name: str::from_utf8(&tuner.name)
+                .unwrap()
+                .trim_matches(char::from(0))
+                .to_string(),
+            typ: Type::from(tuner.type_),
+            capabilities: Capabilities::from(tuner.capability),
+            rangelow: tuner.rangelow,
+            rangehigh: tuner.rangehigh,
+            rxsubchans: SubChannels::from(tuner.rxsubchans),
+            audmode: AudioMode::from(tuner.audmode),
+            signal: tuner.signal,
+            afc: tuner.afc,
+        }
+    }
+}
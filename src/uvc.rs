@@ -0,0 +1,204 @@
+//! UVC metadata as delivered by the `UVCH` metadata format
+//!
+//! UVC devices usually expose a second video node which supports the
+//! [`crate::buffer::Type::MetaCapture`] buffer type. Each buffer captured from such a node
+//! contains one or more blocks laid out like this (all fields little endian, no padding):
+//!
+//! | Offset | Size | Field                                                                   |
+//! |--------|------|-------------------------------------------------------------------------|
+//! | 0      | 8    | system timestamp (`CLOCK_MONOTONIC`) in nanoseconds                     |
+//! | 8      | 2    | USB frame number (SOF) at the time the packet was received              |
+//! | 10     | 1    | length of the UVC payload header (`bHeaderLength`)                      |
+//! | 11     | 1    | UVC payload header flags (`bmHeaderInfo`)                               |
+//! | 12     | n    | remainder of the payload header (PTS, SCR, vendor data), `n = len - 2`  |
+//!
+//! The presentation time stamp (PTS, 4 bytes) follows if [`HeaderFlags::PTS`] is set, then the
+//! source clock reference (SCR, 6 bytes) if [`HeaderFlags::SCR`] is set. See section 2.4.3.3 of
+//! the USB Device Class Definition for Video Devices for details.
+
+use std::{convert::TryInto, fmt};
+
+use crate::format::FourCC;
+
+/// Four character code of the UVC metadata format
+pub const FOURCC: FourCC = FourCC { repr: *b"UVCH" };
+
+bitflags::bitflags! {
+    /// UVC payload header flags (`bmHeaderInfo`)
+    #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+    pub struct HeaderFlags: u8 {
+        /// Frame ID, toggles with each new frame
+        const FID   = 0x01;
+        /// End of frame
+        const EOF   = 0x02;
+        /// Presentation time stamp present
+        const PTS   = 0x04;
+        /// Source clock reference present
+        const SCR   = 0x08;
+        /// Payload specific bit
+        const RES   = 0x10;
+        /// Still image
+        const STI   = 0x20;
+        /// Error
+        const ERR   = 0x40;
+        /// End of header
+        const EOH   = 0x80;
+    }
+}
+
+impl From<u8> for HeaderFlags {
+    fn from(flags: u8) -> Self {
+        Self::from_bits_retain(flags)
+    }
+}
+
+impl From<HeaderFlags> for u8 {
+    fn from(flags: HeaderFlags) -> Self {
+        flags.bits()
+    }
+}
+
+impl fmt::Display for HeaderFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+/// Source clock reference
+pub struct Scr {
+    /// Source time clock in device clock units
+    pub stc: u32,
+    /// USB frame number (SOF) counter
+    pub sof: u16,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// A single UVC metadata block
+pub struct Metadata {
+    /// System timestamp (`CLOCK_MONOTONIC`) in nanoseconds
+    pub ns: u64,
+    /// USB frame number (SOF) at the time the packet was received
+    pub sof: u16,
+    /// Payload header flags
+    pub flags: HeaderFlags,
+    /// Presentation time stamp in device clock units
+    pub pts: Option<u32>,
+    /// Source clock reference
+    pub scr: Option<Scr>,
+}
+
+impl Metadata {
+    /// Parses the first metadata block of a buffer
+    ///
+    /// Returns `None` if the buffer is too short to hold a block or the block is malformed.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Bytes of a buffer captured in the `UVCH` format
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use v4l::uvc::{HeaderFlags, Metadata};
+    ///
+    /// let mut data = Vec::new();
+    /// data.extend_from_slice(&1_000_000u64.to_le_bytes());
+    /// data.extend_from_slice(&42u16.to_le_bytes());
+    /// data.push(12); // header length: length, flags, PTS and SCR
+    /// data.push((HeaderFlags::PTS | HeaderFlags::SCR | HeaderFlags::EOH).bits());
+    /// data.extend_from_slice(&0xdeadbeefu32.to_le_bytes());
+    /// data.extend_from_slice(&0x1234u32.to_le_bytes());
+    /// data.extend_from_slice(&7u16.to_le_bytes());
+    ///
+    /// let meta = Metadata::parse(&data).unwrap();
+    /// assert_eq!(meta.ns, 1_000_000);
+    /// assert_eq!(meta.sof, 42);
+    /// assert_eq!(meta.pts, Some(0xdeadbeef));
+    /// assert_eq!(meta.scr.unwrap().stc, 0x1234);
+    /// assert_eq!(meta.scr.unwrap().sof, 7);
+    /// assert!(Metadata::parse(&data[..11]).is_none());
+    /// ```
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        Self::parse_block(data).map(|(meta, _)| meta)
+    }
+
+    /// Parses all metadata blocks of a buffer
+    ///
+    /// Parsing stops at the first malformed block.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Bytes of a buffer captured in the `UVCH` format
+    pub fn parse_all(data: &[u8]) -> Vec<Self> {
+        let mut blocks = Vec::new();
+        let mut data = data;
+
+        while let Some((meta, len)) = Self::parse_block(data) {
+            blocks.push(meta);
+            data = &data[len..];
+        }
+
+        blocks
+    }
+
+    /// Parses a block, returning it along with the number of bytes it occupies
+    fn parse_block(data: &[u8]) -> Option<(Self, usize)> {
+        if data.len() < 12 {
+            return None;
+        }
+
+        let ns = u64::from_le_bytes(data[0..8].try_into().ok()?);
+        let sof = u16::from_le_bytes(data[8..10].try_into().ok()?);
+        let len = data[10] as usize;
+        let flags = HeaderFlags::from(data[11]);
+
+        // the header length includes the length and flags fields
+        if len < 2 || data.len() < 10 + len {
+            return None;
+        }
+
+        let mut header = &data[12..10 + len];
+        let pts = if flags.contains(HeaderFlags::PTS) {
+            let pts = u32::from_le_bytes(header.get(0..4)?.try_into().ok()?);
+            header = &header[4..];
+            Some(pts)
+        } else {
+            None
+        };
+        let scr = if flags.contains(HeaderFlags::SCR) {
+            Some(Scr {
+                stc: u32::from_le_bytes(header.get(0..4)?.try_into().ok()?),
+                sof: u16::from_le_bytes(header.get(4..6)?.try_into().ok()?),
+            })
+        } else {
+            None
+        };
+
+        Some((
+            Metadata {
+                ns,
+                sof,
+                flags,
+                pts,
+                scr,
+            },
+            10 + len,
+        ))
+    }
+}
+
+impl fmt::Display for Metadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "ns          : {}", self.ns)?;
+        writeln!(f, "sof         : {}", self.sof)?;
+        writeln!(f, "flags       : {}", self.flags)?;
+        if let Some(pts) = self.pts {
+            writeln!(f, "pts         : {}", pts)?;
+        }
+        if let Some(scr) = self.scr {
+            writeln!(f, "scr         : {} (sof {})", scr.stc, scr.sof)?;
+        }
+        Ok(())
+    }
+}
@@ -9,10 +9,31 @@ mod detail {
     use crate::v4l2::vidioc;
     use crate::v4l_sys::*;
     use std::convert::TryInto;
+    use std::io;
 
     pub unsafe fn open(path: *const std::os::raw::c_char, flags: i32) -> std::os::raw::c_int {
         v4l2_open(path, flags)
     }
+    pub unsafe fn open_raw(
+        path: *const std::os::raw::c_char,
+        flags: i32,
+    ) -> io::Result<std::os::raw::c_int> {
+        // Open the device ourselves and hand it to libv4l with format conversion disabled.
+        // libv4l still wraps the fd, so it has to be closed by v4l2_close() as usual.
+        let fd = libc::open(path, flags);
+        if fd == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let ret = v4l2_fd_open(fd, V4L2_DISABLE_CONVERSION as i32);
+        if ret == -1 {
+            // Grab the error before close() gets a chance to overwrite errno
+            let err = io::Error::last_os_error();
+            libc::close(fd);
+            return Err(err);
+        }
+        Ok(ret)
+    }
     pub unsafe fn close(fd: std::os::raw::c_int) -> std::os::raw::c_int {
         v4l2_close(fd)
     }
@@ -59,10 +80,21 @@ mod detail {
 #[cfg(feature = "v4l2-sys")]
 mod detail {
     use crate::v4l2::vidioc;
+    use std::io;
 
     pub unsafe fn open(path: *const std::os::raw::c_char, flags: i32) -> std::os::raw::c_int {
         libc::open(path, flags)
     }
+    pub unsafe fn open_raw(
+        path: *const std::os::raw::c_char,
+        flags: i32,
+    ) -> io::Result<std::os::raw::c_int> {
+        // Without libv4l, there is no format conversion to begin with.
+        match libc::open(path, flags) {
+            -1 => Err(io::Error::last_os_error()),
+            fd => Ok(fd),
+        }
+    }
     pub unsafe fn close(fd: std::os::raw::c_int) -> std::os::raw::c_int {
         libc::close(fd)
     }
@@ -138,6 +170,36 @@ pub fn open<P: AsRef<Path>>(path: P, flags: i32) -> io::Result<std::os::raw::c_i
     }
 }
 
+/// Opens a device with libv4l format conversion disabled.
+///
+/// When built with the `libv4l` feature, libv4l2 emulates common formats (such as RGB3 or YU12)
+/// on top of the formats natively supported by the hardware, converting every frame on the CPU.
+/// Devices opened by this function only expose their native formats. Without the `libv4l`
+/// feature, this is the same as [`open`].
+///
+/// Returns the file descriptor on success.
+/// In case of errors, the last OS error will be reported, aka errno on Linux.
+///
+/// # Arguments
+///
+/// * `path` - Path to the device node
+/// * `flags` - Open flags
+///
+/// # Example
+///
+/// ```
+/// extern crate v4l;
+///
+/// use v4l::v4l2;
+///
+/// let fd = v4l2::open_raw("/dev/video0", libc::O_RDWR);
+/// ```
+pub fn open_raw<P: AsRef<Path>>(path: P, flags: i32) -> io::Result<std::os::raw::c_int> {
+    let c_path = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+
+    unsafe { detail::open_raw(c_path.as_ptr(), flags) }
+}
+
 /// A convenience wrapper around v4l2_close.
 ///
 /// In case of errors, the last OS error will be reported, aka errno on Linux.
@@ -139,7 +139,8 @@ macro_rules! impl_format {
                     self.handle().fd(),
                     v4l2::vidioc::VIDIOC_G_FMT,
                     &mut v4l2_fmt as *mut _ as *mut std::os::raw::c_void,
-                )?;
+                )
+                .map_err(|e| crate::buffer::UnsupportedBufferType::map_format_error($typ, e))?;
 
                 Ok(Format::from(v4l2_fmt.fmt.pix))
             }
@@ -159,10 +160,32 @@ macro_rules! impl_set_format {
                     self.handle().fd(),
                     v4l2::vidioc::VIDIOC_S_FMT,
                     &mut v4l2_fmt as *mut _ as *mut std::os::raw::c_void,
-                )?;
+                )
+                .map_err(|e| crate::buffer::UnsupportedBufferType::map_format_error($typ, e))?;
             }
 
             self.format()
         }
     };
 }
+
+macro_rules! impl_try_format {
+    ($typ:expr) => {
+        fn try_format(&self, fmt: &Format) -> io::Result<Format> {
+            unsafe {
+                let mut v4l2_fmt = v4l2_format {
+                    type_: $typ as u32,
+                    fmt: v4l2_format__bindgen_ty_1 { pix: (*fmt).into() },
+                };
+                v4l2::ioctl(
+                    self.handle().fd(),
+                    v4l2::vidioc::VIDIOC_TRY_FMT,
+                    &mut v4l2_fmt as *mut _ as *mut std::os::raw::c_void,
+                )
+                .map_err(|e| crate::buffer::UnsupportedBufferType::map_format_error($typ, e))?;
+
+                Ok(Format::from(v4l2_fmt.fmt.pix))
+            }
+        }
+    };
+}
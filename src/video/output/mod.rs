@@ -10,6 +10,7 @@ use crate::format::FourCC;
 use crate::format::{Description as FormatDescription, Format};
 use crate::frameinterval::FrameInterval;
 use crate::framesize::FrameSize;
+use crate::parameters::Capabilities;
 use crate::v4l2;
 use crate::v4l_sys::*;
 use crate::video::traits::Output;
@@ -20,6 +21,7 @@ impl Output for Device {
     impl_enum_formats!(Type::VideoOutput);
     impl_format!(Type::VideoOutput);
     impl_set_format!(Type::VideoOutput);
+    impl_try_format!(Type::VideoOutput);
 
     fn params(&self) -> io::Result<Parameters> {
         unsafe {
@@ -38,6 +40,19 @@ impl Output for Device {
     }
 
     fn set_params(&self, params: &Parameters) -> io::Result<Parameters> {
+        // Without V4L2_CAP_TIMEPERFRAME, drivers silently ignore the requested interval.
+        let current = self.params()?;
+        let (req, cur) = (params.interval, current.interval);
+        if !current.capabilities.contains(Capabilities::TIME_PER_FRAME)
+            && u64::from(req.numerator) * u64::from(cur.denominator)
+                != u64::from(cur.numerator) * u64::from(req.denominator)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "device does not support setting the frame interval",
+            ));
+        }
+
         unsafe {
             let mut v4l2_params = v4l2_streamparm {
                 type_: Type::VideoOutput as u32,
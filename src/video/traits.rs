@@ -28,6 +28,9 @@ pub trait Capture {
     fn enum_formats(&self) -> io::Result<Vec<FormatDescription>>;
 
     /// Returns the format currently in use
+    ///
+    /// Fails with [`crate::buffer::UnsupportedBufferType`] (kind [`io::ErrorKind::Unsupported`])
+    /// if the device does not implement this buffer type.
     fn format(&self) -> io::Result<Format>;
 
     /// Modifies the capture format and returns the actual format
@@ -42,11 +45,32 @@ pub trait Capture {
     /// * `fmt` - Desired format
     fn set_format(&self, fmt: &Format) -> io::Result<Format>;
 
+    /// Checks a capture format and returns the format the driver would choose
+    ///
+    /// This works just like [`Capture::set_format`], but does not change the state of the device.
+    /// It can thus be used to probe format parameters before committing to one of them.
+    /// Implementations which cannot probe formats fail with [`io::ErrorKind::Unsupported`].
+    ///
+    /// # Arguments
+    ///
+    /// * `fmt` - Desired format
+    fn try_format(&self, _fmt: &Format) -> io::Result<Format> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "probing capture formats is not supported",
+        ))
+    }
+
     /// Returns the parameters currently in use
     fn params(&self) -> io::Result<CaptureParameters>;
 
     /// Modifies the capture parameters and returns the actual parameters
     ///
+    /// The frame interval can only be changed if the device advertises
+    /// [`crate::parameters::Capabilities::TIME_PER_FRAME`] in its current parameters. Otherwise,
+    /// requesting an interval other than the current one fails with an error of kind
+    /// [`io::ErrorKind::Unsupported`] instead of being silently ignored by the driver.
+    ///
     /// # Arguments
     ///
     /// * `params` - Desired parameters
@@ -74,6 +98,9 @@ pub trait Output {
     fn enum_formats(&self) -> io::Result<Vec<FormatDescription>>;
 
     /// Returns the format currently in use
+    ///
+    /// Fails with [`crate::buffer::UnsupportedBufferType`] (kind [`io::ErrorKind::Unsupported`])
+    /// if the device does not implement this buffer type.
     fn format(&self) -> io::Result<Format>;
 
     /// Modifies the capture format and returns the actual format
@@ -87,11 +114,32 @@ pub trait Output {
     /// * `fmt` - Desired format
     fn set_format(&self, fmt: &Format) -> io::Result<Format>;
 
+    /// Checks an output format and returns the format the driver would choose
+    ///
+    /// This works just like [`Output::set_format`], but does not change the state of the device.
+    /// It can thus be used to probe format parameters before committing to one of them.
+    /// Implementations which cannot probe formats fail with [`io::ErrorKind::Unsupported`].
+    ///
+    /// # Arguments
+    ///
+    /// * `fmt` - Desired format
+    fn try_format(&self, _fmt: &Format) -> io::Result<Format> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "probing output formats is not supported",
+        ))
+    }
+
     /// Returns the parameters currently in use
     fn params(&self) -> io::Result<OutputParameters>;
 
     /// Modifies the output parameters and returns the actual parameters
     ///
+    /// The frame interval can only be changed if the device advertises
+    /// [`crate::parameters::Capabilities::TIME_PER_FRAME`] in its current parameters. Otherwise,
+    /// requesting an interval other than the current one fails with an error of kind
+    /// [`io::ErrorKind::Unsupported`] instead of being silently ignored by the driver.
+    ///
     /// # Arguments
     ///
     /// * `params` - Desired parameters